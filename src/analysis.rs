@@ -0,0 +1,284 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::Video;
+
+pub mod commenters;
+pub mod duplicates;
+pub mod emoji;
+pub mod keywords;
+pub mod language;
+pub mod links;
+pub mod mentions;
+pub mod sentiment;
+pub mod spam;
+pub mod timestamps;
+pub mod wordcloud;
+
+/// Optional passes that enrich fetched comments before they are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Analysis {
+    /// Score each comment's sentiment and summarize it per video.
+    Sentiment,
+    /// Rank the most frequent words and phrases per video and across the channel.
+    Keywords,
+    /// Flag comments whose text repeats an earlier comment anywhere on the channel.
+    Duplicates,
+    /// Detect the language of each comment.
+    Language,
+    /// Score how likely each comment is to be spam, from 0 to 1, by repeated text, links and
+    /// common scam patterns.
+    Spam,
+    /// Draw a word cloud of the most frequent words across the channel, after stopword removal.
+    Wordcloud,
+    /// Extract the @handles mentioned in each comment and rank the most mentioned accounts.
+    Mentions,
+    /// Extract the links in each comment and rank the domains they go to, flagging URL
+    /// shorteners.
+    Links,
+    /// Extract timestamps such as 2:35 from each comment and rank the moments of each video
+    /// mentioned the most.
+    Timestamps,
+    /// Count how many videos each author commented on and when they were first and last active,
+    /// to tell the channel's core community from drive-by commenters.
+    Commenters,
+    /// Count the emoji used in comments per video and across the channel, and how many comments
+    /// are nothing but emoji.
+    Emoji,
+}
+
+/// Channel-wide results of the analyses, written to the run manifest.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnalysisReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<keywords::Keywords>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicates: Option<duplicates::DuplicateSummary>,
+    /// Number of fetched comments detected in each language, before any filters were applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub languages: Option<BTreeMap<String, usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spam: Option<spam::SpamSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mentions: Option<Vec<mentions::MentionCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<links::LinkSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commenters: Option<commenters::CommenterSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<emoji::EmojiUsage>,
+}
+
+impl AnalysisReport {
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_none()
+            && self.duplicates.is_none()
+            && self.languages.is_none()
+            && self.spam.is_none()
+            && self.mentions.is_none()
+            && self.links.is_none()
+            && self.commenters.is_none()
+            && self.emoji.is_none()
+    }
+}
+
+/// Runs the requested analyses over each video, accumulating channel-wide results as it goes.
+pub struct Analyzer {
+    analyses: Vec<Analysis>,
+    drop_duplicates: bool,
+    drop_spam: Option<f64>,
+    keywords: keywords::TermFrequencies,
+    duplicates: duplicates::DuplicateDetector,
+    languages: BTreeMap<String, usize>,
+    spam: spam::SpamDetector,
+    mentions: mentions::MentionExtractor,
+    links: links::LinkExtractor,
+    timestamps: timestamps::TimestampExtractor,
+    commenters: commenters::CommenterTracker,
+    emoji: emoji::EmojiCounter,
+    word_clouds: wordcloud::WordClouds,
+    word_cloud_dir: PathBuf,
+    word_cloud_per_video: bool,
+}
+
+impl Analyzer {
+    /// If `drop_duplicates` is set, duplicate detection runs even when not requested and the
+    /// duplicates it finds are removed, and the same goes for spam scoring and comments scoring at
+    /// least `drop_spam`.
+    pub fn new(analyses: &[Analysis], drop_duplicates: bool, drop_spam: Option<f64>) -> Self {
+        let mut analyses = analyses.to_vec();
+        if drop_duplicates {
+            analyses.push(Analysis::Duplicates);
+        }
+        if drop_spam.is_some() {
+            analyses.push(Analysis::Spam);
+        }
+        analyses.sort();
+        analyses.dedup();
+
+        // Duplicates and then spam run first so that dropped comments don't skew the other
+        // analyses.
+        analyses.sort_by_key(|a| match a {
+            Analysis::Duplicates => 0,
+            Analysis::Spam => 1,
+            _ => 2,
+        });
+
+        Self {
+            analyses,
+            drop_duplicates,
+            drop_spam,
+            keywords: Default::default(),
+            duplicates: Default::default(),
+            languages: Default::default(),
+            spam: Default::default(),
+            mentions: Default::default(),
+            links: Default::default(),
+            timestamps: Default::default(),
+            commenters: Default::default(),
+            emoji: Default::default(),
+            word_clouds: Default::default(),
+            word_cloud_dir: PathBuf::from("."),
+            word_cloud_per_video: false,
+        }
+    }
+
+    /// Write word clouds to `dir`, including one for every video if `per_video` is set.
+    pub fn with_word_clouds(mut self, dir: &Path, per_video: bool) -> Self {
+        self.word_cloud_dir = dir.to_path_buf();
+        self.word_cloud_per_video = per_video;
+        self
+    }
+
+    /// Run the analyses that filters depend on. These must run before filtering, while the rest
+    /// run after it in `analyze_video`.
+    pub fn tag_video(&mut self, video: &mut Video) {
+        if self.analyses.contains(&Analysis::Language) {
+            language::tag(video, &mut self.languages);
+        }
+    }
+
+    pub fn analyze_video(&mut self, video: &mut Video) -> Result<()> {
+        for analysis in &self.analyses {
+            match analysis {
+                Analysis::Sentiment => sentiment::analyze(video),
+                Analysis::Keywords => keywords::analyze(video, &mut self.keywords),
+                Analysis::Duplicates => self.duplicates.analyze(video, self.drop_duplicates),
+                Analysis::Language => {}
+                Analysis::Spam => self.spam.analyze(video, self.drop_spam),
+                Analysis::Wordcloud => {
+                    let per_video_dir = self
+                        .word_cloud_per_video
+                        .then_some(self.word_cloud_dir.as_path());
+                    self.word_clouds.analyze(video, per_video_dir)?;
+                }
+                Analysis::Mentions => self.mentions.analyze(video),
+                Analysis::Links => self.links.analyze(video),
+                Analysis::Timestamps => self.timestamps.analyze(video),
+                // Once the authors are final, in `analyze_authors`.
+                Analysis::Commenters => {}
+                Analysis::Emoji => self.emoji.analyze(video),
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the analyses that report who commented, once nothing else changes the authors, so that
+    /// with anonymizing they only ever see pseudonyms.
+    pub fn analyze_authors(&mut self, video: &Video) {
+        if self.analyses.contains(&Analysis::Commenters) {
+            self.commenters.analyze(video);
+        }
+    }
+
+    /// Write the results that cover the whole channel to files, once every video is analyzed.
+    pub fn finish(&self) -> Result<()> {
+        if self.analyses.contains(&Analysis::Wordcloud) {
+            self.word_clouds
+                .write(&self.word_cloud_dir.join("wordcloud.svg"))?;
+        }
+        Ok(())
+    }
+
+    /// Channel-wide results for every video analyzed so far.
+    pub fn report(&self) -> AnalysisReport {
+        let mut report = AnalysisReport::default();
+        for analysis in &self.analyses {
+            match analysis {
+                Analysis::Sentiment => {}
+                Analysis::Keywords => {
+                    report.keywords = Some(keywords::channel_keywords(&self.keywords))
+                }
+                Analysis::Duplicates => report.duplicates = Some(self.duplicates.summary()),
+                Analysis::Language => report.languages = Some(self.languages.clone()),
+                Analysis::Spam => report.spam = Some(self.spam.summary()),
+                Analysis::Wordcloud => {}
+                Analysis::Mentions => report.mentions = Some(self.mentions.summary()),
+                Analysis::Links => report.links = Some(self.links.summary()),
+                Analysis::Timestamps => {}
+                Analysis::Commenters => report.commenters = Some(self.commenters.summary()),
+                Analysis::Emoji => report.emoji = Some(self.emoji.summary()),
+            }
+        }
+        report
+    }
+}
+
+/// Normalized texts shorter than this are common reactions ("first", "great video") rather than
+/// copy-pasted spam, so they are never treated as repeats.
+const MIN_NORMALIZED_LEN: usize = 20;
+
+/// Lowercase the text and keep only letters and digits, so copies that differ in case,
+/// punctuation, whitespace or emoji normalize to the same string.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// A hash of the normalized text, which copies of a comment share, or None if it's too short to
+/// count as a copy.
+fn repeat_key(text: &str) -> Option<u64> {
+    let normalized = normalize(text);
+    if normalized.len() < MIN_NORMALIZED_LEN {
+        return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_identical_texts_share_a_key() {
+        let key = repeat_key("Check out my channel for free giveaways!");
+        assert!(key.is_some());
+        for copy in [
+            "check out my channel for FREE giveaways",
+            "Check out my channel, for free giveaways!!! 🎁🎁",
+            "  Check  out\nmy channel for free give-aways.",
+        ] {
+            assert_eq!(repeat_key(copy), key, "{copy}");
+        }
+        assert_ne!(repeat_key("Check out my channel for free wallpapers!"), key);
+    }
+
+    #[test]
+    fn short_texts_have_no_key() {
+        assert_eq!(repeat_key("First!!!"), None);
+        assert_eq!(repeat_key("Great video 👍👍👍👍👍👍👍👍👍👍"), None);
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use google_youtube3::chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::Video;
+
+/// Number of commenters active on the most videos kept for the channel.
+const TOP_COMMENTERS: usize = 100;
+
+/// Share of the videos analyzed a commenter must have commented on to be part of the core
+/// community.
+const CORE_SHARE: f64 = 0.25;
+
+/// How involved a commenter is with the channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Community {
+    /// Commented on at least a quarter of the videos, and on more than one.
+    Core,
+    /// Commented on more than one video, but fewer than the core.
+    Regular,
+    /// Commented on a single video.
+    DriveBy,
+}
+
+/// What one author did across the channel.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommenterActivity {
+    pub author_name: String,
+    pub author_channel_url: Option<String>,
+    /// Distinct videos the author commented or replied on.
+    pub videos: usize,
+    pub comments: usize,
+    pub replies: usize,
+    /// When the author's earliest comment or reply was posted, if the dates are known.
+    pub first_active: Option<DateTime<Utc>>,
+    /// When the author's latest comment or reply was posted, if the dates are known.
+    pub last_active: Option<DateTime<Utc>>,
+    pub community: Community,
+}
+
+/// How the channel's commenters split between its core community and drive-by commenters.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommenterSummary {
+    pub videos: usize,
+    pub commenters: usize,
+    /// Commenters on at least this many videos are part of the core community.
+    pub core_min_videos: usize,
+    pub core: usize,
+    pub regular: usize,
+    pub drive_by: usize,
+    /// The commenters active on the most videos, most first.
+    pub top_commenters: Vec<CommenterActivity>,
+}
+
+#[derive(Debug)]
+struct Activity {
+    author_name: String,
+    author_channel_url: Option<String>,
+    videos: usize,
+    /// Index of the last video counted in `videos`, so each video is counted once.
+    last_video: usize,
+    comments: usize,
+    replies: usize,
+    first_active: Option<DateTime<Utc>>,
+    last_active: Option<DateTime<Utc>>,
+}
+
+/// Follows each author across the channel's videos: how many they commented on and when they were
+/// first and last active. The channel's own comments aren't counted, since it isn't part of its
+/// audience.
+#[derive(Debug, Default)]
+pub struct CommenterTracker {
+    /// Activity of each author, keyed by channel id where available since display names aren't
+    /// unique.
+    authors: HashMap<String, Activity>,
+    videos: usize,
+}
+
+impl CommenterTracker {
+    pub fn analyze(&mut self, video: &Video) {
+        self.videos += 1;
+        for comment in &video.comments {
+            if !comment.is_owner {
+                self.count(
+                    comment.author_channel_id.as_deref(),
+                    &comment.author_name,
+                    &comment.author_channel_url,
+                    comment.published_at,
+                    false,
+                );
+            }
+            for child in comment.children.iter().filter(|child| !child.is_owner) {
+                self.count(
+                    child.author_channel_id.as_deref(),
+                    &child.author_name,
+                    &child.author_channel_url,
+                    child.published_at,
+                    true,
+                );
+            }
+        }
+    }
+
+    fn count(
+        &mut self,
+        channel_id: Option<&str>,
+        name: &str,
+        url: &Option<String>,
+        published_at: Option<DateTime<Utc>>,
+        reply: bool,
+    ) {
+        let video = self.videos;
+        let activity = self
+            .authors
+            .entry(channel_id.unwrap_or(name).to_string())
+            .or_insert_with(|| Activity {
+                author_name: name.to_string(),
+                author_channel_url: url.clone(),
+                videos: 0,
+                last_video: 0,
+                comments: 0,
+                replies: 0,
+                first_active: None,
+                last_active: None,
+            });
+        if activity.last_video != video {
+            activity.last_video = video;
+            activity.videos += 1;
+        }
+        if reply {
+            activity.replies += 1;
+        } else {
+            activity.comments += 1;
+        }
+        if let Some(at) = published_at {
+            activity.first_active = Some(activity.first_active.map_or(at, |first| first.min(at)));
+            activity.last_active = Some(activity.last_active.map_or(at, |last| last.max(at)));
+        }
+    }
+
+    /// How the commenters so far split between the core community and drive-by commenters.
+    pub fn summary(&self) -> CommenterSummary {
+        let core_min_videos = ((self.videos as f64 * CORE_SHARE).ceil() as usize).max(2);
+        let community = |videos: usize| {
+            if videos >= core_min_videos {
+                Community::Core
+            } else if videos > 1 {
+                Community::Regular
+            } else {
+                Community::DriveBy
+            }
+        };
+
+        let mut summary = CommenterSummary {
+            videos: self.videos,
+            commenters: self.authors.len(),
+            core_min_videos,
+            core: 0,
+            regular: 0,
+            drive_by: 0,
+            top_commenters: vec![],
+        };
+        for activity in self.authors.values() {
+            match community(activity.videos) {
+                Community::Core => summary.core += 1,
+                Community::Regular => summary.regular += 1,
+                Community::DriveBy => summary.drive_by += 1,
+            }
+        }
+
+        let mut ranked: Vec<&Activity> = self.authors.values().collect();
+        ranked.sort_by(|a, b| {
+            b.videos
+                .cmp(&a.videos)
+                .then_with(|| (b.comments + b.replies).cmp(&(a.comments + a.replies)))
+                .then_with(|| a.author_name.cmp(&b.author_name))
+        });
+        summary.top_commenters = ranked
+            .into_iter()
+            .take(TOP_COMMENTERS)
+            .map(|activity| CommenterActivity {
+                author_name: activity.author_name.clone(),
+                author_channel_url: activity.author_channel_url.clone(),
+                videos: activity.videos,
+                comments: activity.comments,
+                replies: activity.replies,
+                first_active: activity.first_active,
+                last_active: activity.last_active,
+                community: community(activity.videos),
+            })
+            .collect();
+        summary
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::repeat_key;
+use crate::Video;
+
+/// Number of most repeated texts included in the report.
+const TOP_REPEATED: usize = 25;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateSummary {
+    /// Comments whose text repeats an earlier comment's text.
+    pub duplicates: usize,
+    /// Duplicates removed from the output because `--dedup` was passed.
+    pub dropped: usize,
+    pub top_repeated: Vec<RepeatedText>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepeatedText {
+    /// Text of the first comment seen with this normalized text.
+    pub text: String,
+    /// Id of the first comment seen with this normalized text.
+    pub first_id: String,
+    pub count: usize,
+}
+
+/// Tracks every comment text seen during the run, keyed by a hash of its normalized form.
+#[derive(Debug, Default)]
+pub struct DuplicateDetector {
+    seen: HashMap<u64, RepeatedText>,
+    duplicates: usize,
+    dropped: usize,
+}
+
+impl DuplicateDetector {
+    /// Flag comments on the video that repeat an earlier comment with `duplicate_of`, removing them
+    /// instead if `drop` is set. Dropping a top level comment drops its replies with it.
+    pub fn analyze(&mut self, video: &mut Video, drop: bool) {
+        for comment in &mut video.comments {
+            comment.duplicate_of = self.check(&comment.id, &comment.text);
+            for child in &mut comment.children {
+                child.duplicate_of = self.check(&child.id, &child.text);
+            }
+
+            if drop {
+                let before = comment.children.len();
+                comment.children.retain(|c| c.duplicate_of.is_none());
+                self.dropped += before - comment.children.len();
+            }
+        }
+
+        if drop {
+            let before = video.comments.len();
+            video.comments.retain(|c| c.duplicate_of.is_none());
+            self.dropped += before - video.comments.len();
+        }
+    }
+
+    /// Returns the id of the first comment with the same normalized text, if this isn't it.
+    fn check(&mut self, id: &str, text: &str) -> Option<String> {
+        let key = repeat_key(text)?;
+        let entry = self.seen.entry(key).or_insert_with(|| RepeatedText {
+            text: text.to_string(),
+            first_id: id.to_string(),
+            count: 0,
+        });
+        entry.count += 1;
+
+        if entry.count > 1 {
+            self.duplicates += 1;
+            Some(entry.first_id.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn summary(&self) -> DuplicateSummary {
+        let mut top_repeated: Vec<RepeatedText> = self
+            .seen
+            .values()
+            .filter(|r| r.count > 1)
+            .cloned()
+            .collect();
+        top_repeated.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.text.cmp(&b.text)));
+        top_repeated.truncate(TOP_REPEATED);
+
+        DuplicateSummary {
+            duplicates: self.duplicates,
+            dropped: self.dropped,
+            top_repeated,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use emojis::SkinTone;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{text::as_emoji, Video};
+
+/// Number of most used emoji kept for each video.
+const VIDEO_EMOJI: usize = 10;
+/// Number of most used emoji kept for the whole channel.
+const CHANNEL_EMOJI: usize = 50;
+
+/// How often an emoji was used.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EmojiCount {
+    pub emoji: String,
+    /// The emoji's Unicode name, such as "face with tears of joy".
+    pub name: String,
+    /// Times it was used, counting each use in a comment.
+    pub count: usize,
+}
+
+/// How much emoji are used in the comments and replies of a video or channel.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EmojiUsage {
+    /// Comments and replies looked at.
+    pub comments: usize,
+    /// Comments and replies with at least one emoji.
+    pub with_emoji: usize,
+    /// Comments and replies made of nothing but emoji.
+    pub emoji_only: usize,
+    /// Share of comments and replies made of nothing but emoji, from 0 to 1.
+    pub emoji_only_ratio: f64,
+    /// The most used emoji, most first.
+    pub top: Vec<EmojiCount>,
+}
+
+/// Counts the emoji in comments, per video and across the channel. Emoji with a skin tone are
+/// counted with the emoji without one, since they're the same reaction.
+#[derive(Debug, Default)]
+pub struct EmojiCounter {
+    channel: Counts,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    comments: usize,
+    with_emoji: usize,
+    emoji_only: usize,
+    emoji: HashMap<&'static str, (&'static str, usize)>,
+}
+
+impl EmojiCounter {
+    /// Set `emoji` on the video, and add its emoji to the channel's.
+    pub fn analyze(&mut self, video: &mut Video) {
+        let mut counts = Counts::default();
+        for comment in &video.comments {
+            counts.add_text(&comment.text);
+            for child in &comment.children {
+                counts.add_text(&child.text);
+            }
+        }
+        video.emoji = Some(counts.usage(VIDEO_EMOJI));
+
+        self.channel.comments += counts.comments;
+        self.channel.with_emoji += counts.with_emoji;
+        self.channel.emoji_only += counts.emoji_only;
+        for (emoji, (name, count)) in counts.emoji {
+            self.channel.emoji.entry(emoji).or_insert((name, 0)).1 += count;
+        }
+    }
+
+    /// Emoji usage across every video so far.
+    pub fn summary(&self) -> EmojiUsage {
+        self.channel.usage(CHANNEL_EMOJI)
+    }
+}
+
+impl Counts {
+    fn add_text(&mut self, text: &str) {
+        let (mut emoji, mut other) = (0, false);
+        for grapheme in text.graphemes(true) {
+            match as_emoji(grapheme) {
+                Some(found) => {
+                    let base = found.with_skin_tone(SkinTone::Default).unwrap_or(found);
+                    self.emoji
+                        .entry(base.as_str())
+                        .or_insert((base.name(), 0))
+                        .1 += 1;
+                    emoji += 1;
+                }
+                // Emoji are often joined by spaces or followed by a variation selector on its own.
+                None if grapheme.trim().is_empty() || grapheme == "\u{fe0f}" => {}
+                None => other = true,
+            }
+        }
+        self.comments += 1;
+        if emoji > 0 {
+            self.with_emoji += 1;
+            if !other {
+                self.emoji_only += 1;
+            }
+        }
+    }
+
+    fn usage(&self, top: usize) -> EmojiUsage {
+        let mut ranked: Vec<EmojiCount> = self
+            .emoji
+            .iter()
+            .map(|(emoji, &(name, count))| EmojiCount {
+                emoji: emoji.to_string(),
+                name: name.to_string(),
+                count,
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.emoji.cmp(&b.emoji)));
+        ranked.truncate(top);
+        EmojiUsage {
+            comments: self.comments,
+            with_emoji: self.with_emoji,
+            emoji_only: self.emoji_only,
+            emoji_only_ratio: if self.comments == 0 {
+                0.0
+            } else {
+                self.emoji_only as f64 / self.comments as f64
+            },
+            top: ranked,
+        }
+    }
+}
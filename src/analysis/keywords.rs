@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::Video;
+
+/// Number of ranked terms kept for each video.
+const VIDEO_TERMS: usize = 25;
+/// Number of ranked terms kept for the whole channel.
+const CHANNEL_TERMS: usize = 100;
+
+#[rustfmt::skip]
+const STOPWORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any",
+    "are", "aren't", "as", "at", "be", "because", "been", "before", "being", "below", "between",
+    "both", "but", "by", "can", "can't", "could", "couldn't", "did", "didn't", "do", "does",
+    "doesn't", "doing", "don't", "down", "during", "each", "even", "few", "for", "from",
+    "further", "get", "got", "had", "hadn't", "has", "hasn't", "have", "haven't", "having",
+    "he", "he's", "her", "here", "hers", "herself", "him", "himself", "his", "how", "i", "i'm",
+    "i've", "if", "in", "into", "is", "isn't", "it", "it's", "its", "itself", "just", "let's",
+    "like", "me", "more", "most", "much", "my", "myself", "no", "nor", "not", "now", "of",
+    "off", "on", "once", "one", "only", "or", "other", "our", "ours", "ourselves", "out",
+    "over", "own", "really", "same", "she", "she's", "should", "shouldn't", "so", "some",
+    "such", "than", "that", "that's", "the", "their", "theirs", "them", "themselves", "then",
+    "there", "there's", "these", "they", "they're", "this", "those", "through", "to", "too",
+    "under", "until", "up", "us", "very", "was", "wasn't", "we", "we're", "were", "weren't",
+    "what", "what's", "when", "where", "which", "while", "who", "who's", "whom", "why", "will",
+    "with", "won't", "would", "wouldn't", "you", "you're", "you've", "your", "yours",
+    "yourself", "yourselves",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TermCount {
+    pub term: String,
+    pub count: usize,
+}
+
+/// Most frequent words and two-word phrases, excluding stopwords.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Keywords {
+    pub words: Vec<TermCount>,
+    pub bigrams: Vec<TermCount>,
+}
+
+#[derive(Debug, Default)]
+pub struct TermFrequencies {
+    words: HashMap<String, usize>,
+    bigrams: HashMap<String, usize>,
+}
+
+impl TermFrequencies {
+    fn add_text(&mut self, text: &str) {
+        let tokens = tokenize(text);
+        for token in tokens.iter().flatten() {
+            *self.words.entry(token.clone()).or_default() += 1;
+        }
+
+        // Only adjacent words are paired, so a stopword between two words breaks the phrase.
+        for pair in tokens.windows(2) {
+            if let [Some(first), Some(second)] = pair {
+                *self.bigrams.entry(format!("{first} {second}")).or_default() += 1;
+            }
+        }
+    }
+
+    fn merge(&mut self, other: &TermFrequencies) {
+        for (term, count) in &other.words {
+            *self.words.entry(term.clone()).or_default() += count;
+        }
+        for (term, count) in &other.bigrams {
+            *self.bigrams.entry(term.clone()).or_default() += count;
+        }
+    }
+
+    pub fn ranked(&self, top: usize) -> Keywords {
+        Keywords {
+            words: rank(&self.words, top),
+            bigrams: rank(&self.bigrams, top),
+        }
+    }
+}
+
+/// Rank the video's terms onto the video, and fold them into the channel-wide frequencies.
+pub fn analyze(video: &mut Video, channel: &mut TermFrequencies) {
+    let mut frequencies = TermFrequencies::default();
+    for comment in &video.comments {
+        frequencies.add_text(&comment.text);
+        for child in &comment.children {
+            frequencies.add_text(&child.text);
+        }
+    }
+
+    video.keywords = Some(frequencies.ranked(VIDEO_TERMS));
+    channel.merge(&frequencies);
+}
+
+pub fn channel_keywords(channel: &TermFrequencies) -> Keywords {
+    channel.ranked(CHANNEL_TERMS)
+}
+
+/// Split text into lowercase words. Stopwords and tokens too short or numeric to be meaningful are
+/// kept as `None` so callers can tell which words were adjacent.
+pub fn tokenize(text: &str) -> Vec<Option<String>> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’'))
+        .map(|t| t.trim_matches(|c| c == '\'' || c == '’'))
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            let token = t.to_lowercase().replace('’', "'");
+            let meaningful = token.chars().count() > 1
+                && !token.chars().all(|c| c.is_numeric())
+                && !is_stopword(&token);
+            meaningful.then_some(token)
+        })
+        .collect()
+}
+
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.binary_search(&word).is_ok()
+}
+
+fn rank(counts: &HashMap<String, usize>, top: usize) -> Vec<TermCount> {
+    let mut ranked: Vec<TermCount> = counts
+        .iter()
+        .map(|(term, &count)| TermCount {
+            term: term.clone(),
+            count,
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+    ranked.truncate(top);
+    ranked
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use whatlang::Lang;
+
+use crate::Video;
+
+/// ISO 639-1 codes for every language whatlang can detect.
+const LANGUAGE_CODES: &[(Lang, &str)] = &[
+    (Lang::Afr, "af"),
+    (Lang::Aka, "ak"),
+    (Lang::Amh, "am"),
+    (Lang::Ara, "ar"),
+    (Lang::Aze, "az"),
+    (Lang::Bel, "be"),
+    (Lang::Ben, "bn"),
+    (Lang::Bul, "bg"),
+    (Lang::Cat, "ca"),
+    (Lang::Ces, "cs"),
+    (Lang::Cmn, "zh"),
+    (Lang::Cym, "cy"),
+    (Lang::Dan, "da"),
+    (Lang::Deu, "de"),
+    (Lang::Ell, "el"),
+    (Lang::Eng, "en"),
+    (Lang::Epo, "eo"),
+    (Lang::Est, "et"),
+    (Lang::Fin, "fi"),
+    (Lang::Fra, "fr"),
+    (Lang::Guj, "gu"),
+    (Lang::Heb, "he"),
+    (Lang::Hin, "hi"),
+    (Lang::Hrv, "hr"),
+    (Lang::Hun, "hu"),
+    (Lang::Hye, "hy"),
+    (Lang::Ind, "id"),
+    (Lang::Ita, "it"),
+    (Lang::Jav, "jv"),
+    (Lang::Jpn, "ja"),
+    (Lang::Kan, "kn"),
+    (Lang::Kat, "ka"),
+    (Lang::Khm, "km"),
+    (Lang::Kor, "ko"),
+    (Lang::Lat, "la"),
+    (Lang::Lav, "lv"),
+    (Lang::Lit, "lt"),
+    (Lang::Mal, "ml"),
+    (Lang::Mar, "mr"),
+    (Lang::Mkd, "mk"),
+    (Lang::Mya, "my"),
+    (Lang::Nep, "ne"),
+    (Lang::Nld, "nl"),
+    (Lang::Nob, "nb"),
+    (Lang::Ori, "or"),
+    (Lang::Pan, "pa"),
+    (Lang::Pes, "fa"),
+    (Lang::Pol, "pl"),
+    (Lang::Por, "pt"),
+    (Lang::Ron, "ro"),
+    (Lang::Rus, "ru"),
+    (Lang::Sin, "si"),
+    (Lang::Slk, "sk"),
+    (Lang::Slv, "sl"),
+    (Lang::Sna, "sn"),
+    (Lang::Spa, "es"),
+    (Lang::Srp, "sr"),
+    (Lang::Swe, "sv"),
+    (Lang::Tam, "ta"),
+    (Lang::Tel, "te"),
+    (Lang::Tgl, "tl"),
+    (Lang::Tha, "th"),
+    (Lang::Tuk, "tk"),
+    (Lang::Tur, "tr"),
+    (Lang::Ukr, "uk"),
+    (Lang::Urd, "ur"),
+    (Lang::Uzb, "uz"),
+    (Lang::Vie, "vi"),
+    (Lang::Yid, "yi"),
+    (Lang::Zul, "zu"),
+];
+
+/// Parse an ISO 639-1 code for a language that can be detected.
+pub fn parse_language_code(code: &str) -> Result<String, String> {
+    let code = code.trim().to_lowercase();
+    if LANGUAGE_CODES.iter().any(|(_, c)| *c == code) {
+        Ok(code)
+    } else {
+        Err(format!("{code} is not a supported ISO 639-1 language code"))
+    }
+}
+
+/// Tag every comment and reply on the video with its detected language, counting how many
+/// comments were found in each language.
+pub fn tag(video: &mut Video, counts: &mut BTreeMap<String, usize>) {
+    for comment in &mut video.comments {
+        comment.language = detect(&comment.text);
+        for child in &mut comment.children {
+            child.language = detect(&child.text);
+        }
+    }
+
+    let languages = video
+        .comments
+        .iter()
+        .flat_map(|c| std::iter::once(&c.language).chain(c.children.iter().map(|c| &c.language)));
+    for language in languages {
+        let key = language.as_deref().unwrap_or("unknown");
+        *counts.entry(key.to_string()).or_default() += 1;
+    }
+}
+
+/// Returns the ISO 639-1 code of the text's language, or `None` if it can't be reliably detected.
+pub fn detect(text: &str) -> Option<String> {
+    let info = whatlang::detect(text).filter(|i| i.is_reliable())?;
+    LANGUAGE_CODES
+        .iter()
+        .find(|(lang, _)| *lang == info.lang())
+        .map(|(_, code)| code.to_string())
+}
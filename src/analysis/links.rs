@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde::Serialize;
+use url::Url;
+
+use crate::Video;
+
+/// Number of most linked domains kept for the channel.
+const TOP_DOMAINS: usize = 100;
+
+/// URL shorteners, which hide where a link goes and are favoured by scam comments.
+const SHORTENERS: &[&str] = &[
+    "bit.ly",
+    "buff.ly",
+    "cutt.ly",
+    "goo.gl",
+    "is.gd",
+    "ow.ly",
+    "rb.gy",
+    "rebrand.ly",
+    "shorturl.at",
+    "t.co",
+    "t.ly",
+    "tiny.cc",
+    "tinyurl.com",
+    "v.gd",
+];
+
+/// How often a domain was linked to across the channel.
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainCount {
+    pub domain: String,
+    pub links: usize,
+    /// Set for URL shorteners, which hide where the link goes.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub shortener: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkSummary {
+    /// Comments and replies with at least one link.
+    pub comments: usize,
+    /// Links to URL shorteners.
+    pub shortened: usize,
+    /// The most linked domains, most linked first.
+    pub domains: Vec<DomainCount>,
+}
+
+/// Finds links in comments, counting the domains they go to across the channel.
+#[derive(Debug)]
+pub struct LinkExtractor {
+    pattern: Regex,
+    comments: usize,
+    domains: HashMap<String, usize>,
+}
+
+impl Default for LinkExtractor {
+    fn default() -> Self {
+        Self {
+            // Links with a scheme or starting with www., or otherwise a domain followed by a path,
+            // so that words joined by a period aren't taken for links.
+            pattern: Regex::new(
+                r#"(?i)\b(?:https?://|www\.)[^\s<>"]+|\b[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}/[^\s<>"]*"#,
+            )
+            .unwrap(),
+            comments: 0,
+            domains: HashMap::new(),
+        }
+    }
+}
+
+impl LinkExtractor {
+    /// Set `links` on every comment and reply on the video, and count their domains.
+    pub fn analyze(&mut self, video: &mut Video) {
+        for comment in &mut video.comments {
+            comment.links = self.extract(&comment.text);
+            for child in &mut comment.children {
+                child.links = self.extract(&child.text);
+            }
+        }
+    }
+
+    /// The links in the text, in order.
+    fn extract(&mut self, text: &str) -> Vec<String> {
+        let links: Vec<String> = self
+            .pattern
+            .find_iter(text)
+            // Punctuation ending a sentence or closing brackets around a link isn't part of it.
+            .map(|m| {
+                m.as_str()
+                    .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '\''])
+            })
+            .map(str::to_string)
+            .collect();
+        if !links.is_empty() {
+            self.comments += 1;
+        }
+        for link in &links {
+            if let Some(domain) = domain(link) {
+                *self.domains.entry(domain).or_default() += 1;
+            }
+        }
+        links
+    }
+
+    pub fn summary(&self) -> LinkSummary {
+        let mut domains: Vec<DomainCount> = self
+            .domains
+            .iter()
+            .map(|(domain, &links)| DomainCount {
+                domain: domain.clone(),
+                links,
+                shortener: is_shortener(domain),
+            })
+            .collect();
+        let shortened = domains
+            .iter()
+            .filter(|d| d.shortener)
+            .map(|d| d.links)
+            .sum();
+        domains.sort_by(|a, b| b.links.cmp(&a.links).then_with(|| a.domain.cmp(&b.domain)));
+        domains.truncate(TOP_DOMAINS);
+        LinkSummary {
+            comments: self.comments,
+            shortened,
+            domains,
+        }
+    }
+}
+
+/// The lowercased host a link goes to, without any leading `www.`.
+fn domain(link: &str) -> Option<String> {
+    let url = if link.contains("://") {
+        Url::parse(link)
+    } else {
+        Url::parse(&format!("http://{link}"))
+    }
+    .ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").unwrap_or(&host).to_string())
+}
+
+fn is_shortener(domain: &str) -> bool {
+    SHORTENERS.contains(&domain)
+}
@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::Video;
+
+/// Number of most mentioned accounts kept for the channel.
+const TOP_MENTIONS: usize = 100;
+
+/// How often an account was mentioned across the channel.
+#[derive(Debug, Clone, Serialize)]
+pub struct MentionCount {
+    /// The handle, lowercased, since handles are case insensitive.
+    pub handle: String,
+    /// Comments and replies mentioning the account.
+    pub comments: usize,
+    /// Videos with at least one comment mentioning the account.
+    pub videos: usize,
+}
+
+/// Finds `@handle` mentions in comments, counting the accounts mentioned across the channel.
+#[derive(Debug)]
+pub struct MentionExtractor {
+    pattern: Regex,
+    /// Comments and videos mentioning each lowercased handle.
+    counts: HashMap<String, (usize, usize)>,
+}
+
+impl Default for MentionExtractor {
+    fn default() -> Self {
+        Self {
+            // Handles are letters, digits, underscores, hyphens and periods. Anything before the @
+            // other than whitespace or punctuation makes it part of a word, like an email address.
+            pattern: Regex::new(r"(?:^|[^\w@.])@(\w[\w.-]*)").unwrap(),
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl MentionExtractor {
+    /// Set `mentions` on every comment and reply on the video, and count them.
+    pub fn analyze(&mut self, video: &mut Video) {
+        let mut on_video = HashSet::new();
+        for comment in &mut video.comments {
+            comment.mentions = self.extract(&comment.text, &mut on_video);
+            for child in &mut comment.children {
+                child.mentions = self.extract(&child.text, &mut on_video);
+            }
+        }
+        for handle in on_video {
+            self.counts.entry(handle).or_default().1 += 1;
+        }
+    }
+
+    /// The handles mentioned in the text, as written and in order, without repeats.
+    fn extract(&mut self, text: &str, on_video: &mut HashSet<String>) -> Vec<String> {
+        let mut mentions: Vec<String> = vec![];
+        for captures in self.pattern.captures_iter(text) {
+            // A sentence ending right after a handle isn't part of it.
+            let handle = format!("@{}", captures[1].trim_end_matches(['.', '-']));
+            if mentions.iter().any(|m| m.eq_ignore_ascii_case(&handle)) {
+                continue;
+            }
+            let lowercase = handle.to_lowercase();
+            self.counts.entry(lowercase.clone()).or_default().0 += 1;
+            on_video.insert(lowercase);
+            mentions.push(handle);
+        }
+        mentions
+    }
+
+    /// The most mentioned accounts so far, most mentioned first.
+    pub fn summary(&self) -> Vec<MentionCount> {
+        let mut ranked: Vec<MentionCount> = self
+            .counts
+            .iter()
+            .map(|(handle, &(comments, videos))| MentionCount {
+                handle: handle.clone(),
+                comments,
+                videos,
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.comments
+                .cmp(&a.comments)
+                .then_with(|| a.handle.cmp(&b.handle))
+        });
+        ranked.truncate(TOP_MENTIONS);
+        ranked
+    }
+}
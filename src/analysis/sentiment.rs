@@ -0,0 +1,71 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use vader_sentiment::SentimentIntensityAnalyzer;
+
+use crate::Video;
+
+/// Compound scores at or beyond these thresholds are considered positive or negative, as recommended by VADER.
+const POSITIVE_THRESHOLD: f64 = 0.05;
+const NEGATIVE_THRESHOLD: f64 = -0.05;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Sentiment {
+    /// Normalized score between -1 (most negative) and 1 (most positive).
+    pub compound: f64,
+    pub positive: f64,
+    pub neutral: f64,
+    pub negative: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SentimentSummary {
+    /// Mean compound score of all comments and replies on the video.
+    pub mean_compound: f64,
+    pub positive_count: usize,
+    pub neutral_count: usize,
+    pub negative_count: usize,
+}
+
+/// Score every comment and reply on the video, and summarize the scores on the video itself.
+pub fn analyze(video: &mut Video) {
+    let analyzer = SentimentIntensityAnalyzer::new();
+    let mut scores = vec![];
+
+    for comment in &mut video.comments {
+        let sentiment = score(&analyzer, &comment.text);
+        scores.push(sentiment.compound);
+        comment.sentiment = Some(sentiment);
+
+        for child in &mut comment.children {
+            let sentiment = score(&analyzer, &child.text);
+            scores.push(sentiment.compound);
+            child.sentiment = Some(sentiment);
+        }
+    }
+
+    let mean_compound = if scores.is_empty() {
+        0.0
+    } else {
+        scores.iter().sum::<f64>() / scores.len() as f64
+    };
+    video.sentiment = Some(SentimentSummary {
+        mean_compound,
+        positive_count: scores.iter().filter(|&&s| s >= POSITIVE_THRESHOLD).count(),
+        neutral_count: scores
+            .iter()
+            .filter(|&&s| s > NEGATIVE_THRESHOLD && s < POSITIVE_THRESHOLD)
+            .count(),
+        negative_count: scores.iter().filter(|&&s| s <= NEGATIVE_THRESHOLD).count(),
+    });
+}
+
+fn score(analyzer: &SentimentIntensityAnalyzer, text: &str) -> Sentiment {
+    let scores = analyzer.polarity_scores(text);
+    let get = |key| scores.get(key).copied().unwrap_or_default();
+    Sentiment {
+        compound: get("compound"),
+        positive: get("pos"),
+        neutral: get("neu"),
+        negative: get("neg"),
+    }
+}
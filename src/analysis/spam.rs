@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde::Serialize;
+
+use super::{normalize, repeat_key};
+use crate::Video;
+
+/// Scores at or above this count as likely spam in the report.
+const LIKELY_SPAM: f64 = 0.5;
+/// Comments with fewer letters and digits than this besides their links are only links.
+const MIN_TEXT_BESIDES_LINKS: usize = 5;
+
+/// What each signal adds to the score, which is capped at 1.
+const SAME_TEXT_OTHER_AUTHOR: f64 = 0.5;
+const SAME_TEXT_SAME_AUTHOR: f64 = 0.25;
+const LINK_ONLY: f64 = 0.5;
+const HAS_LINK: f64 = 0.1;
+
+/// Patterns common in scam comments, such as impersonators asking to be messaged on WhatsApp,
+/// with what each adds to the score.
+const SCAM_PATTERNS: &[(&str, f64)] = &[
+    // A phone number in international format.
+    (r"\+\s?\d[\d\s().-]{7,}\d", 0.4),
+    (r"(?i)\b(whats\s?app|telegram|wickr|signal app)\b", 0.3),
+    (
+        r"(?i)\b(text|message|contact|reach|dm|write to)\s+(me|him|her|them|us)\b",
+        0.2,
+    ),
+    (
+        r"(?i)\b(invest(ment|ing)?|crypto|bitcoin|btc|forex|binary options|profits?)\b",
+        0.2,
+    ),
+    (
+        r"(?i)\b(giveaway|winner|you (have )?won|claim)\b.*\b(prize|gift|reward|iphone)\b",
+        0.3,
+    ),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpamSummary {
+    /// Comments scored at 0.5 or above.
+    pub likely_spam: usize,
+    /// Comments removed from the output because of `--drop-spam`.
+    pub dropped: usize,
+}
+
+/// Parse a spam score threshold, which has to be between 0 and 1.
+pub fn parse_threshold(s: &str) -> Result<f64, String> {
+    let threshold: f64 = s.parse().map_err(|_| format!("{s} is not a number"))?;
+    if (0.0..=1.0).contains(&threshold) {
+        Ok(threshold)
+    } else {
+        Err(format!("{s} is not between 0 and 1"))
+    }
+}
+
+/// Scores how likely each comment is to be spam, from 0 to 1, remembering every text seen during
+/// the run to catch the same text posted by several authors.
+#[derive(Debug)]
+pub struct SpamDetector {
+    /// The author of the first comment seen with each normalized text, keyed by its hash.
+    authors: HashMap<u64, String>,
+    link: Regex,
+    scam_patterns: Vec<(Regex, f64)>,
+    likely_spam: usize,
+    dropped: usize,
+}
+
+impl Default for SpamDetector {
+    fn default() -> Self {
+        Self {
+            authors: HashMap::new(),
+            link: Regex::new(r"(?i)\b(https?://|www\.)\S+").unwrap(),
+            scam_patterns: SCAM_PATTERNS
+                .iter()
+                .map(|(pattern, weight)| (Regex::new(pattern).unwrap(), *weight))
+                .collect(),
+            likely_spam: 0,
+            dropped: 0,
+        }
+    }
+}
+
+impl SpamDetector {
+    /// Set `spam_score` on every comment and reply on the video. With a threshold, comments scoring
+    /// at or above it are removed. Dropping a top level comment drops its replies with it.
+    pub fn analyze(&mut self, video: &mut Video, drop_threshold: Option<f64>) {
+        for comment in &mut video.comments {
+            let author = comment
+                .author_channel_id
+                .as_deref()
+                .unwrap_or(&comment.author_name);
+            comment.spam_score = Some(self.score(&comment.text, author));
+            for child in &mut comment.children {
+                let author = child
+                    .author_channel_id
+                    .as_deref()
+                    .unwrap_or(&child.author_name);
+                child.spam_score = Some(self.score(&child.text, author));
+            }
+
+            if let Some(threshold) = drop_threshold {
+                let before = comment.children.len();
+                comment
+                    .children
+                    .retain(|c| c.spam_score.unwrap_or_default() < threshold);
+                self.dropped += before - comment.children.len();
+            }
+        }
+
+        if let Some(threshold) = drop_threshold {
+            let before = video.comments.len();
+            video
+                .comments
+                .retain(|c| c.spam_score.unwrap_or_default() < threshold);
+            self.dropped += before - video.comments.len();
+        }
+    }
+
+    fn score(&mut self, text: &str, author: &str) -> f64 {
+        let mut score = 0.0;
+
+        if let Some(key) = repeat_key(text) {
+            match self.authors.get(&key) {
+                Some(first) if first != author => score += SAME_TEXT_OTHER_AUTHOR,
+                Some(_) => score += SAME_TEXT_SAME_AUTHOR,
+                None => {
+                    self.authors.insert(key, author.to_string());
+                }
+            }
+        }
+
+        if self.link.is_match(text) {
+            let besides_links = normalize(&self.link.replace_all(text, ""));
+            if besides_links.chars().count() < MIN_TEXT_BESIDES_LINKS {
+                score += LINK_ONLY;
+            } else {
+                score += HAS_LINK;
+            }
+        }
+
+        for (pattern, weight) in &self.scam_patterns {
+            if pattern.is_match(text) {
+                score += weight;
+            }
+        }
+
+        let score = (score.min(1.0) * 100.0).round() / 100.0;
+        if score >= LIKELY_SPAM {
+            self.likely_spam += 1;
+        }
+        score
+    }
+
+    pub fn summary(&self) -> SpamSummary {
+        SpamSummary {
+            likely_spam: self.likely_spam,
+            dropped: self.dropped,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::Video;
+
+/// Length of the windows nearby timestamps are grouped into, since "2:35" and "2:37" usually point
+/// at the same moment.
+const WINDOW_SECONDS: u32 = 10;
+/// Number of moments kept for each video.
+const TOP_MOMENTS: usize = 10;
+
+/// A part of the video that comments point to with timestamps.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Moment {
+    /// Offset of the start of the moment in the video, in seconds.
+    pub seconds: u32,
+    /// The start of the moment as it would be written in a comment, such as 1:02:10.
+    pub timestamp: String,
+    /// Comments and replies with a timestamp in the moment.
+    pub comments: usize,
+}
+
+/// Finds timestamps such as 2:35 or 1:02:10 in comments, written in ASCII digits.
+#[derive(Debug)]
+pub struct TimestampExtractor {
+    pattern: Regex,
+}
+
+impl Default for TimestampExtractor {
+    fn default() -> Self {
+        Self {
+            pattern: Regex::new(r"\b(?:([0-9]{1,2}):)?([0-9]{1,2}):([0-9]{2})\b").unwrap(),
+        }
+    }
+}
+
+impl TimestampExtractor {
+    /// Set `timestamps` on every comment and reply on the video, and rank the moments mentioned
+    /// the most onto the video.
+    pub fn analyze(&self, video: &mut Video) {
+        // Comments pointing at each window, keyed by its start.
+        let mut windows: BTreeMap<u32, usize> = BTreeMap::new();
+        let mut count = |timestamps: &[u32]| {
+            let starts: BTreeSet<u32> = timestamps.iter().map(|s| s - s % WINDOW_SECONDS).collect();
+            for start in starts {
+                *windows.entry(start).or_default() += 1;
+            }
+        };
+        for comment in &mut video.comments {
+            comment.timestamps = self.extract(&comment.text);
+            count(&comment.timestamps);
+            for child in &mut comment.children {
+                child.timestamps = self.extract(&child.text);
+                count(&child.timestamps);
+            }
+        }
+
+        let mut moments: Vec<Moment> = windows
+            .into_iter()
+            .map(|(seconds, comments)| Moment {
+                seconds,
+                timestamp: format_timestamp(seconds),
+                comments,
+            })
+            .collect();
+        moments.sort_by(|a, b| b.comments.cmp(&a.comments).then(a.seconds.cmp(&b.seconds)));
+        moments.truncate(TOP_MOMENTS);
+        video.moments = Some(moments);
+    }
+
+    /// The offsets in seconds of the timestamps in the text, in order. Minutes and seconds past 59
+    /// aren't timestamps, except for the minutes of videos under an hour.
+    fn extract(&self, text: &str) -> Vec<u32> {
+        self.pattern
+            .captures_iter(text)
+            .filter_map(|captures| {
+                let hours: Option<u32> = captures
+                    .get(1)
+                    .map(|h| h.as_str().parse())
+                    .transpose()
+                    .ok()?;
+                let minutes: u32 = captures[2].parse().ok()?;
+                let seconds: u32 = captures[3].parse().ok()?;
+                if seconds > 59 || (hours.is_some() && minutes > 59) {
+                    return None;
+                }
+                Some(hours.unwrap_or_default() * 3600 + minutes * 60 + seconds)
+            })
+            .collect()
+    }
+}
+
+fn format_timestamp(seconds: u32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_timestamps_in_order() {
+        let extractor = TimestampExtractor::default();
+        assert_eq!(
+            extractor.extract("at 2:35 and again at 1:02:10, not 12:3"),
+            [155, 3730]
+        );
+    }
+
+    #[test]
+    fn ignores_non_ascii_digits() {
+        let extractor = TimestampExtractor::default();
+        assert!(extractor.extract("٢:٣٥ ２:３５ ２:35").is_empty());
+        assert_eq!(extractor.extract("١٢ 2:35"), [155]);
+    }
+
+    #[test]
+    fn ignores_out_of_range_timestamps() {
+        let extractor = TimestampExtractor::default();
+        assert!(extractor.extract("2:60 1:60:00 99:99:99").is_empty());
+        // Videos under an hour can have minutes past 59.
+        assert_eq!(extractor.extract("75:00 99:59:59"), [4500, 359999]);
+        assert!(extractor.extract("123:45:67 1234:56").is_empty());
+    }
+
+    #[test]
+    fn formats_timestamps_like_comments() {
+        assert_eq!(format_timestamp(5), "0:05");
+        assert_eq!(format_timestamp(155), "2:35");
+        assert_eq!(format_timestamp(3730), "1:02:10");
+    }
+}
@@ -0,0 +1,142 @@
+use std::{collections::HashMap, fmt::Write as _, fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use super::keywords::tokenize;
+use crate::Video;
+
+/// Number of most frequent words placed in each cloud.
+const MAX_WORDS: usize = 100;
+const WIDTH: f64 = 1000.0;
+const HEIGHT: f64 = 600.0;
+const MIN_FONT_SIZE: f64 = 12.0;
+const MAX_FONT_SIZE: f64 = 80.0;
+/// Rough width of a character relative to the font size, for laying out words without measuring
+/// them in a real font.
+const CHAR_WIDTH: f64 = 0.6;
+/// Steps taken along the spiral looking for room for a word before it's left out.
+const MAX_STEPS: usize = 2000;
+const COLORS: &[&str] = &[
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+];
+
+/// Counts words across the channel, after stopword removal, to draw word clouds from.
+#[derive(Debug, Default)]
+pub struct WordClouds {
+    words: HashMap<String, usize>,
+}
+
+impl WordClouds {
+    /// Count the words on the video, and if `per_video_dir` is given, draw the video's own cloud
+    /// there as `wordcloud-<video id>.svg`.
+    pub fn analyze(&mut self, video: &Video, per_video_dir: Option<&Path>) -> Result<()> {
+        let mut words: HashMap<String, usize> = HashMap::new();
+        let texts = video
+            .comments
+            .iter()
+            .flat_map(|c| std::iter::once(&c.text).chain(c.children.iter().map(|c| &c.text)));
+        for text in texts {
+            for word in tokenize(text).into_iter().flatten() {
+                *words.entry(word).or_default() += 1;
+            }
+        }
+
+        if let Some(dir) = per_video_dir {
+            write(&words, &dir.join(format!("wordcloud-{}.svg", video.id)))?;
+        }
+        for (word, count) in words {
+            *self.words.entry(word).or_default() += count;
+        }
+        Ok(())
+    }
+
+    /// Draw the cloud of every video analyzed so far.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write(&self.words, path)
+    }
+}
+
+/// A word placed in the cloud, with its bounding box.
+struct Placed {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Placed {
+    fn overlaps(&self, other: &Placed) -> bool {
+        (self.x - other.x).abs() * 2.0 < self.width + other.width
+            && (self.y - other.y).abs() * 2.0 < self.height + other.height
+    }
+}
+
+/// Lay out the most frequent words from the center outwards along a spiral, sized by frequency,
+/// and write them as an SVG image. Words that don't fit are left out.
+fn write(words: &HashMap<String, usize>, path: &Path) -> Result<()> {
+    let mut ranked: Vec<(&String, &usize)> = words.iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    ranked.truncate(MAX_WORDS);
+    let max = ranked.first().map_or(1, |(_, count)| **count) as f64;
+    let min = ranked.last().map_or(1, |(_, count)| **count) as f64;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">"#
+    )?;
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+
+    let mut placed: Vec<Placed> = vec![];
+    for (i, (word, count)) in ranked.iter().enumerate() {
+        let scale = if max > min {
+            ((**count as f64).sqrt() - min.sqrt()) / (max.sqrt() - min.sqrt())
+        } else {
+            1.0
+        };
+        let font_size = MIN_FONT_SIZE + scale * (MAX_FONT_SIZE - MIN_FONT_SIZE);
+        let width = word.chars().count() as f64 * font_size * CHAR_WIDTH;
+        let height = font_size;
+
+        let spot = (0..MAX_STEPS).find_map(|step| {
+            let angle = step as f64 * 0.1;
+            let radius = 2.0 * angle;
+            let candidate = Placed {
+                x: WIDTH / 2.0 + radius * angle.cos() * (WIDTH / HEIGHT),
+                y: HEIGHT / 2.0 + radius * angle.sin(),
+                width,
+                height,
+            };
+            let inside = candidate.x - width / 2.0 >= 0.0
+                && candidate.x + width / 2.0 <= WIDTH
+                && candidate.y - height / 2.0 >= 0.0
+                && candidate.y + height / 2.0 <= HEIGHT;
+            (inside && !placed.iter().any(|p| p.overlaps(&candidate))).then_some(candidate)
+        });
+        let Some(spot) = spot else {
+            continue;
+        };
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="{font_size:.1}" fill="{}" text-anchor="middle" dominant-baseline="central"><title>{count}</title>{}</text>"#,
+            spot.x,
+            spot.y,
+            COLORS[i % COLORS.len()],
+            escape(word)
+        )?;
+        placed.push(spot);
+    }
+    writeln!(svg, "</svg>")?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Unable to create directory {}", dir.display()))?;
+    }
+    fs::write(path, svg).with_context(|| format!("Unable to write {}", path.display()))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,68 @@
+use std::io::Write;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::{
+    sink::{comment_records, CommentRecord, Sink},
+    Video,
+};
+
+/// Labeling tool whose import format is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationTool {
+    LabelStudio,
+    Doccano,
+}
+
+/// A Label Studio task, whose data is shown to labelers as `$text` and the other fields by name.
+#[derive(Serialize)]
+struct LabelStudioTask<'a> {
+    data: CommentRecord<'a>,
+}
+
+/// A doccano example. Fields other than `text` and `label` are kept as its metadata.
+#[derive(Serialize)]
+struct DoccanoExample<'a> {
+    #[serde(flatten)]
+    record: CommentRecord<'a>,
+    label: [&'a str; 0],
+}
+
+/// Writes a line of JSON for every comment and reply, as a task ready to be imported into a
+/// labeling tool, with the comment's text to label and the rest of its record alongside.
+pub struct AnnotationSink<W: Write> {
+    w: W,
+    tool: AnnotationTool,
+}
+
+impl<W: Write> AnnotationSink<W> {
+    pub fn new(tool: AnnotationTool, w: W) -> Self {
+        Self { w, tool }
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for AnnotationSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        for record in comment_records(video) {
+            match self.tool {
+                AnnotationTool::LabelStudio => {
+                    serde_json::to_writer(&mut self.w, &LabelStudioTask { data: record })?
+                }
+                AnnotationTool::Doccano => {
+                    serde_json::to_writer(&mut self.w, &DoccanoExample { record, label: [] })?
+                }
+            }
+            writeln!(self.w)?;
+        }
+        self.w.flush()?;
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.w.flush()?;
+        Ok(())
+    }
+}
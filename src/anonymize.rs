@@ -0,0 +1,101 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::Video;
+
+/// Number of hex characters of the salted hash kept in the pseudonym.
+const PSEUDONYM_LEN: usize = 16;
+
+/// Replaces author identifiers with salted hashes, so the same author gets the same pseudonym
+/// everywhere the salt is reused.
+pub struct Anonymizer {
+    salt: Vec<u8>,
+}
+
+impl Anonymizer {
+    /// Use the salt stored in `salt_file`, creating the file with a new random salt if it doesn't
+    /// exist yet. Without a salt file, a random salt is used so pseudonyms are only stable within
+    /// this run.
+    pub fn new(salt_file: Option<&str>) -> Result<Self> {
+        let salt = match salt_file {
+            Some(path) if Path::new(path).exists() => {
+                fs::read(path).with_context(|| format!("Unable to read salt file {path}"))?
+            }
+            Some(path) => {
+                let salt = random_salt();
+                fs::write(path, &salt)
+                    .with_context(|| format!("Unable to write salt file {path}"))?;
+                salt
+            }
+            None => random_salt(),
+        };
+
+        Ok(Self { salt })
+    }
+
+    /// Replace the display name and channel id of every author on the video with a pseudonym, and
+    /// remove their channel urls and avatars.
+    pub fn anonymize_video(&self, video: &mut Video) {
+        for comment in &mut video.comments {
+            let pseudonym = self.pseudonym(
+                comment
+                    .author_channel_id
+                    .as_deref()
+                    .unwrap_or(&comment.author_name),
+            );
+            comment.author_name = pseudonym.clone();
+            comment.author_channel_id = Some(pseudonym);
+            comment.author_channel_url = None;
+            comment.author_profile_image_url = None;
+            comment.author_handle = None;
+            comment.author_channel_title = None;
+
+            for child in &mut comment.children {
+                let pseudonym = self.pseudonym(
+                    child
+                        .author_channel_id
+                        .as_deref()
+                        .unwrap_or(&child.author_name),
+                );
+                child.author_name = pseudonym.clone();
+                child.author_channel_id = Some(pseudonym);
+                child.author_channel_url = None;
+                child.author_profile_image_url = None;
+                child.author_handle = None;
+                child.author_channel_title = None;
+            }
+        }
+
+        for message in video.live_chat.iter_mut().flatten() {
+            let pseudonym = self.pseudonym(
+                message
+                    .author_channel_id
+                    .as_deref()
+                    .unwrap_or(&message.author_name),
+            );
+            message.author_name = pseudonym.clone();
+            message.author_channel_id = Some(pseudonym);
+        }
+    }
+
+    fn pseudonym(&self, identifier: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.salt);
+        hasher.update(identifier.as_bytes());
+        let hash: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        format!("anon-{}", &hash[..PSEUDONYM_LEN])
+    }
+}
+
+fn random_salt() -> Vec<u8> {
+    let mut salt = vec![0; 32];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
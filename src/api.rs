@@ -0,0 +1,229 @@
+use std::{fmt, time::Duration};
+
+use anyhow::{anyhow, Result};
+use reqwest::{
+    header::{ETAG, IF_NONE_MATCH},
+    StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use tracing::{debug, warn};
+
+use crate::{cache::Entry, exit::Failure, systemd, Client};
+
+const BASE_URL: &str = "https://youtube.googleapis.com/youtube/v3";
+
+/// Times a request that timed out, was rate limited or failed with a server error is retried before giving up.
+const MAX_RETRIES: u32 = 4;
+/// Wait before the first retry, doubled for each one after.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// An error status returned by the API.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl ApiError {
+    /// Whether the request failed because the project behind the credential has run out of quota
+    /// for the day.
+    pub fn is_quota_exceeded(&self) -> bool {
+        self.status == StatusCode::FORBIDDEN
+            && self.has_reason(&["quotaExceeded", "dailyLimitExceeded"])
+    }
+
+    /// Whether the request was rejected for coming too fast, rather than for running out of
+    /// quota.
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == StatusCode::TOO_MANY_REQUESTS
+            || (self.status == StatusCode::FORBIDDEN
+                && self.has_reason(&["rateLimitExceeded", "userRateLimitExceeded"]))
+    }
+
+    /// Whether the request failed because the owner turned off comments on the video.
+    pub fn is_comments_disabled(&self) -> bool {
+        self.status == StatusCode::FORBIDDEN && self.has_reason(&["commentsDisabled"])
+    }
+
+    /// The first reason the error body gives, such as forbidden or videoNotFound.
+    pub fn reason(&self) -> Option<String> {
+        self.reasons().into_iter().next()
+    }
+
+    /// Whether the error body gives one of these reasons.
+    fn has_reason(&self, reasons: &[&str]) -> bool {
+        self.reasons()
+            .iter()
+            .any(|reason| reasons.contains(&reason.as_str()))
+    }
+
+    /// Every reason the error body gives, or none if it isn't a JSON error.
+    fn reasons(&self) -> Vec<String> {
+        #[derive(Deserialize)]
+        struct Body {
+            error: Error,
+        }
+        #[derive(Deserialize)]
+        struct Error {
+            #[serde(default)]
+            errors: Vec<Reason>,
+        }
+        #[derive(Deserialize)]
+        struct Reason {
+            reason: String,
+        }
+
+        serde_json::from_str::<Body>(&self.body).map_or(vec![], |body| {
+            body.error.errors.into_iter().map(|e| e.reason).collect()
+        })
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "API request failed with {}: {}", self.status, self.body)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Request a page from a list endpoint of the Data API. The generated client has no way to send
+/// If-None-Match, so list requests are made directly over HTTP, with the same credentials.
+/// Parameters with empty values are left out.
+///
+/// When the active credential runs out of quota, the same request is retried with the next one,
+/// so paging carries on from the same page token. Requests that time out or fail with a server
+/// error are retried after a growing wait, since those are usually brief outages. Requests that are
+/// rate limited are retried the same way, and make the client's throttle allow fewer at once.
+pub async fn list<T: DeserializeOwned>(
+    client: &Client,
+    endpoint: &str,
+    params: &[(&str, &str)],
+) -> Result<T> {
+    let params: Vec<_> = params
+        .iter()
+        .copied()
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    let cached = client.cache.as_ref().map(|cache| {
+        let path = cache.path(endpoint, &params);
+        let entry = cache.get(&path);
+        (cache, path, entry)
+    });
+
+    let mut retries = 0;
+    loop {
+        let _permit = match &client.throttle {
+            Some(throttle) => Some(throttle.acquire().await),
+            None => None,
+        };
+        let (index, credential) = client.credential();
+        let mut request = client
+            .http
+            .get(format!("{BASE_URL}/{endpoint}"))
+            .query(&params);
+        match &credential.api_key {
+            Some(key) => request = request.query(&[("key", key)]),
+            None => {
+                let token = credential
+                    .youtube
+                    .auth
+                    .get_token(&[client.scope.url()])
+                    .await
+                    .map_err(|e| {
+                        Failure::Auth.wrap(anyhow!("Unable to get an OAuth token: {e}"))
+                    })?;
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+            }
+        }
+        if let Some((_, _, Some(entry))) = &cached {
+            request = request.header(IF_NONE_MATCH, &entry.etag);
+        }
+
+        let response = match request.send().await {
+            Err(e) if e.is_timeout() && retries < MAX_RETRIES => {
+                retries += 1;
+                backoff(endpoint, retries, &e).await;
+                continue;
+            }
+            response => response?,
+        };
+        systemd::heartbeat();
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED {
+            if let Some((_, _, Some(entry))) = &cached {
+                debug!(endpoint, "Reusing cached page");
+                if let Some(archive) = &client.raw_archive {
+                    archive.put(endpoint, &params, &entry.body)?;
+                }
+                return Ok(serde_json::from_value(entry.body.clone())?);
+            }
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let error = ApiError { status, body };
+            if status.is_server_error() && retries < MAX_RETRIES {
+                retries += 1;
+                backoff(endpoint, retries, &error).await;
+                continue;
+            }
+            if error.is_rate_limited() && retries < MAX_RETRIES {
+                if let Some(throttle) = &client.throttle {
+                    throttle.throttled();
+                }
+                retries += 1;
+                backoff(endpoint, retries, &error).await;
+                continue;
+            }
+            if error.is_quota_exceeded() && client.rotate(index) {
+                warn!(
+                    exhausted = credential.name,
+                    next = client.credential().1.name,
+                    "Quota exceeded, switching credentials"
+                );
+                continue;
+            }
+            return Err(error.into());
+        }
+
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string);
+        let body = match response.bytes().await {
+            Err(e) if e.is_timeout() && retries < MAX_RETRIES => {
+                retries += 1;
+                backoff(endpoint, retries, &e).await;
+                continue;
+            }
+            body => body?,
+        };
+        if let Some(throttle) = &client.throttle {
+            throttle.succeeded();
+        }
+        let body: serde_json::Value = serde_json::from_slice(&body)?;
+        if let Some(archive) = &client.raw_archive {
+            archive.put(endpoint, &params, &body)?;
+        }
+        if let (Some((cache, path, _)), Some(etag)) = (&cached, etag) {
+            cache.put(
+                path,
+                &Entry {
+                    etag,
+                    body: body.clone(),
+                },
+            )?;
+        }
+        return Ok(serde_json::from_value(body)?);
+    }
+}
+
+/// Wait before making a failed request again for the `retry`th time.
+async fn backoff(endpoint: &str, retry: u32, error: &(dyn fmt::Display + Sync)) {
+    let wait = INITIAL_BACKOFF * 2u32.pow(retry - 1);
+    warn!(endpoint, retry, ?wait, %error, "Request failed, retrying");
+    tokio::time::sleep(wait).await;
+}
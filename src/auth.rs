@@ -0,0 +1,277 @@
+use std::{fs, future::Future, pin::Pin, sync::atomic::Ordering};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum};
+use google_youtube3::{
+    api::ChannelListResponse,
+    chrono::{Duration, Local},
+    oauth2::authenticator_delegate::InstalledFlowDelegate,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use url::Url;
+
+use crate::{
+    api, connect,
+    secrets::{self, SecretStore},
+    AccessScope, Client, ClientArgs, CredentialSource,
+};
+
+/// Where Google describes an access token: the scopes it was granted and when it expires.
+const TOKEN_INFO_URL: &str = "https://oauth2.googleapis.com/tokeninfo";
+
+#[derive(Args)]
+pub struct AuthArgs {
+    /// With check, sign in or refresh the token of every credential as usual, then print the scopes each token was granted and when it expires, and make a cheap API request with each, costing a unit of quota, to find credential problems before a long run. Exits with an error if any credential doesn't work.
+    #[arg(value_enum)]
+    action: Option<AuthAction>,
+
+    /// Delete the cached tokens first, to sign in again, such as with a different account.
+    #[arg(long)]
+    force: bool,
+
+    #[command(flatten)]
+    pub client: ClientArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum AuthAction {
+    /// Check that every credential works.
+    Check,
+}
+
+/// An access token as the token info endpoint describes it.
+#[derive(Debug, Deserialize)]
+struct TokenInfo {
+    /// The granted scopes, separated by spaces.
+    scope: String,
+    /// Seconds until the token expires, as a string.
+    expires_in: String,
+}
+
+/// Sign in with every client secret that doesn't have a cached token yet, prompting in the browser
+/// for each, so a later download or watch can run unattended.
+pub async fn run(args: &AuthArgs) -> Result<()> {
+    let sources = args.client.credential_sources();
+    if args.force {
+        for source in &sources {
+            if let CredentialSource::ClientSecret {
+                token_cache_name, ..
+            } = source
+            {
+                if args.client.secrets == SecretStore::Keyring {
+                    secrets::delete_tokens(token_cache_name)?;
+                } else if let Err(e) = fs::remove_file(token_cache_name) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        return Err(e).with_context(|| {
+                            format!("Unable to delete token cache {token_cache_name}")
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let client = connect(&args.client, AccessScope::Readonly).await?;
+    if args.action == Some(AuthAction::Check) {
+        return check(&client, &sources).await;
+    }
+
+    for source in &sources {
+        match source {
+            CredentialSource::ClientSecret {
+                name,
+                token_cache_name,
+            } => {
+                let name = if name.trim_start().starts_with('{') {
+                    "client secret JSON"
+                } else {
+                    name
+                };
+                if args.client.secrets == SecretStore::Keyring {
+                    println!("Signed in with {name}, token saved to the keyring");
+                } else {
+                    println!("Signed in with {name}, token cached in {token_cache_name}");
+                }
+            }
+            CredentialSource::ApiKey(_) => println!("API key needs no sign in"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Make a test request with every credential, after printing what each OAuth token was granted.
+async fn check(client: &Client, sources: &[CredentialSource]) -> Result<()> {
+    let mut failed = 0;
+    for (i, source) in sources.iter().enumerate() {
+        client.active.store(i, Ordering::Relaxed);
+        let credential = &client.credentials[i];
+        match source {
+            CredentialSource::ClientSecret {
+                name,
+                token_cache_name,
+            } => {
+                let name = if name.trim_start().starts_with('{') {
+                    "client secret JSON"
+                } else {
+                    name
+                };
+                println!("{} ({name}, token in {token_cache_name})", credential.name);
+                match token_info(client, i).await {
+                    Ok(info) => {
+                        println!("  Scopes:  {}", info.scope.replace(' ', ", "));
+                        match info.expires_in.parse() {
+                            Ok(seconds) => {
+                                let expires = Local::now() + Duration::seconds(seconds);
+                                println!(
+                                    "  Expires: {} (in {} minutes), and is refreshed as needed",
+                                    expires.format("%Y-%m-%d %H:%M"),
+                                    seconds / 60
+                                );
+                            }
+                            Err(_) => println!("  Expires: unknown"),
+                        }
+                    }
+                    Err(e) => {
+                        println!("  Unable to look up the token: {e:#}");
+                        failed += 1;
+                        continue;
+                    }
+                }
+            }
+            CredentialSource::ApiKey(_) => println!("{}", credential.name),
+        }
+
+        let result = test_request(client, source).await;
+        // A credential that's out of quota gets the request retried with the next one.
+        if client.credential().0 != i {
+            println!("  Test request: out of quota until midnight Pacific Time");
+            failed += 1;
+            continue;
+        }
+        match result {
+            Ok(found) => println!("  Test request: OK, {found}"),
+            Err(e) => {
+                println!("  Test request: failed, {e:#}");
+                failed += 1;
+            }
+        }
+    }
+    client.reset_credentials();
+
+    if failed > 0 {
+        bail!("{failed} of {} credentials don't work", sources.len());
+    }
+    Ok(())
+}
+
+/// What Google says about the access token of the credential at `index`.
+async fn token_info(client: &Client, index: usize) -> Result<TokenInfo> {
+    let token = client.credentials[index]
+        .youtube
+        .auth
+        .get_token(&[client.scope.url()])
+        .await
+        .map_err(|e| anyhow::anyhow!("Unable to get an OAuth token: {e}"))?
+        .context("No OAuth token was returned")?;
+    let response = client
+        .http
+        .get(TOKEN_INFO_URL)
+        .query(&[("access_token", &token)])
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.json().await?)
+}
+
+/// Make a request costing a unit of quota with the active credential, describing what it found:
+/// the signed in account's channel for OAuth, or just that the key was accepted.
+async fn test_request(client: &Client, source: &CredentialSource) -> Result<String> {
+    client.before_request().await;
+    match source {
+        CredentialSource::ClientSecret { .. } => {
+            let response: ChannelListResponse =
+                api::list(client, "channels", &[("part", "snippet"), ("mine", "true")]).await?;
+            let snippet = response
+                .items
+                .and_then(|items| items.into_iter().next())
+                .and_then(|channel| channel.snippet);
+            Ok(match snippet {
+                Some(snippet) => format!(
+                    "signed in as {}",
+                    snippet
+                        .custom_url
+                        .or(snippet.title)
+                        .unwrap_or_else(|| "a channel without a name".to_string())
+                ),
+                None => "signed in to an account without a YouTube channel".to_string(),
+            })
+        }
+        CredentialSource::ApiKey(_) => {
+            let _: Value =
+                api::list(client, "i18nRegions", &[("part", "snippet"), ("hl", "en")]).await?;
+            Ok("the API key was accepted".to_string())
+        }
+    }
+}
+
+/// How the authorization code gets back to the tool once the user has signed in.
+#[derive(Debug, Clone, Copy)]
+pub enum CodeReturn {
+    /// Listen on a local port for the browser to be redirected to, any free one if None.
+    Listen(Option<u16>),
+    /// Ask for the address the browser was sent to, or just the code in it, to be pasted in.
+    Paste,
+}
+
+/// Signs in without a local listener. The browser is redirected to localhost, where nothing is
+/// listening, so the page fails to load but the code is in its address, which is pasted back.
+pub struct PasteCode;
+
+/// The redirect URI registered by default for desktop OAuth clients.
+const PASTE_REDIRECT_URI: &str = "http://localhost";
+
+impl InstalledFlowDelegate for PasteCode {
+    fn redirect_uri(&self) -> Option<&str> {
+        Some(PASTE_REDIRECT_URI)
+    }
+
+    fn present_user_url<'a>(
+        &'a self,
+        url: &'a str,
+        _need_code: bool,
+    ) -> Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>> {
+        Box::pin(async move {
+            eprintln!("Open this link in a browser, on any machine, and sign in:\n\n{url}\n");
+            eprintln!("The browser then goes to a localhost page that doesn't load. Paste its address here, or just the code in it:");
+            let mut input = String::new();
+            BufReader::new(tokio::io::stdin())
+                .read_line(&mut input)
+                .await
+                .map_err(|e| format!("Unable to read the code: {e}"))?;
+            code_from(input.trim())
+        })
+    }
+}
+
+/// The authorization code in a pasted redirect address, or the code itself if that's what was
+/// pasted.
+fn code_from(input: &str) -> Result<String, String> {
+    if input.is_empty() {
+        return Err("No code was pasted".to_string());
+    }
+    if !input.contains("code=") {
+        return Ok(input.to_string());
+    }
+    let url = Url::parse(input).map_err(|e| format!("Unable to read the address: {e}"))?;
+    let mut params = url.query_pairs();
+    if let Some((_, error)) = params.clone().find(|(name, _)| name == "error") {
+        return Err(format!("Signing in failed: {error}"));
+    }
+    params
+        .find(|(name, _)| name == "code")
+        .map(|(_, code)| code.into_owned())
+        .ok_or_else(|| "The address has no code in it".to_string())
+}
@@ -0,0 +1,405 @@
+use std::{collections::HashSet, io::Write};
+
+use anyhow::Result;
+use base64::Engine;
+use clap::{Args, ValueHint};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+
+use crate::{output, report::comment_url, Video};
+
+#[derive(Args)]
+pub struct BrowseArgs {
+    /// Name of a JSON file previously written by this tool.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+}
+
+const HELP: &str = "↑↓ move  ←→/tab switch pane  enter expand  / search  y copy link  q quit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Videos,
+    Comments,
+}
+
+/// A line of the comment pane.
+#[derive(Debug, Clone, Copy)]
+enum Row {
+    Thread(usize),
+    Reply(usize, usize),
+}
+
+struct Browser {
+    videos: Vec<Video>,
+    /// Indices of the videos shown, which is every video unless searching.
+    visible: Vec<usize>,
+    video_state: ListState,
+    rows: Vec<Row>,
+    comment_state: ListState,
+    /// Threads whose replies are shown, by video and thread index.
+    expanded: HashSet<(usize, usize)>,
+    focus: Focus,
+    /// The search, lowercased.
+    query: String,
+    editing_query: bool,
+    status: String,
+}
+
+/// Open an output file in an interactive terminal browser.
+pub fn run(args: &BrowseArgs) -> Result<()> {
+    let videos = output::read(&args.input_name)?.videos;
+
+    let mut browser = Browser::new(videos);
+    let mut terminal = ratatui::init();
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Browser {
+    fn new(videos: Vec<Video>) -> Self {
+        let mut browser = Self {
+            visible: (0..videos.len()).collect(),
+            videos,
+            video_state: ListState::default(),
+            rows: vec![],
+            comment_state: ListState::default(),
+            expanded: HashSet::new(),
+            focus: Focus::Videos,
+            query: String::new(),
+            editing_query: false,
+            status: HELP.to_string(),
+        };
+        browser.select_video(0);
+        browser
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key)? {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns false once the browser should close.
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+        if self.editing_query {
+            match key.code {
+                KeyCode::Enter => {
+                    self.editing_query = false;
+                    self.apply_search();
+                }
+                KeyCode::Esc => {
+                    self.editing_query = false;
+                    self.query.clear();
+                    self.apply_search();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Char(c) => self.query.extend(c.to_lowercase()),
+                _ => {}
+            }
+            return Ok(true);
+        }
+
+        match key.code {
+            KeyCode::Char('q') => return Ok(false),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::Left | KeyCode::Char('h') => self.focus = Focus::Videos,
+            KeyCode::Right | KeyCode::Char('l') => self.focus_comments(),
+            KeyCode::Tab => match self.focus {
+                Focus::Videos => self.focus_comments(),
+                Focus::Comments => self.focus = Focus::Videos,
+            },
+            KeyCode::Enter | KeyCode::Char(' ') => match self.focus {
+                Focus::Videos => self.focus_comments(),
+                Focus::Comments => self.toggle_thread(),
+            },
+            KeyCode::Char('/') => {
+                self.editing_query = true;
+                self.query.clear();
+            }
+            KeyCode::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.apply_search();
+            }
+            KeyCode::Char('y') => self.copy_link()?,
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn current_video(&self) -> Option<usize> {
+        self.video_state
+            .selected()
+            .and_then(|i| self.visible.get(i))
+            .copied()
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        match self.focus {
+            Focus::Videos => {
+                let selected = offset(self.video_state.selected(), delta, self.visible.len());
+                self.select_video(selected);
+            }
+            Focus::Comments => {
+                let selected = offset(self.comment_state.selected(), delta, self.rows.len());
+                self.comment_state.select(Some(selected));
+            }
+        }
+    }
+
+    fn select_video(&mut self, index: usize) {
+        self.video_state
+            .select((!self.visible.is_empty()).then_some(index));
+        self.build_rows();
+        self.comment_state
+            .select((!self.rows.is_empty()).then_some(0));
+    }
+
+    fn focus_comments(&mut self) {
+        if !self.rows.is_empty() {
+            self.focus = Focus::Comments;
+        }
+    }
+
+    /// Lay out the comment pane for the current video. While searching, only threads with a
+    /// match are shown, along with every matching reply, unless the video's title matches.
+    fn build_rows(&mut self) {
+        self.rows.clear();
+        let Some(video_index) = self.current_video() else {
+            return;
+        };
+        let video = &self.videos[video_index];
+        let filtering = !self.query.is_empty() && !video.title.to_lowercase().contains(&self.query);
+        for (i, thread) in video.comments.iter().enumerate() {
+            let thread_matches = self.matches(&thread.author_name, &thread.text);
+            let reply_matches: Vec<bool> = thread
+                .children
+                .iter()
+                .map(|reply| self.matches(&reply.author_name, &reply.text))
+                .collect();
+            if filtering && !thread_matches && !reply_matches.contains(&true) {
+                continue;
+            }
+
+            self.rows.push(Row::Thread(i));
+            let expanded = self.expanded.contains(&(video_index, i));
+            for (j, reply_match) in reply_matches.into_iter().enumerate() {
+                if expanded || (filtering && reply_match) {
+                    self.rows.push(Row::Reply(i, j));
+                }
+            }
+        }
+    }
+
+    fn matches(&self, author_name: &str, text: &str) -> bool {
+        self.query.is_empty()
+            || text.to_lowercase().contains(&self.query)
+            || author_name.to_lowercase().contains(&self.query)
+    }
+
+    fn apply_search(&mut self) {
+        self.visible = (0..self.videos.len())
+            .filter(|&i| {
+                let video = &self.videos[i];
+                self.query.is_empty()
+                    || video.title.to_lowercase().contains(&self.query)
+                    || video.comments.iter().any(|thread| {
+                        self.matches(&thread.author_name, &thread.text)
+                            || thread
+                                .children
+                                .iter()
+                                .any(|reply| self.matches(&reply.author_name, &reply.text))
+                    })
+            })
+            .collect();
+        self.focus = Focus::Videos;
+        self.select_video(0);
+        self.status = if self.query.is_empty() {
+            HELP.to_string()
+        } else {
+            format!(
+                "{} videos match \"{}\". Esc clears the search.",
+                self.visible.len(),
+                self.query
+            )
+        };
+    }
+
+    fn toggle_thread(&mut self) {
+        let (Some(video), Some(row)) = (self.current_video(), self.selected_row()) else {
+            return;
+        };
+        let thread = match row {
+            Row::Thread(i) | Row::Reply(i, _) => i,
+        };
+        if !self.expanded.remove(&(video, thread)) {
+            self.expanded.insert((video, thread));
+        }
+        self.build_rows();
+        let position = self
+            .rows
+            .iter()
+            .position(|row| matches!(row, Row::Thread(i) if *i == thread));
+        self.comment_state.select(position);
+    }
+
+    fn selected_row(&self) -> Option<Row> {
+        self.comment_state
+            .selected()
+            .and_then(|i| self.rows.get(i))
+            .copied()
+    }
+
+    /// Copy the link to the selected comment with an OSC 52 escape sequence, which most terminals
+    /// pass on to the system clipboard, including over SSH.
+    fn copy_link(&mut self) -> Result<()> {
+        let (Some(video_index), Some(row)) = (self.current_video(), self.selected_row()) else {
+            return Ok(());
+        };
+        let video = &self.videos[video_index];
+        let (id, ..) = comment(video, row);
+        let link = comment_url(&video.id, id);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&link);
+        let mut stdout = std::io::stdout();
+        write!(stdout, "\x1b]52;c;{encoded}\x07")?;
+        stdout.flush()?;
+        self.status = format!("Copied {link}");
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, detail, status] = Layout::vertical([
+            Constraint::Min(5),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [videos_area, comments_area] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+
+        let highlight = Style::new().add_modifier(Modifier::REVERSED);
+        let videos: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| {
+                let video = &self.videos[i];
+                let replies: usize = video.comments.iter().map(|c| c.children.len()).sum();
+                ListItem::new(Line::from(vec![
+                    Span::raw(video.title.clone()),
+                    Span::raw(format!(" ({}+{})", video.comments.len(), replies)).dark_gray(),
+                ]))
+            })
+            .collect();
+        let videos = List::new(videos)
+            .block(pane("Videos", self.focus == Focus::Videos))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(videos, videos_area, &mut self.video_state);
+
+        let video_index = self.current_video();
+        let video = video_index.map(|i| &self.videos[i]);
+        let comments: Vec<ListItem> = match (video_index, video) {
+            (Some(video_index), Some(video)) => self
+                .rows
+                .iter()
+                .map(|&row| {
+                    let (_, author, likes, text) = comment(video, row);
+                    let prefix = match row {
+                        Row::Thread(i) if video.comments[i].children.is_empty() => "  ".to_string(),
+                        Row::Thread(i) if self.expanded.contains(&(video_index, i)) => {
+                            "- ".to_string()
+                        }
+                        Row::Thread(i) => format!("+{} ", video.comments[i].children.len()),
+                        Row::Reply(..) => "    ↳ ".to_string(),
+                    };
+                    let first_line = text.lines().next().unwrap_or_default();
+                    ListItem::new(Line::from(vec![
+                        Span::raw(prefix).dark_gray(),
+                        Span::raw(author.to_string()).bold(),
+                        Span::raw(format!(" ♥{likes} ")).dark_gray(),
+                        Span::raw(first_line.to_string()),
+                    ]))
+                })
+                .collect(),
+            _ => vec![],
+        };
+        let comments = List::new(comments)
+            .block(pane("Comments", self.focus == Focus::Comments))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(comments, comments_area, &mut self.comment_state);
+
+        let detail_text = match (video, self.selected_row()) {
+            (Some(video), Some(row)) => {
+                let (id, author, likes, text) = comment(video, row);
+                let mut lines = vec![
+                    Line::from(vec![
+                        Span::raw(author.to_string()).bold(),
+                        Span::raw(format!("  ♥{likes}  {}", comment_url(&video.id, id)))
+                            .dark_gray(),
+                    ]),
+                    Line::default(),
+                ];
+                lines.extend(text.lines().map(|line| Line::raw(line.to_string())));
+                lines
+            }
+            _ => vec![],
+        };
+        let detail_pane = Paragraph::new(detail_text)
+            .block(Block::bordered())
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail_pane, detail);
+
+        let status_line = if self.editing_query {
+            Line::from(vec![Span::raw("/").bold(), Span::raw(self.query.clone())])
+        } else {
+            Line::raw(self.status.clone()).dark_gray()
+        };
+        frame.render_widget(Paragraph::new(status_line), status);
+    }
+}
+
+fn pane(title: &str, focused: bool) -> Block<'_> {
+    let block = Block::bordered().title(title);
+    if focused {
+        block.border_style(Style::new().bold())
+    } else {
+        block
+    }
+}
+
+/// Move a selection by `delta`, staying within a list of `len` items.
+fn offset(selected: Option<usize>, delta: isize, len: usize) -> usize {
+    let selected = selected.unwrap_or(0) as isize;
+    (selected + delta).clamp(0, len.saturating_sub(1) as isize) as usize
+}
+
+/// The id, author, likes and text of a row's comment.
+fn comment(video: &Video, row: Row) -> (&str, &str, u32, &str) {
+    match row {
+        Row::Thread(i) => {
+            let c = &video.comments[i];
+            (&c.id, &c.author_name, c.like_count, &c.text)
+        }
+        Row::Reply(i, j) => {
+            let c = &video.comments[i].children[j];
+            (&c.id, &c.author_name, c.like_count, &c.text)
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use google_youtube3::chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    output::Header,
+    sink::{comment_records, CommentRecord},
+    Video,
+};
+
+/// An archive turned around to list what each commenter has written.
+#[derive(Serialize)]
+struct ByAuthor<'a> {
+    header: &'a Header,
+    /// Those who wrote the most first.
+    authors: Vec<Author<'a>>,
+}
+
+/// Everything one commenter wrote on the channel.
+#[derive(Serialize)]
+struct Author<'a> {
+    author_name: &'a str,
+    author_channel_id: Option<&'a str>,
+    author_channel_url: Option<&'a str>,
+    comments: usize,
+    replies: usize,
+    /// Videos they wrote at least one comment or reply on.
+    videos: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_published_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_published_at: Option<DateTime<Utc>>,
+    /// Their comments and replies across every video, oldest first.
+    items: Vec<CommentRecord<'a>>,
+}
+
+/// Write the comments and replies on the videos grouped by author to a JSON file.
+pub fn write(header: &Header, videos: &[Video], path: &Path, compact: bool) -> Result<()> {
+    // Authors are keyed by channel id where available since display names aren't unique.
+    let mut authors: HashMap<&str, Author> = HashMap::new();
+    for video in videos {
+        let mut on_video = vec![];
+        for record in comment_records(video) {
+            let key = record.author_channel_id.unwrap_or(record.author_name);
+            let author = authors.entry(key).or_insert_with(|| Author {
+                author_name: record.author_name,
+                author_channel_id: record.author_channel_id,
+                author_channel_url: record.author_channel_url,
+                comments: 0,
+                replies: 0,
+                videos: 0,
+                first_published_at: None,
+                last_published_at: None,
+                items: vec![],
+            });
+            if record.parent_id.is_some() {
+                author.replies += 1;
+            } else {
+                author.comments += 1;
+            }
+            if !on_video.contains(&key) {
+                on_video.push(key);
+                author.videos += 1;
+            }
+            if let Some(published_at) = record.published_at {
+                author.first_published_at = Some(
+                    author
+                        .first_published_at
+                        .map_or(published_at, |first| first.min(published_at)),
+                );
+                author.last_published_at = author.last_published_at.max(Some(published_at));
+            }
+            author.items.push(record);
+        }
+    }
+
+    let mut authors: Vec<Author> = authors.into_values().collect();
+    for author in &mut authors {
+        author
+            .items
+            .sort_by(|a, b| (a.published_at, a.comment_id).cmp(&(b.published_at, b.comment_id)));
+    }
+    authors.sort_by(|a, b| {
+        (b.comments + b.replies)
+            .cmp(&(a.comments + a.replies))
+            .then_with(|| a.author_name.cmp(b.author_name))
+    });
+
+    let by_author = ByAuthor { header, authors };
+    let file =
+        File::create(path).with_context(|| format!("Unable to create {}", path.display()))?;
+    let mut w = BufWriter::new(file);
+    if compact {
+        serde_json::to_writer(&mut w, &by_author)?;
+    } else {
+        serde_json::to_writer_pretty(&mut w, &by_author)?;
+    }
+    writeln!(w)?;
+    w.flush()?;
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// On-disk cache of list responses, keyed by endpoint and parameters. Each entry keeps the ETag
+/// YouTube sent with the response, so the next identical request can ask for the page only if it
+/// changed and reuse the stored one when YouTube answers 304 Not Modified.
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    pub etag: String,
+    pub body: serde_json::Value,
+}
+
+impl ResponseCache {
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Unable to create cache directory {}", dir.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self, endpoint: &str, params: &[(&str, &str)]) -> PathBuf {
+        let mut params = params.to_vec();
+        params.sort();
+        let mut hasher = Sha256::new();
+        hasher.update(endpoint.as_bytes());
+        for (name, value) in params {
+            hasher.update(format!("&{name}={value}").as_bytes());
+        }
+        let hash: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        self.dir.join(format!("{hash}.json"))
+    }
+
+    pub fn get(&self, path: &Path) -> Option<Entry> {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn put(&self, path: &Path, entry: &Entry) -> Result<()> {
+        fs::write(path, serde_json::to_vec(entry)?)
+            .with_context(|| format!("Unable to write cache entry {}", path.display()))
+    }
+}
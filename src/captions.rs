@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{
+    warnings::{WarningCode, Warnings},
+    watch_page,
+};
+
+/// One caption track of a video, in a single language.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CaptionTrack {
+    /// BCP-47 code of the track's language, e.g. "en" or "pt-BR".
+    pub language: String,
+    /// Name of the track as shown in the player.
+    pub name: Option<String>,
+    /// Whether the track was generated by automatic speech recognition.
+    pub auto_generated: bool,
+    pub segments: Vec<CaptionSegment>,
+}
+
+/// A line of captions and when it's shown.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CaptionSegment {
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    pub text: String,
+}
+
+/// Fetch every caption track of a video. The Data API only lets a video's owner download its
+/// captions, so tracks are read from the timedtext URLs the player uses instead. Records a warning
+/// and returns None if they couldn't be fetched.
+pub async fn fetch(
+    http_client: &reqwest::Client,
+    video_id: &str,
+    warnings: &mut Warnings,
+) -> Option<Vec<CaptionTrack>> {
+    match fetch_tracks(http_client, video_id).await {
+        Ok(tracks) => Some(tracks),
+        Err(e) => {
+            warnings.push(
+                WarningCode::CaptionsUnavailable,
+                Some(video_id),
+                None,
+                format!("Unable to fetch captions: {e:#}"),
+            );
+            None
+        }
+    }
+}
+
+async fn fetch_tracks(http_client: &reqwest::Client, video_id: &str) -> Result<Vec<CaptionTrack>> {
+    let page = watch_page::fetch(http_client, video_id).await?;
+    let player_response = watch_page::embedded_json(&page, "ytInitialPlayerResponse")
+        .context("Watch page has no ytInitialPlayerResponse")?;
+    // Videos without captions have no tracklist at all.
+    let Some(tracks) = player_response
+        .pointer("/captions/playerCaptionsTracklistRenderer/captionTracks")
+        .and_then(Value::as_array)
+    else {
+        return Ok(vec![]);
+    };
+
+    let mut caption_tracks = vec![];
+    for track in tracks {
+        let base_url = track["baseUrl"]
+            .as_str()
+            .context("Caption track has no URL")?;
+        let language = track["languageCode"]
+            .as_str()
+            .context("Caption track has no language")?;
+        let timed_text: Value = http_client
+            .get(format!("{base_url}&fmt=json3"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("Unable to parse {language} captions"))?;
+        let segments = parse_segments(&timed_text);
+        debug!(
+            video_id,
+            language,
+            segments = segments.len(),
+            "Fetched caption track"
+        );
+
+        caption_tracks.push(CaptionTrack {
+            language: language.to_string(),
+            name: track["name"]["simpleText"]
+                .as_str()
+                .or_else(|| track["name"]["runs"][0]["text"].as_str())
+                .map(str::to_string),
+            auto_generated: track["kind"].as_str() == Some("asr"),
+            segments,
+        });
+    }
+
+    Ok(caption_tracks)
+}
+
+/// Events without text, such as the window positioning at the start of a track, are skipped.
+fn parse_segments(timed_text: &Value) -> Vec<CaptionSegment> {
+    let events = timed_text["events"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    events
+        .iter()
+        .filter_map(|event| {
+            let text: String = event["segs"]
+                .as_array()?
+                .iter()
+                .filter_map(|seg| seg["utf8"].as_str())
+                .collect();
+            let text = text.trim();
+            if text.is_empty() {
+                return None;
+            }
+            Some(CaptionSegment {
+                start_ms: event["tStartMs"].as_u64().unwrap_or_default(),
+                duration_ms: event["dDurationMs"].as_u64().unwrap_or_default(),
+                text: text.to_string(),
+            })
+        })
+        .collect()
+}
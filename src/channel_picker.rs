@@ -0,0 +1,145 @@
+use std::io::{self, IsTerminal};
+
+use anyhow::{anyhow, bail, Result};
+use google_youtube3::api::{ChannelListResponse, SearchListResponse};
+use tracing::{debug, warn};
+
+use crate::{api, discover::SEARCH_COST, exit::Failure, quota, Client};
+
+/// Most channels a name search offers to pick from.
+const MAX_CANDIDATES: usize = 10;
+
+/// A channel that a handle or name could refer to.
+pub struct Candidate {
+    pub id: String,
+    pub title: String,
+    /// Missing for channels without a handle.
+    pub handle: Option<String>,
+    /// Missing for channels that hide it.
+    pub subscriber_count: Option<u64>,
+}
+
+impl Candidate {
+    fn describe(&self) -> String {
+        let handle = self
+            .handle
+            .as_deref()
+            .map(|h| format!(" ({h})"))
+            .unwrap_or_default();
+        let subscribers = self
+            .subscriber_count
+            .map_or("hidden".to_string(), |count| count.to_string());
+        format!(
+            "{}{handle}, {subscribers} subscribers, {}",
+            self.title, self.id
+        )
+    }
+}
+
+/// Look up the title, handle and subscriber count of each channel, 50 to a request, keeping the
+/// order they were given in. Channels YouTube doesn't know of are left out.
+pub async fn lookup(ids: &[String], client: &Client) -> Result<Vec<Candidate>> {
+    let mut candidates = vec![];
+    for chunk in ids.chunks(50) {
+        client.before_request().await;
+        let response: ChannelListResponse = api::list(
+            client,
+            "channels",
+            &[("part", "snippet,statistics"), ("id", &chunk.join(","))],
+        )
+        .await?;
+        debug!(
+            endpoint = "channels.list",
+            channels = chunk.len(),
+            "Fetched channels"
+        );
+
+        let mut found = response.items.unwrap_or_default();
+        for id in chunk {
+            let Some(index) = found.iter().position(|c| c.id.as_ref() == Some(id)) else {
+                continue;
+            };
+            let channel = found.swap_remove(index);
+            let snippet = channel.snippet.unwrap_or_default();
+            candidates.push(Candidate {
+                id: id.clone(),
+                title: snippet.title.unwrap_or_default(),
+                handle: snippet.custom_url,
+                subscriber_count: channel
+                    .statistics
+                    .filter(|s| s.hidden_subscriber_count != Some(true))
+                    .and_then(|s| s.subscriber_count),
+            });
+        }
+    }
+    Ok(candidates)
+}
+
+/// Find the channels whose names best match with search.list, most relevant first.
+pub async fn search(name: &str, client: &Client) -> Result<Vec<Candidate>> {
+    client.before_request().await;
+    quota::record(SEARCH_COST - 1);
+    let response: SearchListResponse = api::list(
+        client,
+        "search",
+        &[
+            ("part", "snippet"),
+            ("type", "channel"),
+            ("q", name),
+            ("maxResults", &MAX_CANDIDATES.to_string()),
+        ],
+    )
+    .await?;
+    debug!(
+        endpoint = "search.list",
+        name,
+        items = response.items.as_ref().map_or(0, |i| i.len()),
+        "Searched for channels"
+    );
+
+    let ids: Vec<String> = response
+        .items
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|result| result.id.and_then(|id| id.channel_id))
+        .collect();
+    lookup(&ids, client).await
+}
+
+/// Choose the channel meant by `query` from the candidates, most likely first. When there are
+/// several and stdin is a terminal, they're listed with their titles, subscriber counts and ids to
+/// pick from. Otherwise, so scheduled runs aren't held up, the first is taken with a warning.
+pub fn pick(query: &str, mut candidates: Vec<Candidate>) -> Result<Candidate> {
+    if candidates.is_empty() {
+        return Err(Failure::ChannelNotFound.wrap(anyhow!("No channel found for {query:?}")));
+    }
+    if candidates.len() == 1 {
+        return Ok(candidates.remove(0));
+    }
+    if !io::stdin().is_terminal() {
+        let others: Vec<String> = candidates[1..].iter().map(Candidate::describe).collect();
+        warn!(
+            query,
+            picked = candidates[0].describe(),
+            others = others.join("; "),
+            "Several channels match, taking the first"
+        );
+        return Ok(candidates.remove(0));
+    }
+
+    eprintln!("Several channels match {query:?}:");
+    for (i, candidate) in candidates.iter().enumerate() {
+        eprintln!("  {}. {}", i + 1, candidate.describe());
+    }
+    loop {
+        eprint!("Which channel? [1-{}] ", candidates.len());
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 || answer.trim().is_empty() {
+            bail!("No channel picked");
+        }
+        match answer.trim().parse::<usize>() {
+            Ok(n) if (1..=candidates.len()).contains(&n) => return Ok(candidates.remove(n - 1)),
+            _ => eprintln!("Enter a number from 1 to {}", candidates.len()),
+        }
+    }
+}
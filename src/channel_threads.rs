@@ -0,0 +1,104 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::Result;
+use google_youtube3::api::CommentThreadListResponse;
+use tracing::{debug, info};
+
+use crate::{api, Client, FetchOptions, ModerationStatus};
+
+/// The comment threads of a channel's videos, fetched together with allThreadsRelatedToChannelId
+/// and grouped by video. Each video's threads are handed out once, as its only page, so a video
+/// fetched again later, such as by watch mode, goes back to asking for its own threads.
+pub struct ChannelThreads {
+    videos: Mutex<HashMap<String, CommentThreadListResponse>>,
+}
+
+impl ChannelThreads {
+    /// Page through every thread related to the channel, keeping those on the videos given. Videos
+    /// nobody has commented on get an empty page, as do videos with comments turned off, which the
+    /// channel-wide listing doesn't tell apart.
+    pub async fn fetch(
+        client: &Client,
+        channel_id: &str,
+        video_ids: &[&str],
+        options: &FetchOptions,
+    ) -> Result<Self> {
+        let mut videos: HashMap<String, CommentThreadListResponse> = video_ids
+            .iter()
+            .map(|&id| {
+                let page = CommentThreadListResponse {
+                    items: Some(vec![]),
+                    ..Default::default()
+                };
+                (id.to_string(), page)
+            })
+            .collect();
+        let (api_text_format, _) = options.text_format.api_value();
+        let (mut pages, mut threads) = (0, 0);
+        let mut page_token = String::new();
+        loop {
+            client.before_request().await;
+            let response: CommentThreadListResponse = api::list(
+                client,
+                "commentThreads",
+                &[
+                    (
+                        "part",
+                        if options.no_replies {
+                            "snippet"
+                        } else {
+                            "snippet,replies"
+                        },
+                    ),
+                    ("textFormat", api_text_format),
+                    ("order", options.order.api_value()),
+                    ("allThreadsRelatedToChannelId", channel_id),
+                    ("maxResults", &client.page_size(100)),
+                    ("pageToken", &page_token),
+                    (
+                        "searchTerms",
+                        options.search_terms.as_deref().unwrap_or_default(),
+                    ),
+                    (
+                        "moderationStatus",
+                        options
+                            .moderation_status
+                            .map_or("", ModerationStatus::api_value),
+                    ),
+                ],
+            )
+            .await?;
+            debug!(
+                endpoint = "commentThreads.list",
+                channel_id,
+                page_token,
+                items = response.items.as_ref().map_or(0, |i| i.len()),
+                "Fetched page"
+            );
+            pages += 1;
+
+            for thread in response.items.unwrap_or_default() {
+                let video_id = thread.snippet.as_ref().and_then(|s| s.video_id.as_deref());
+                // Threads on videos that weren't picked, or on the channel itself, are dropped.
+                let Some(page) = video_id.and_then(|id| videos.get_mut(id)) else {
+                    continue;
+                };
+                page.items.get_or_insert_with(Vec::new).push(thread);
+                threads += 1;
+            }
+            match response.next_page_token {
+                Some(t) => page_token = t,
+                None => break,
+            }
+        }
+        info!(pages, threads, "Fetched the channel's comment threads");
+        Ok(Self {
+            videos: Mutex::new(videos),
+        })
+    }
+
+    /// The threads of a video, unless they weren't fetched or have been handed out already.
+    pub fn take(&self, video_id: &str) -> Option<CommentThreadListResponse> {
+        self.videos.lock().unwrap().remove(video_id)
+    }
+}
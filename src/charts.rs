@@ -0,0 +1,237 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use google_youtube3::chrono::{Duration, NaiveDate};
+use plotters::prelude::*;
+
+use crate::Video;
+
+/// Number of videos shown in the chart of the most commented videos.
+const TOP_VIDEOS: usize = 15;
+/// Longest video title shown before it is truncated.
+const MAX_TITLE_WIDTH: usize = 40;
+const SIZE: (u32, u32) = (1200, 700);
+
+const COMMENTS_COLOR: RGBColor = RGBColor(0x1f, 0x77, 0xb4);
+const REPLIES_COLOR: RGBColor = RGBColor(0xff, 0x7f, 0x0e);
+
+/// Comment and reply counts of one video.
+struct VideoCounts {
+    title: String,
+    comments: usize,
+    replies: usize,
+}
+
+/// Counts gathered as videos are written, to draw the charts from at the end of the run.
+#[derive(Default)]
+pub struct ChartData {
+    /// Comments and replies posted on each day, across the channel.
+    daily: BTreeMap<NaiveDate, usize>,
+    videos: Vec<VideoCounts>,
+}
+
+impl ChartData {
+    pub fn add(&mut self, video: &Video) {
+        let published = video.comments.iter().flat_map(|c| {
+            std::iter::once(c.published_at).chain(c.children.iter().map(|c| c.published_at))
+        });
+        for published_at in published.flatten() {
+            *self.daily.entry(published_at.date_naive()).or_default() += 1;
+        }
+        self.videos.push(VideoCounts {
+            title: video.title.clone(),
+            comments: video.comments.len(),
+            replies: video.comments.iter().map(|c| c.children.len()).sum(),
+        });
+    }
+
+    /// Write `activity.svg`, the comments and replies posted per day, `top_videos.svg`, the videos
+    /// with the most comments and replies, and `reply_ratio.svg`, each video's number of threads
+    /// against its replies per thread, to the directory.
+    pub fn write(&self, dir: &Path, channel_handle: &str) -> Result<()> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Unable to create directory {}", dir.display()))?;
+        let draw = |name: &str, result: Result<(), Box<dyn std::error::Error>>| {
+            result.map_err(|e| anyhow::anyhow!("Unable to draw {name}: {e}"))
+        };
+        draw(
+            "activity.svg",
+            self.activity(&dir.join("activity.svg"), channel_handle),
+        )?;
+        draw(
+            "top_videos.svg",
+            self.top_videos(&dir.join("top_videos.svg"), channel_handle),
+        )?;
+        draw(
+            "reply_ratio.svg",
+            self.reply_ratio(&dir.join("reply_ratio.svg"), channel_handle),
+        )?;
+        Ok(())
+    }
+
+    fn activity(
+        &self,
+        path: &Path,
+        channel_handle: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let root = SVGBackend::new(path, SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+        let (Some(first), Some(last)) = (self.daily.keys().next(), self.daily.keys().last()) else {
+            root.present()?;
+            return Ok(());
+        };
+        // A range of a single day can't be drawn.
+        let last = (*last).max(*first + Duration::days(1));
+        let max = self.daily.values().copied().max().unwrap_or_default();
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("Comments and replies per day on {channel_handle}"),
+                ("sans-serif", 24),
+            )
+            .margin(20)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(*first..last, 0..max + 1)?;
+        chart
+            .configure_mesh()
+            .x_label_formatter(&|date| date.format("%Y-%m-%d").to_string())
+            .y_desc("Comments and replies")
+            .draw()?;
+        chart.draw_series(LineSeries::new(
+            self.daily.iter().map(|(date, count)| (*date, *count)),
+            &COMMENTS_COLOR,
+        ))?;
+        root.present()?;
+        Ok(())
+    }
+
+    fn top_videos(
+        &self,
+        path: &Path,
+        channel_handle: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut videos: Vec<&VideoCounts> = self.videos.iter().collect();
+        videos.sort_by_key(|v| std::cmp::Reverse(v.comments + v.replies));
+        videos.truncate(TOP_VIDEOS);
+        // Drawn from the bottom up, so the most commented video goes last to end up on top.
+        videos.reverse();
+        let max = videos
+            .iter()
+            .map(|v| v.comments + v.replies)
+            .max()
+            .unwrap_or_default();
+
+        let root = SVGBackend::new(path, SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("Most commented videos on {channel_handle}"),
+                ("sans-serif", 24),
+            )
+            .margin(20)
+            .x_label_area_size(40)
+            .y_label_area_size(320)
+            .build_cartesian_2d(0..max + 1, (0..videos.len()).into_segmented())?;
+        chart
+            .configure_mesh()
+            .disable_y_mesh()
+            .y_labels(videos.len())
+            .y_label_formatter(&|segment| {
+                match segment {
+                    SegmentValue::CenterOf(i) => videos.get(*i).map(|v| truncate(&v.title)),
+                    _ => None,
+                }
+                .unwrap_or_default()
+            })
+            .x_desc("Comments and replies")
+            .draw()?;
+
+        let bar = |i: usize, from: usize, to: usize, color: RGBColor| {
+            let mut bar = Rectangle::new(
+                [
+                    (from, SegmentValue::Exact(i)),
+                    (to, SegmentValue::Exact(i + 1)),
+                ],
+                color.filled(),
+            );
+            bar.set_margin(4, 4, 0, 0);
+            bar
+        };
+        chart
+            .draw_series(
+                videos
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| bar(i, 0, v.comments, COMMENTS_COLOR)),
+            )?
+            .label("Comments")
+            .legend(|(x, y)| {
+                Rectangle::new([(x, y - 5), (x + 10, y + 5)], COMMENTS_COLOR.filled())
+            });
+        chart
+            .draw_series(
+                videos
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| bar(i, v.comments, v.comments + v.replies, REPLIES_COLOR)),
+            )?
+            .label("Replies")
+            .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], REPLIES_COLOR.filled()));
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::LowerRight)
+            .background_style(WHITE)
+            .border_style(BLACK)
+            .draw()?;
+        root.present()?;
+        Ok(())
+    }
+
+    fn reply_ratio(
+        &self,
+        path: &Path,
+        channel_handle: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let points: Vec<(usize, f64)> = self
+            .videos
+            .iter()
+            .filter(|v| v.comments > 0)
+            .map(|v| (v.comments, v.replies as f64 / v.comments as f64))
+            .collect();
+        let max_threads = points.iter().map(|p| p.0).max().unwrap_or_default();
+        let max_ratio = points.iter().map(|p| p.1).fold(0.0, f64::max);
+
+        let root = SVGBackend::new(path, SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("Replies per thread on {channel_handle}"),
+                ("sans-serif", 24),
+            )
+            .margin(20)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(0..max_threads + 1, 0.0..max_ratio.max(1.0) * 1.05)?;
+        chart
+            .configure_mesh()
+            .x_desc("Threads on the video")
+            .y_desc("Replies per thread")
+            .draw()?;
+        chart.draw_series(
+            points
+                .iter()
+                .map(|point| Circle::new(*point, 4, COMMENTS_COLOR.filled())),
+        )?;
+        root.present()?;
+        Ok(())
+    }
+}
+
+fn truncate(title: &str) -> String {
+    if title.chars().count() <= MAX_TITLE_WIDTH {
+        return title.to_string();
+    }
+    let truncated: String = title.chars().take(MAX_TITLE_WIDTH - 1).collect();
+    format!("{truncated}…")
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    warnings::{Warning, Warnings},
+    ChildComment, ParentComment, Video,
+};
+
+/// One line of the checkpoint journal.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Entry {
+    /// Always the first line, so a checkpoint isn't resumed against the wrong channel.
+    Channel(String),
+    Video(Box<Video>),
+    Warning(Warning),
+    /// A page of threads on a video still being fetched, with all of their replies.
+    Page {
+        video_id: String,
+        comments: Vec<ParentComment>,
+        /// Missing after the last page.
+        next_page_token: Option<String>,
+    },
+    /// A page of replies to a long thread on the page of threads being fetched.
+    Replies {
+        video_id: String,
+        thread_id: String,
+        replies: Vec<ChildComment>,
+        /// Missing after the last page.
+        next_page_token: Option<String>,
+    },
+}
+
+/// The borrowed form of [`Entry`], so recording a video doesn't need a copy of it.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum EntryRef<'a> {
+    Channel(&'a str),
+    Video(&'a Video),
+    Warning(&'a Warning),
+    Page {
+        video_id: &'a str,
+        comments: &'a [ParentComment],
+        next_page_token: Option<&'a str>,
+    },
+    Replies {
+        video_id: &'a str,
+        thread_id: &'a str,
+        replies: &'a [ChildComment],
+        next_page_token: Option<&'a str>,
+    },
+}
+
+/// What was fetched of a video before its fetch was cut short, to carry on from. Pages are
+/// journaled as they complete, so videos with enormous numbers of comments don't start over from
+/// the first page.
+#[derive(Debug, Clone)]
+pub struct PartialVideo {
+    /// Threads from every complete page, with their replies.
+    pub comments: Vec<ParentComment>,
+    /// Token of the first page of threads not yet complete, or None once every page is.
+    pub page_token: Option<String>,
+    /// Replies fetched so far to the long threads on that page, keyed by thread id, along with the
+    /// token of the next page of each, or None once every page is fetched.
+    pub replies: HashMap<String, (Vec<ChildComment>, Option<String>)>,
+}
+
+impl Default for PartialVideo {
+    /// A video nothing has been fetched of yet.
+    fn default() -> Self {
+        Self {
+            comments: vec![],
+            page_token: Some(String::new()),
+            replies: HashMap::new(),
+        }
+    }
+}
+
+impl PartialVideo {
+    fn add_page(&mut self, comments: &[ParentComment], next_page_token: Option<&str>) {
+        self.comments.extend_from_slice(comments);
+        self.page_token = next_page_token.map(str::to_string);
+        self.replies.clear();
+    }
+
+    fn add_replies(
+        &mut self,
+        thread_id: &str,
+        replies: &[ChildComment],
+        next_page_token: Option<&str>,
+    ) {
+        let thread = self.replies.entry(thread_id.to_string()).or_default();
+        thread.0.extend_from_slice(replies);
+        thread.1 = next_page_token.map(str::to_string);
+    }
+}
+
+/// A newline-delimited journal of every video fetched so far, along with the warnings recorded
+/// while fetching them. Each video is flushed as soon as it's recorded, so the journal survives
+/// the process being killed, and a later run can pick up where this one left off.
+pub struct Checkpoint {
+    w: BufWriter<File>,
+    warnings_recorded: usize,
+    /// What's been journaled of videos that haven't been recorded yet, keyed by id.
+    partial: HashMap<String, PartialVideo>,
+}
+
+impl Checkpoint {
+    /// Start a new journal, replacing any existing one.
+    pub fn create(path: &Path, channel_handle: &str) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Unable to create checkpoint {}", path.display()))?;
+        let mut checkpoint = Self {
+            w: BufWriter::new(file),
+            warnings_recorded: 0,
+            partial: HashMap::new(),
+        };
+        checkpoint.append(&EntryRef::Channel(channel_handle))?;
+        checkpoint.w.flush()?;
+        Ok(checkpoint)
+    }
+
+    /// Open an existing journal to replay the videos in it before recording more.
+    pub fn resume(path: &Path, channel_handle: &str) -> Result<Replay> {
+        let file = File::open(path)
+            .with_context(|| format!("Unable to open checkpoint {}", path.display()))?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        match serde_json::from_str(&line) {
+            Ok(Entry::Channel(channel)) if channel == channel_handle => {}
+            Ok(Entry::Channel(channel)) => {
+                bail!("Checkpoint is for {channel}, not {channel_handle}")
+            }
+            _ => bail!("Checkpoint {} is malformed", path.display()),
+        }
+
+        Ok(Replay {
+            path: path.to_path_buf(),
+            reader,
+            valid_len: line.len() as u64,
+            line,
+            warnings: vec![],
+            partial: HashMap::new(),
+        })
+    }
+
+    /// Record a fetched video, along with any warnings recorded since the last one.
+    pub fn record(&mut self, video: &Video, warnings: &Warnings) -> Result<()> {
+        self.append_warnings(warnings)?;
+        self.append(&EntryRef::Video(video))?;
+        self.w.flush()?;
+        self.partial.remove(&video.id);
+        Ok(())
+    }
+
+    /// Record a complete page of threads on a video still being fetched, along with any warnings
+    /// recorded since the last entry.
+    pub fn record_page(
+        &mut self,
+        video_id: &str,
+        comments: &[ParentComment],
+        next_page_token: Option<&str>,
+        warnings: &Warnings,
+    ) -> Result<()> {
+        self.append_warnings(warnings)?;
+        self.append(&EntryRef::Page {
+            video_id,
+            comments,
+            next_page_token,
+        })?;
+        self.w.flush()?;
+        self.partial
+            .entry(video_id.to_string())
+            .or_default()
+            .add_page(comments, next_page_token);
+        Ok(())
+    }
+
+    /// Record a page of replies to a long thread on the page of threads being fetched.
+    pub fn record_replies(
+        &mut self,
+        video_id: &str,
+        thread_id: &str,
+        replies: &[ChildComment],
+        next_page_token: Option<&str>,
+    ) -> Result<()> {
+        self.append(&EntryRef::Replies {
+            video_id,
+            thread_id,
+            replies,
+            next_page_token,
+        })?;
+        self.w.flush()?;
+        self.partial
+            .entry(video_id.to_string())
+            .or_default()
+            .add_replies(thread_id, replies, next_page_token);
+        Ok(())
+    }
+
+    /// What's been journaled of the video so far, if it hasn't been recorded yet, to carry on
+    /// fetching it from.
+    pub fn partial(&self, video_id: &str) -> Option<PartialVideo> {
+        self.partial.get(video_id).cloned()
+    }
+
+    fn append_warnings(&mut self, warnings: &Warnings) -> Result<()> {
+        for warning in &warnings.as_slice()[self.warnings_recorded..] {
+            self.append(&EntryRef::Warning(warning))?;
+        }
+        self.warnings_recorded = warnings.len();
+        Ok(())
+    }
+
+    fn append(&mut self, entry: &EntryRef) -> Result<()> {
+        serde_json::to_writer(&mut self.w, entry)?;
+        writeln!(self.w)?;
+        Ok(())
+    }
+}
+
+/// Reads the videos back out of an existing journal one at a time.
+pub struct Replay {
+    path: PathBuf,
+    reader: BufReader<File>,
+    line: String,
+    /// Length of the journal up to the end of the last complete entry.
+    valid_len: u64,
+    warnings: Vec<Warning>,
+    /// Videos whose fetch was cut short, keyed by id.
+    partial: HashMap<String, PartialVideo>,
+}
+
+impl Replay {
+    pub fn next_video(&mut self) -> Result<Option<Video>> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            // The last line may have been cut off if the process was killed mid-write.
+            if !self.line.ends_with('\n') {
+                return Ok(None);
+            }
+            let Ok(entry) = serde_json::from_str(&self.line) else {
+                return Ok(None);
+            };
+            self.valid_len += self.line.len() as u64;
+            match entry {
+                Entry::Video(video) => {
+                    self.partial.remove(&video.id);
+                    return Ok(Some(*video));
+                }
+                Entry::Warning(warning) => self.warnings.push(warning),
+                Entry::Page {
+                    video_id,
+                    comments,
+                    next_page_token,
+                } => {
+                    self.partial
+                        .entry(video_id)
+                        .or_default()
+                        .add_page(&comments, next_page_token.as_deref());
+                }
+                Entry::Replies {
+                    video_id,
+                    thread_id,
+                    replies,
+                    next_page_token,
+                } => {
+                    self.partial.entry(video_id).or_default().add_replies(
+                        &thread_id,
+                        &replies,
+                        next_page_token.as_deref(),
+                    );
+                }
+                Entry::Channel(_) => bail!("Checkpoint {} is malformed", self.path.display()),
+            }
+        }
+    }
+
+    /// Reopen the journal for further recording once every video has been replayed. Returns it
+    /// along with the warnings recorded with the replayed videos.
+    pub fn finish(self) -> Result<(Checkpoint, Warnings)> {
+        // Drop anything after the last complete entry before appending to it.
+        let mut file = OpenOptions::new().write(true).open(&self.path)?;
+        file.set_len(self.valid_len)?;
+        file.seek(SeekFrom::End(0))?;
+        let checkpoint = Checkpoint {
+            w: BufWriter::new(file),
+            warnings_recorded: self.warnings.len(),
+            partial: self.partial,
+        };
+        Ok((checkpoint, self.warnings.into()))
+    }
+}
@@ -0,0 +1,273 @@
+use std::{cmp::Reverse, collections::HashMap, path::Path};
+
+use anyhow::Result;
+use clap::{Args, ValueHint};
+use serde::Serialize;
+
+use crate::{output, Video};
+
+#[derive(Args)]
+pub struct CompareArgs {
+    /// Name of the JSON file of the first channel.
+    #[arg(value_hint = ValueHint::FilePath)]
+    first_name: String,
+
+    /// Name of the JSON file of the second channel.
+    #[arg(value_hint = ValueHint::FilePath)]
+    second_name: String,
+
+    /// Number of shared commenters to show.
+    #[arg(short, long, default_value_t = 10)]
+    top: usize,
+
+    /// Print the comparison as JSON instead of tables.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Comparison {
+    first: ChannelStats,
+    second: ChannelStats,
+    overlap: Overlap,
+    /// Commenters on both channels, most active across the two first.
+    shared_commenters: Vec<SharedCommenter>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ChannelStats {
+    /// The channel's handle, or the file name for files without a header.
+    channel: String,
+    videos: usize,
+    comments: usize,
+    replies: usize,
+    commenters: usize,
+    comments_per_video: f64,
+    /// Replies per comment, over every thread.
+    replies_per_comment: f64,
+    /// Comments and replies per commenter.
+    posts_per_commenter: f64,
+    /// Average likes of the comments, not counting replies.
+    average_likes: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Overlap {
+    shared_commenters: usize,
+    /// Shared commenters as a fraction of everyone who commented on either channel.
+    jaccard: f64,
+    /// Fraction of the first channel's commenters who also commented on the second.
+    of_first: f64,
+    /// Fraction of the second channel's commenters who also commented on the first.
+    of_second: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SharedCommenter {
+    author_name: String,
+    author_channel_url: Option<String>,
+    /// Comments and replies on the first channel.
+    first: usize,
+    /// Comments and replies on the second channel.
+    second: usize,
+}
+
+/// How much an author posted on one channel.
+#[derive(Debug, Clone)]
+struct Commenter {
+    author_name: String,
+    author_channel_url: Option<String>,
+    posts: usize,
+}
+
+pub fn run(args: &CompareArgs) -> Result<()> {
+    let first = output::read(&args.first_name)?;
+    let second = output::read(&args.second_name)?;
+    let first_channel = channel_name(&args.first_name, first.header.as_ref());
+    let second_channel = channel_name(&args.second_name, second.header.as_ref());
+
+    let comparison = compute(
+        (first_channel, &first.videos),
+        (second_channel, &second.videos),
+        args.top,
+    );
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+    } else {
+        print_tables(&comparison);
+    }
+
+    Ok(())
+}
+
+fn channel_name(file_name: &str, header: Option<&output::Header>) -> String {
+    match header {
+        Some(header) if !header.channel_handle.is_empty() => header.channel_handle.clone(),
+        _ => Path::new(file_name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file_name.to_string()),
+    }
+}
+
+fn compute(first: (String, &[Video]), second: (String, &[Video]), top: usize) -> Comparison {
+    let first_commenters = commenters(first.1);
+    let second_commenters = commenters(second.1);
+
+    let mut shared: Vec<SharedCommenter> = first_commenters
+        .iter()
+        .filter_map(|(key, commenter)| {
+            let other = second_commenters.get(key)?;
+            Some(SharedCommenter {
+                author_name: commenter.author_name.clone(),
+                author_channel_url: commenter.author_channel_url.clone(),
+                first: commenter.posts,
+                second: other.posts,
+            })
+        })
+        .collect();
+    let union = first_commenters.len() + second_commenters.len() - shared.len();
+    let overlap = Overlap {
+        shared_commenters: shared.len(),
+        jaccard: ratio(shared.len(), union),
+        of_first: ratio(shared.len(), first_commenters.len()),
+        of_second: ratio(shared.len(), second_commenters.len()),
+    };
+    shared.sort_by_key(|c| (Reverse(c.first + c.second), c.author_name.clone()));
+    shared.truncate(top);
+
+    Comparison {
+        first: channel_stats(first.0, first.1, first_commenters.len()),
+        second: channel_stats(second.0, second.1, second_commenters.len()),
+        overlap,
+        shared_commenters: shared,
+    }
+}
+
+/// Everyone who commented or replied on the videos. Authors are keyed by channel id where
+/// available since display names aren't unique.
+fn commenters(videos: &[Video]) -> HashMap<&str, Commenter> {
+    let authors = videos.iter().flat_map(|v| &v.comments).flat_map(|comment| {
+        let thread = [(
+            &comment.author_channel_id,
+            &comment.author_name,
+            &comment.author_channel_url,
+        )];
+        let replies = comment.children.iter().map(|child| {
+            (
+                &child.author_channel_id,
+                &child.author_name,
+                &child.author_channel_url,
+            )
+        });
+        thread.into_iter().chain(replies)
+    });
+
+    let mut commenters: HashMap<&str, Commenter> = HashMap::new();
+    for (channel_id, name, url) in authors {
+        commenters
+            .entry(channel_id.as_deref().unwrap_or(name))
+            .or_insert_with(|| Commenter {
+                author_name: name.clone(),
+                author_channel_url: url.clone(),
+                posts: 0,
+            })
+            .posts += 1;
+    }
+    commenters
+}
+
+fn channel_stats(channel: String, videos: &[Video], commenters: usize) -> ChannelStats {
+    let comments: usize = videos.iter().map(|v| v.comments.len()).sum();
+    // Files written with --no-replies have the count without the replies themselves.
+    let replies: usize = videos
+        .iter()
+        .flat_map(|v| &v.comments)
+        .map(|c| c.children.len().max(c.reply_count as usize))
+        .sum();
+    let likes: u64 = videos
+        .iter()
+        .flat_map(|v| &v.comments)
+        .map(|c| u64::from(c.like_count))
+        .sum();
+    let posts: usize = videos
+        .iter()
+        .flat_map(|v| &v.comments)
+        .map(|c| 1 + c.children.len())
+        .sum();
+    ChannelStats {
+        channel,
+        videos: videos.len(),
+        comments,
+        replies,
+        commenters,
+        comments_per_video: ratio(comments, videos.len()),
+        replies_per_comment: ratio(replies, comments),
+        posts_per_commenter: ratio(posts, commenters),
+        average_likes: ratio(likes as usize, comments),
+    }
+}
+
+/// `n / total`, or 0 when there's nothing to divide by.
+fn ratio(n: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        n as f64 / total as f64
+    }
+}
+
+fn print_tables(comparison: &Comparison) {
+    let (first, second) = (&comparison.first, &comparison.second);
+    println!("{:<22} {:>14} {:>14}", "", first.channel, second.channel);
+    let counts = [
+        ("Videos", first.videos, second.videos),
+        ("Comments", first.comments, second.comments),
+        ("Replies", first.replies, second.replies),
+        ("Commenters", first.commenters, second.commenters),
+    ];
+    for (label, first, second) in counts {
+        println!("{label:<22} {first:>14} {second:>14}");
+    }
+    let averages = [
+        (
+            "Comments per video",
+            first.comments_per_video,
+            second.comments_per_video,
+        ),
+        (
+            "Replies per comment",
+            first.replies_per_comment,
+            second.replies_per_comment,
+        ),
+        (
+            "Posts per commenter",
+            first.posts_per_commenter,
+            second.posts_per_commenter,
+        ),
+        ("Average likes", first.average_likes, second.average_likes),
+    ];
+    for (label, first, second) in averages {
+        println!("{label:<22} {first:>14.2} {second:>14.2}");
+    }
+
+    let overlap = &comparison.overlap;
+    println!("\nShared commenters: {}", overlap.shared_commenters);
+    println!(
+        "{:.1}% of {}'s commenters, {:.1}% of {}'s, {:.1}% of both together",
+        overlap.of_first * 100.0,
+        first.channel,
+        overlap.of_second * 100.0,
+        second.channel,
+        overlap.jaccard * 100.0
+    );
+
+    println!("\nMost active shared commenters");
+    println!("{:>8} {:>8}  Author", "First", "Second");
+    for commenter in &comparison.shared_commenters {
+        println!(
+            "{:>8} {:>8}  {}",
+            commenter.first, commenter.second, commenter.author_name
+        );
+    }
+}
@@ -0,0 +1,679 @@
+use std::{any::Any, collections::HashMap, fs, net::SocketAddr, path::PathBuf, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use clap::{parser::ValueSource, Arg, ArgMatches, Args, Command};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{
+    analysis::Analysis,
+    profanity::ProfanityMode,
+    progress::ProgressMode,
+    rotate::RotateSuffix,
+    schedule::Schedule,
+    secrets::SecretStore,
+    sink::{split::SplitPeriod, Column, SinkKind},
+    translate::Backend,
+    AccessScope, ClientArgs, CommentSort, DownloadArgs, Format, ModerationStatus, Order,
+    TextFormat, TlsRoots, VideoOrder,
+};
+
+/// Name of the profile used when `--profile` isn't passed.
+const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+}
+
+/// Settings for a download. Each one has the same meaning as the command line option of the same
+/// name, which takes precedence when both are given.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct Profile {
+    channel: Option<String>,
+    mine: Option<bool>,
+    token_cache_name: Option<String>,
+    encrypt_token_cache: Option<bool>,
+    secrets: Option<SecretStore>,
+    client_secret_name: Option<String>,
+    credentials: Option<Vec<String>>,
+    scopes: Option<AccessScope>,
+    proxy: Option<String>,
+    rps: Option<f64>,
+    request_interval: Option<u64>,
+    connect_timeout: Option<u64>,
+    request_timeout: Option<u64>,
+    user_agent: Option<String>,
+    http1_only: Option<bool>,
+    pool_max_idle: Option<usize>,
+    tls_roots: Option<TlsRoots>,
+    oauth_port: Option<u16>,
+    paste_code: Option<bool>,
+    output_dir: Option<String>,
+    output_name: Option<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    format: Option<Vec<Format>>,
+    split_by: Option<SplitPeriod>,
+    flat: Option<bool>,
+    compact: Option<bool>,
+    stable: Option<bool>,
+    columns: Option<Vec<Column>>,
+    template: Option<String>,
+    manifest_name: Option<String>,
+    sink: Option<SinkKind>,
+    es_url: Option<String>,
+    index: Option<String>,
+    dsn: Option<String>,
+    brokers: Option<Vec<String>>,
+    redis_url: Option<String>,
+    spreadsheet_id: Option<String>,
+    sheet: Option<String>,
+    topic: Option<String>,
+    checkpoint_name: Option<String>,
+    status_name: Option<String>,
+    append: Option<bool>,
+    wait_for_quota: Option<bool>,
+    backfill: Option<String>,
+    update: Option<String>,
+    cache_dir: Option<String>,
+    archive_raw: Option<String>,
+    notify: Option<Vec<String>>,
+    upload: Option<String>,
+    video_order: Option<VideoOrder>,
+    sort_comments: Option<CommentSort>,
+    seen_store: Option<String>,
+    channel_playlist: Option<String>,
+    all_playlists: Option<bool>,
+    search: Option<String>,
+    max_results: Option<usize>,
+    max_videos: Option<usize>,
+    max_total_comments: Option<usize>,
+    time_limit: Option<String>,
+    min_duration: Option<String>,
+    max_duration: Option<String>,
+    category: Option<Vec<String>>,
+    text_format: Option<TextFormat>,
+    order: Option<Order>,
+    search_terms: Option<String>,
+    moderation_status: Option<ModerationStatus>,
+    no_replies: Option<bool>,
+    channel_threads: Option<bool>,
+    reply_concurrency: Option<usize>,
+    page_size: Option<u32>,
+    live_chat: Option<bool>,
+    include_captions: Option<bool>,
+    watch: Option<bool>,
+    interval: Option<String>,
+    schedule: Option<String>,
+    recent: Option<usize>,
+    updates_name: Option<String>,
+    rotate_size: Option<String>,
+    rotate_suffix: Option<RotateSuffix>,
+    rotate_compress: Option<bool>,
+    webhook: Option<String>,
+    feed: Option<String>,
+    metrics_addr: Option<SocketAddr>,
+    analyze: Option<Vec<Analysis>>,
+    dedup: Option<bool>,
+    wordcloud_dir: Option<String>,
+    wordcloud_per_video: Option<bool>,
+    spam_score: Option<bool>,
+    drop_spam: Option<f64>,
+    profanity: Option<ProfanityMode>,
+    profanity_words: Option<String>,
+    anonymize: Option<bool>,
+    redact_pii: Option<bool>,
+    nfc: Option<bool>,
+    strip_emoji: Option<bool>,
+    emoji_to_shortcode: Option<bool>,
+    salt_file: Option<String>,
+    comment_filter: Option<String>,
+    min_likes: Option<u32>,
+    language: Option<Vec<String>>,
+    resolve_handles: Option<bool>,
+    handle_cache: Option<String>,
+    translate_to: Option<String>,
+    translator: Option<Backend>,
+    translate_url: Option<String>,
+    exclude_authors: Option<String>,
+    exclude_owner_comments: Option<bool>,
+    only_authors: Option<String>,
+    charts: Option<String>,
+    progress: Option<ProgressMode>,
+    quiet: Option<bool>,
+}
+
+/// Read a list that may also be given as a single value, as options that used to take one are.
+fn one_or_many<'de, D, T>(deserializer: D) -> std::result::Result<Option<Vec<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => Some(vec![value]),
+        OneOrMany::Many(values) => Some(values),
+    })
+}
+
+/// Default location of the config file, `~/.config/ytcomments/config.toml`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("ytcomments").join("config.toml"))
+}
+
+/// Load the profile selected by the options, or an empty one if there's no config file.
+fn load_profile(client: &ClientArgs) -> Result<Profile> {
+    read_profile(client.config.as_deref(), client.profile.as_deref())
+}
+
+/// Load the named profile from the config file at `config`, or the default location.
+fn read_profile(config: Option<&str>, profile: Option<&str>) -> Result<Profile> {
+    let path = match config {
+        Some(path) => Some(PathBuf::from(path)),
+        None => default_config_path().filter(|p| p.exists()),
+    };
+
+    let mut config: Config = match path {
+        Some(path) => {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("Unable to read config file {}", path.display()))?;
+            toml::from_str(&contents)
+                .with_context(|| format!("Unable to parse config file {}", path.display()))?
+        }
+        None => Config::default(),
+    };
+
+    let profile = match profile {
+        Some(name) => config
+            .profiles
+            .remove(name)
+            .with_context(|| format!("No profile named {name} in the config file"))?,
+        None => config.profiles.remove(DEFAULT_PROFILE).unwrap_or_default(),
+    };
+    Ok(profile)
+}
+
+/// Fill in the connection options that weren't passed on the command line from the selected
+/// profile, for commands other than a download.
+pub fn apply_client_profile(client: &mut ClientArgs, matches: &ArgMatches) -> Result<()> {
+    let mut profile = load_profile(client)?;
+    let mut from_profile = vec![];
+    merge_client(&mut profile, client, matches, &mut from_profile);
+    check_conflicts(
+        &ClientArgs::augment_args(Command::new("client")),
+        matches,
+        &from_profile,
+    )
+}
+
+/// Whether a setting taken from the profile counts as given, which a flag turned off doesn't.
+fn is_given(value: &dyn Any) -> bool {
+    value.downcast_ref::<bool>() != Some(&false)
+}
+
+/// Apply the `conflicts_with` rules of the command's options to those given either on the command
+/// line or in the profile, since clap only checks the command line.
+fn check_conflicts(command: &Command, matches: &ArgMatches, from_profile: &[&str]) -> Result<()> {
+    let given = |arg: &Arg| {
+        let id = arg.get_id().as_str();
+        from_profile.contains(&id) || matches.value_source(id) == Some(ValueSource::CommandLine)
+    };
+    let name = |arg: &Arg| arg.get_long().unwrap_or(arg.get_id().as_str()).to_string();
+    for arg in command.get_arguments().filter(|arg| given(arg)) {
+        if let Some(other) = command
+            .get_arg_conflicts_with(arg)
+            .into_iter()
+            .find(|other| given(other))
+        {
+            bail!("{} can't be used together with {}", name(arg), name(other));
+        }
+    }
+    Ok(())
+}
+
+/// Merge the connection options, adding the ones the profile sets to `from_profile`.
+fn merge_client(
+    profile: &mut Profile,
+    client: &mut ClientArgs,
+    matches: &ArgMatches,
+    from_profile: &mut Vec<&'static str>,
+) {
+    let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+    macro_rules! merge {
+        ($field:ident) => {
+            if !from_cli(stringify!($field)) {
+                if let Some(value) = profile.$field.take() {
+                    if is_given(&value) {
+                        from_profile.push(stringify!($field));
+                    }
+                    client.$field = value;
+                }
+            }
+        };
+    }
+    macro_rules! merge_optional {
+        ($field:ident) => {
+            if !from_cli(stringify!($field)) && profile.$field.is_some() {
+                from_profile.push(stringify!($field));
+                client.$field = profile.$field.take();
+            }
+        };
+    }
+
+    merge!(token_cache_name);
+    merge!(encrypt_token_cache);
+    merge!(secrets);
+    merge!(client_secret_name);
+    merge!(credentials);
+    merge_optional!(scopes);
+    merge_optional!(proxy);
+    merge_optional!(rps);
+    merge_optional!(request_interval);
+    merge!(connect_timeout);
+    merge!(request_timeout);
+    merge_optional!(user_agent);
+    merge!(http1_only);
+    merge_optional!(pool_max_idle);
+    merge!(tls_roots);
+    merge_optional!(oauth_port);
+    merge!(paste_code);
+}
+
+/// Fill in every option that wasn't passed on the command line from the selected profile.
+pub fn apply_profile(args: &mut DownloadArgs, matches: &ArgMatches) -> Result<()> {
+    let mut profile = load_profile(&args.client)?;
+    // The options the profile sets, which are checked against each other and the command line
+    // once merged.
+    let mut from_profile = vec![];
+    merge_client(&mut profile, &mut args.client, matches, &mut from_profile);
+
+    let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+    macro_rules! merge {
+        ($field:ident) => {
+            if !from_cli(stringify!($field)) {
+                if let Some(value) = profile.$field.take() {
+                    if is_given(&value) {
+                        from_profile.push(stringify!($field));
+                    }
+                    args.$field = value;
+                }
+            }
+        };
+    }
+    macro_rules! merge_optional {
+        ($field:ident) => {
+            if !from_cli(stringify!($field)) && profile.$field.is_some() {
+                from_profile.push(stringify!($field));
+                args.$field = profile.$field.take();
+            }
+        };
+    }
+
+    if !from_cli("channel_handle") && profile.channel.is_some() {
+        from_profile.push("channel_handle");
+        args.channel_handle = profile.channel.take();
+    }
+    merge!(mine);
+    merge_optional!(output_dir);
+    merge!(output_name);
+    merge!(format);
+    merge_optional!(split_by);
+    merge!(flat);
+    merge!(compact);
+    merge!(stable);
+    merge!(columns);
+    merge_optional!(template);
+    merge!(manifest_name);
+    merge!(sink);
+    merge_optional!(es_url);
+    merge!(index);
+    merge_optional!(dsn);
+    merge!(brokers);
+    merge_optional!(redis_url);
+    merge_optional!(spreadsheet_id);
+    merge!(sheet);
+    merge!(topic);
+    merge!(checkpoint_name);
+    merge_optional!(status_name);
+    merge!(append);
+    merge!(wait_for_quota);
+    merge_optional!(backfill);
+    merge_optional!(update);
+    merge_optional!(cache_dir);
+    merge_optional!(archive_raw);
+    merge!(notify);
+    merge_optional!(upload);
+    merge_optional!(video_order);
+    merge_optional!(sort_comments);
+    merge_optional!(seen_store);
+    merge_optional!(channel_playlist);
+    merge!(all_playlists);
+    merge_optional!(search);
+    merge!(max_results);
+    merge_optional!(max_videos);
+    merge_optional!(max_total_comments);
+    merge!(category);
+    merge!(text_format);
+    merge!(order);
+    merge_optional!(search_terms);
+    merge_optional!(moderation_status);
+    merge!(no_replies);
+    merge!(channel_threads);
+    merge!(reply_concurrency);
+    merge_optional!(page_size);
+    merge!(live_chat);
+    merge!(include_captions);
+    merge!(watch);
+    merge!(recent);
+    merge!(updates_name);
+    merge!(rotate_suffix);
+    merge!(rotate_compress);
+    merge_optional!(webhook);
+    merge_optional!(feed);
+    merge_optional!(metrics_addr);
+    merge!(analyze);
+    merge!(dedup);
+    merge_optional!(wordcloud_dir);
+    merge!(wordcloud_per_video);
+    merge!(spam_score);
+    merge_optional!(drop_spam);
+    merge_optional!(profanity);
+    merge_optional!(profanity_words);
+    merge!(anonymize);
+    merge!(redact_pii);
+    merge!(nfc);
+    merge!(strip_emoji);
+    merge!(emoji_to_shortcode);
+    merge_optional!(salt_file);
+    merge_optional!(min_likes);
+    merge_optional!(exclude_authors);
+    merge_optional!(only_authors);
+    merge!(exclude_owner_comments);
+    merge!(resolve_handles);
+    merge_optional!(handle_cache);
+    merge_optional!(translator);
+    merge_optional!(translate_url);
+    merge_optional!(charts);
+    merge!(progress);
+    merge!(quiet);
+
+    if !from_cli("comment_filter") {
+        if let Some(pattern) = profile.comment_filter {
+            from_profile.push("comment_filter");
+            args.comment_filter =
+                Some(Regex::new(&pattern).context("Invalid comment-filter in the config profile")?);
+        }
+    }
+
+    if !from_cli("interval") {
+        if let Some(interval) = profile.interval {
+            from_profile.push("interval");
+            args.interval = crate::watch::parse_interval(&interval)
+                .map_err(anyhow::Error::msg)
+                .context("Invalid interval in the config profile")?;
+        }
+    }
+
+    if !from_cli("schedule") {
+        if let Some(schedule) = profile.schedule {
+            from_profile.push("schedule");
+            args.schedule = Some(
+                crate::schedule::parse_schedule(&schedule)
+                    .map_err(anyhow::Error::msg)
+                    .context("Invalid schedule in the config profile")?,
+            );
+        }
+    }
+
+    if !from_cli("rotate_size") {
+        if let Some(size) = profile.rotate_size {
+            from_profile.push("rotate_size");
+            args.rotate_size = Some(
+                crate::rotate::parse_size(&size)
+                    .map_err(anyhow::Error::msg)
+                    .context("Invalid rotate-size in the config profile")?,
+            );
+        }
+    }
+
+    if !from_cli("min_duration") {
+        if let Some(duration) = profile.min_duration {
+            from_profile.push("min_duration");
+            args.min_duration = Some(
+                crate::watch::parse_interval(&duration)
+                    .map_err(anyhow::Error::msg)
+                    .context("Invalid min-duration in the config profile")?,
+            );
+        }
+    }
+
+    if !from_cli("max_duration") {
+        if let Some(duration) = profile.max_duration {
+            from_profile.push("max_duration");
+            args.max_duration = Some(
+                crate::watch::parse_interval(&duration)
+                    .map_err(anyhow::Error::msg)
+                    .context("Invalid max-duration in the config profile")?,
+            );
+        }
+    }
+
+    if !from_cli("time_limit") {
+        if let Some(limit) = profile.time_limit {
+            from_profile.push("time_limit");
+            args.time_limit = Some(
+                crate::watch::parse_interval(&limit)
+                    .map_err(anyhow::Error::msg)
+                    .context("Invalid time-limit in the config profile")?,
+            );
+        }
+    }
+
+    if !from_cli("language") {
+        if let Some(languages) = profile.language {
+            from_profile.push("language");
+            args.language = languages
+                .iter()
+                .map(|l| crate::analysis::language::parse_language_code(l))
+                .collect::<Result<_, _>>()
+                .map_err(anyhow::Error::msg)
+                .context("Invalid language in the config profile")?;
+        }
+    }
+
+    if !from_cli("translate_to") {
+        if let Some(language) = profile.translate_to {
+            from_profile.push("translate_to");
+            args.translate_to = Some(
+                crate::analysis::language::parse_language_code(&language)
+                    .map_err(anyhow::Error::msg)
+                    .context("Invalid translate-to in the config profile")?,
+            );
+        }
+    }
+
+    check_conflicts(
+        &DownloadArgs::augment_args(Command::new("download")),
+        matches,
+        &from_profile,
+    )?;
+    // clap doesn't expose `requires`, so those rules are repeated here.
+    let given = |id: &str| from_cli(id) || from_profile.contains(&id);
+    if args
+        .drop_spam
+        .is_some_and(|threshold| !(0.0..=1.0).contains(&threshold))
+    {
+        bail!("drop-spam must be between 0 and 1");
+    }
+    if args.profanity_words.is_some() && args.profanity.is_none() {
+        bail!("profanity-words can only be used together with profanity");
+    }
+    if (args.wordcloud_dir.is_some() || args.wordcloud_per_video)
+        && !args.analyze.contains(&Analysis::Wordcloud)
+    {
+        bail!("wordcloud-dir and wordcloud-per-video can only be used together with analyze wordcloud");
+    }
+    if let (Some(min), Some(max)) = (args.min_duration, args.max_duration) {
+        if min > max {
+            bail!("min-duration must not be longer than max-duration");
+        }
+    }
+    if args.reply_concurrency == 0 {
+        bail!("reply-concurrency must be at least 1");
+    }
+    if args.page_size == Some(0) {
+        bail!("page-size must be at least 1");
+    }
+    if args.salt_file.is_some() && !args.anonymize {
+        bail!("salt-file can only be used together with anonymize");
+    }
+    if args.handle_cache.is_some() && !args.resolve_handles {
+        bail!("handle-cache can only be used together with resolve-handles");
+    }
+    if args.moderation_status.is_some() && args.client.uses_api_key() {
+        bail!("moderation-status can't be used with an API key, since it requires OAuth");
+    }
+    if args.webhook.is_some() && !args.watch {
+        bail!("webhook can only be used together with watch");
+    }
+    if args.rotate_size.is_some() && !args.watch {
+        bail!("rotate-size can only be used together with watch");
+    }
+    if (args.rotate_compress || given("rotate_suffix")) && args.rotate_size.is_none() {
+        bail!("rotate-compress and rotate-suffix can only be used together with rotate-size");
+    }
+    if (args.schedule.is_some() || given("interval") || given("recent")) && !args.watch {
+        bail!("schedule, interval and recent can only be used together with watch");
+    }
+    if given("max_results") && args.search.is_none() {
+        bail!("max-results can only be used together with search");
+    }
+    if args.feed.is_some() && !args.watch {
+        bail!("feed can only be used together with watch");
+    }
+    if args.metrics_addr.is_some() && !args.watch {
+        bail!("metrics-addr can only be used together with watch");
+    }
+    if (args.translator.is_some() || args.translate_url.is_some()) && args.translate_to.is_none() {
+        bail!("translator and translate-url can only be used together with translate-to");
+    }
+    if args.translate_url.is_some() && args.translator != Some(Backend::Libretranslate) {
+        bail!("translate-url can only be used together with translator libretranslate");
+    }
+    if args.split_by.is_some() && args.sink != SinkKind::File {
+        bail!("split-by can only be used together with sink file");
+    }
+    let writes = |formats: &[Format]| args.format.iter().any(|f| formats.contains(f));
+    if !args.columns.is_empty()
+        && !writes(&[Format::Csv, Format::Xlsx])
+        && args.sink != SinkKind::Sheets
+    {
+        bail!("columns can only be used together with format csv or xlsx, or sink sheets");
+    }
+    if args.flat && !writes(&[Format::Json, Format::Ndjson]) {
+        bail!("flat can only be used together with format json or ndjson");
+    }
+    if args.compact && !writes(&[Format::Json]) {
+        bail!("compact can only be used together with format json");
+    }
+    if args.format.len() > 1 && args.sink != SinkKind::File {
+        bail!("several formats can only be used together with sink file");
+    }
+    if args.template.is_some() && args.sink != SinkKind::File {
+        bail!("template can only be used together with sink file");
+    }
+    // These leave out comments that haven't been deleted, which would then be marked as deleted.
+    // Channel threads can't tell videos with comments turned off from ones without comments.
+    if args.update.is_some()
+        && (args.search_terms.is_some()
+            || args.moderation_status.is_some()
+            || args.no_replies
+            || args.channel_threads)
+    {
+        bail!("update can't be used together with search-terms, moderation-status, no-replies or channel-threads");
+    }
+
+    Ok(())
+}
+
+/// The watch mode settings that can change while it runs.
+pub struct WatchSettings {
+    pub interval: Duration,
+    pub schedule: Option<Schedule>,
+    pub recent: usize,
+}
+
+/// Reads the watch mode settings of the profile again, such as on SIGHUP, so that a long-running
+/// watch picks up changes to the config file without restarting. Settings given on the command
+/// line still take precedence.
+pub struct WatchReload {
+    config: Option<String>,
+    profile: Option<String>,
+    /// The settings given on the command line, or their defaults.
+    cli: WatchSettings,
+    from_cli: Vec<&'static str>,
+}
+
+impl WatchReload {
+    pub fn new(args: &DownloadArgs, matches: &ArgMatches) -> Self {
+        let from_cli = ["interval", "schedule", "recent"]
+            .into_iter()
+            .filter(|id| matches.value_source(id) == Some(ValueSource::CommandLine))
+            .collect();
+        Self {
+            config: args.client.config.clone(),
+            profile: args.client.profile.clone(),
+            cli: WatchSettings {
+                interval: matches
+                    .get_one("interval")
+                    .copied()
+                    .unwrap_or(args.interval),
+                schedule: matches.get_one::<Schedule>("schedule").cloned(),
+                recent: matches.get_one("recent").copied().unwrap_or(args.recent),
+            },
+            from_cli,
+        }
+    }
+
+    pub fn reload(&self) -> Result<WatchSettings> {
+        let profile = read_profile(self.config.as_deref(), self.profile.as_deref())?;
+        let from_cli = |id: &str| self.from_cli.contains(&id);
+
+        let interval = match profile.interval.filter(|_| !from_cli("interval")) {
+            Some(interval) => crate::watch::parse_interval(&interval)
+                .map_err(anyhow::Error::msg)
+                .context("Invalid interval in the config profile")?,
+            None => self.cli.interval,
+        };
+        let schedule = match profile.schedule.filter(|_| !from_cli("schedule")) {
+            Some(schedule) => Some(
+                crate::schedule::parse_schedule(&schedule)
+                    .map_err(anyhow::Error::msg)
+                    .context("Invalid schedule in the config profile")?,
+            ),
+            None => self.cli.schedule.clone(),
+        };
+        let recent = match profile.recent.filter(|_| !from_cli("recent")) {
+            Some(recent) => recent,
+            None => self.cli.recent,
+        };
+        Ok(WatchSettings {
+            interval,
+            schedule,
+            recent,
+        })
+    }
+}
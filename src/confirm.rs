@@ -0,0 +1,54 @@
+use std::io::{self, IsTerminal};
+
+use anyhow::{bail, Result};
+
+use crate::PlaylistItem;
+
+/// What a download is about to fetch, from the statistics of the videos it listed.
+pub struct Estimate {
+    videos: usize,
+    /// Comments YouTube reports on the videos, which may include some that are held for review or
+    /// deleted.
+    comments: u64,
+    /// Quota units the comment pages will take, one per page. Threads with more replies than come
+    /// with them take more.
+    quota: u64,
+}
+
+impl Estimate {
+    pub fn new(items: &[PlaylistItem]) -> Self {
+        Self {
+            videos: items.len(),
+            comments: items.iter().filter_map(|item| item.comment_count).sum(),
+            quota: items
+                .iter()
+                .filter(|item| item.comment_count != Some(0))
+                .map(PlaylistItem::expected_pages)
+                .sum(),
+        }
+    }
+}
+
+/// Whether `ask` would ask, and so needs every video listed first.
+pub fn will_ask() -> bool {
+    io::stdin().is_terminal()
+}
+
+/// Show what the download is about to fetch and ask whether to go ahead, failing if the answer
+/// isn't yes. Nothing is asked when stdin isn't a terminal, so scheduled runs aren't held up.
+pub fn ask(channel: &str, estimate: &Estimate) -> Result<()> {
+    if !will_ask() {
+        return Ok(());
+    }
+    eprintln!(
+        "About to fetch the comments of {} videos from {channel}, about {} comments costing about {} quota units.",
+        estimate.videos, estimate.comments, estimate.quota
+    );
+    eprint!("Continue? [y/N] ");
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        bail!("Download cancelled");
+    }
+    Ok(())
+}
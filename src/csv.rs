@@ -0,0 +1,57 @@
+use std::io::Write;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::sink::{comment_records, Column, Field, Sink};
+use crate::Video;
+
+/// Writes every comment and reply as a row of CSV, with the video each belongs to in its own
+/// columns, after a row of column names.
+pub struct CsvSink<W: Write> {
+    w: W,
+    columns: Vec<Column>,
+}
+
+impl<W: Write> CsvSink<W> {
+    pub fn new(columns: &[Column], mut w: W) -> Result<Self> {
+        let columns = Column::or_all(columns);
+        let names: Vec<_> = columns.iter().map(|c| c.name()).collect();
+        writeln!(w, "{}", names.join(","))?;
+        Ok(Self { w, columns })
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for CsvSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        for record in comment_records(video) {
+            let fields: Vec<_> = self
+                .columns
+                .iter()
+                .map(|column| match column.value(&record) {
+                    Field::Text(text) => quote(&text),
+                    Field::Number(number) => number.to_string(),
+                    Field::Empty => String::new(),
+                })
+                .collect();
+            writeln!(self.w, "{}", fields.join(","))?;
+        }
+        self.w.flush()?;
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.w.flush()?;
+        Ok(())
+    }
+}
+
+/// Quote a field if it holds anything that would otherwise end it, doubling any quotes inside.
+pub fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use clap::{Args, ValueHint};
+use serde::Serialize;
+
+use crate::{output, ChildComment, ParentComment, Video};
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Name of the JSON file from the earlier run.
+    #[arg(value_hint = ValueHint::FilePath)]
+    old_name: String,
+
+    /// Name of the JSON file from the later run, or - to read it from stdin so a fresh fetch can be piped in with `-o /dev/stdout`.
+    #[arg(value_hint = ValueHint::FilePath)]
+    new_name: String,
+}
+
+/// Everything that changed between two runs. Replies are only listed on threads present in both,
+/// since the replies of a new or removed thread come and go with it.
+#[derive(Debug, Clone, Default, Serialize)]
+struct Diff {
+    new_comments: Vec<Change>,
+    new_replies: Vec<Change>,
+    removed_comments: Vec<Change>,
+    removed_replies: Vec<Change>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Change {
+    video_id: String,
+    video_title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<String>,
+    id: String,
+    author_name: String,
+    text: String,
+}
+
+pub fn run(args: &DiffArgs) -> Result<()> {
+    let old = output::read(&args.old_name)?.videos;
+    let new = output::read(&args.new_name)?.videos;
+    let diff = compute(&old, &new);
+    println!("{}", serde_json::to_string_pretty(&diff)?);
+    Ok(())
+}
+
+fn compute(old: &[Video], new: &[Video]) -> Diff {
+    let mut diff = Diff::default();
+    let old_videos: HashMap<&str, &Video> = old.iter().map(|v| (v.id.as_str(), v)).collect();
+    let new_videos: HashMap<&str, &Video> = new.iter().map(|v| (v.id.as_str(), v)).collect();
+
+    for video in new {
+        let old_comments = old_videos
+            .get(video.id.as_str())
+            .map(|v| v.comments.as_slice())
+            .unwrap_or_default();
+        compare_threads(video, old_comments, &video.comments, &mut diff, false);
+    }
+    for video in old {
+        let new_comments = new_videos
+            .get(video.id.as_str())
+            .map(|v| v.comments.as_slice())
+            .unwrap_or_default();
+        compare_threads(video, new_comments, &video.comments, &mut diff, true);
+    }
+
+    diff
+}
+
+/// Record the threads in `comments` that aren't in `others`, and the replies missing from
+/// threads that are in both. `removed` says which way round the comparison is.
+fn compare_threads(
+    video: &Video,
+    others: &[ParentComment],
+    comments: &[ParentComment],
+    diff: &mut Diff,
+    removed: bool,
+) {
+    let others: HashMap<String, &ParentComment> =
+        others.iter().map(|c| (parent_key(c), c)).collect();
+    for comment in comments {
+        let Some(other) = others.get(&parent_key(comment)) else {
+            let change = Change {
+                video_id: video.id.clone(),
+                video_title: video.title.clone(),
+                parent_id: None,
+                id: comment.id.clone(),
+                author_name: comment.author_name.clone(),
+                text: comment.text.clone(),
+            };
+            if removed {
+                diff.removed_comments.push(change);
+            } else {
+                diff.new_comments.push(change);
+            }
+            continue;
+        };
+
+        let other_replies: HashSet<String> = other.children.iter().map(child_key).collect();
+        for child in &comment.children {
+            if other_replies.contains(&child_key(child)) {
+                continue;
+            }
+            let change = Change {
+                video_id: video.id.clone(),
+                video_title: video.title.clone(),
+                parent_id: Some(comment.id.clone()),
+                id: child.id.clone(),
+                author_name: child.author_name.clone(),
+                text: child.text.clone(),
+            };
+            if removed {
+                diff.removed_replies.push(change);
+            } else {
+                diff.new_replies.push(change);
+            }
+        }
+    }
+}
+
+/// Comments are matched by id, falling back to author and text for output written before ids
+/// were recorded.
+pub fn parent_key(comment: &ParentComment) -> String {
+    comment_key(&comment.id, &comment.author_name, &comment.text)
+}
+
+pub fn child_key(comment: &ChildComment) -> String {
+    comment_key(&comment.id, &comment.author_name, &comment.text)
+}
+
+fn comment_key(id: &str, author_name: &str, text: &str) -> String {
+    if id.is_empty() {
+        format!("{author_name}\n{text}")
+    } else {
+        id.to_string()
+    }
+}
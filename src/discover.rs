@@ -0,0 +1,93 @@
+use anyhow::Result;
+use google_youtube3::{
+    api::SearchListResponse,
+    chrono::{DateTime, SecondsFormat, Utc},
+};
+use tracing::debug;
+
+use crate::{api, quota, Client, PlaylistItem, Thumbnails};
+
+/// Quota units charged for each page of search results.
+pub const SEARCH_COST: u64 = 100;
+
+/// Where and when to look for videos matching a search.
+pub struct SearchScope<'a> {
+    pub channel_id: Option<&'a str>,
+    pub published_after: Option<DateTime<Utc>>,
+    pub published_before: Option<DateTime<Utc>>,
+}
+
+/// Find up to `max_results` videos matching the query with search.list, most relevant first.
+/// YouTube stops returning results after about 500, however many match.
+pub async fn search_videos(
+    query: &str,
+    scope: &SearchScope<'_>,
+    max_results: usize,
+    client: &Client,
+) -> Result<Vec<PlaylistItem>> {
+    let published_after = scope
+        .published_after
+        .map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let published_before = scope
+        .published_before
+        .map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let mut items = vec![];
+    let mut page_token = String::new();
+    while items.len() < max_results {
+        let page_size = (max_results - items.len()).min(50).to_string();
+        client.before_request().await;
+        quota::record(SEARCH_COST - 1);
+        let response: SearchListResponse = api::list(
+            client,
+            "search",
+            &[
+                ("part", "snippet"),
+                ("type", "video"),
+                ("q", query),
+                ("channelId", scope.channel_id.unwrap_or_default()),
+                (
+                    "publishedAfter",
+                    published_after.as_deref().unwrap_or_default(),
+                ),
+                (
+                    "publishedBefore",
+                    published_before.as_deref().unwrap_or_default(),
+                ),
+                ("maxResults", &page_size),
+                ("pageToken", &page_token),
+            ],
+        )
+        .await?;
+        debug!(
+            endpoint = "search.list",
+            query,
+            page_token,
+            items = response.items.as_ref().map_or(0, |i| i.len()),
+            "Fetched page"
+        );
+
+        for result in response.items.unwrap_or_default() {
+            let Some(video_id) = result.id.and_then(|id| id.video_id) else {
+                continue;
+            };
+            let snippet = result.snippet.unwrap_or_default();
+            items.push(PlaylistItem {
+                // Replaced by the unescaped title once statistics are added.
+                title: snippet.title.unwrap_or_default(),
+                video_id,
+                published_at: snippet.published_at,
+                thumbnails: snippet.thumbnails.map(Thumbnails::from_api),
+                view_count: None,
+                comment_count: None,
+                duration: None,
+                category_id: None,
+            });
+        }
+        match response.next_page_token {
+            Some(t) => page_token = t,
+            None => break,
+        }
+    }
+    items.truncate(max_results);
+    Ok(items)
+}
@@ -0,0 +1,47 @@
+use google_youtube3::chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// An earlier version of the text of a comment or reply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Edit {
+    pub text: String,
+    /// When this version was written, as reported by YouTube. Missing for versions from files
+    /// written before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// When the text was first noticed to have changed from this version. Missing for changes
+    /// found by `merge` in files without a fetch time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_at: Option<DateTime<Utc>>,
+}
+
+/// Combine the edit history of an older copy of a comment into the newer copy's, adding the older
+/// text as an earlier version if it differs from the newer text. Versions both copies know of are
+/// kept once, and the history stays ordered from oldest to newest.
+pub fn record_edits(
+    text: &str,
+    history: &mut Vec<Edit>,
+    older_text: String,
+    older_updated_at: Option<DateTime<Utc>>,
+    older_history: Vec<Edit>,
+    detected_at: Option<DateTime<Utc>>,
+) {
+    let newer_history = std::mem::replace(history, older_history);
+    for edit in newer_history {
+        if !history.contains(&edit) {
+            history.push(edit);
+        }
+    }
+    let known = history
+        .iter()
+        .any(|e| e.text == older_text && e.updated_at == older_updated_at);
+    if older_text != text && !known {
+        history.push(Edit {
+            text: older_text,
+            updated_at: older_updated_at,
+            detected_at,
+        });
+    }
+    history.sort_by_key(|e| e.updated_at);
+}
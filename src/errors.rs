@@ -0,0 +1,63 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::api::ApiError;
+
+/// What went wrong fetching a video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The API returned an error status.
+    Api,
+    /// Every credential ran out of quota for the day.
+    QuotaExceeded,
+    /// The request couldn't be sent or its response couldn't be read.
+    Network,
+    /// A response couldn't be parsed.
+    Parse,
+    Other,
+}
+
+/// A video whose comments couldn't be fetched, so it was left out of the output.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VideoError {
+    pub video_id: String,
+    pub title: String,
+    pub kind: ErrorKind,
+    /// HTTP status of the failed request, if it got that far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl VideoError {
+    pub fn new(video_id: &str, title: &str, error: &anyhow::Error) -> Self {
+        let (kind, status) = if let Some(e) = error.downcast_ref::<ApiError>() {
+            let kind = if e.is_quota_exceeded() {
+                ErrorKind::QuotaExceeded
+            } else {
+                ErrorKind::Api
+            };
+            (kind, Some(e.status.as_u16()))
+        } else if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+            let kind = if e.is_decode() {
+                ErrorKind::Parse
+            } else {
+                ErrorKind::Network
+            };
+            (kind, e.status().map(|s| s.as_u16()))
+        } else if error.downcast_ref::<serde_json::Error>().is_some() {
+            (ErrorKind::Parse, None)
+        } else {
+            (ErrorKind::Other, None)
+        };
+
+        Self {
+            video_id: video_id.to_string(),
+            title: title.to_string(),
+            kind,
+            status,
+            message: format!("{error:#}"),
+        }
+    }
+}
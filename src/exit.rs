@@ -0,0 +1,88 @@
+use std::{error::Error as StdError, fmt, process::ExitCode};
+
+use anyhow::Error;
+use google_youtube3::oauth2;
+use reqwest::StatusCode;
+
+use crate::api::ApiError;
+
+/// Why a command failed, each with its own exit code so that scripts can tell them apart. Any
+/// other failure exits with 1, and invalid arguments with 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Signing in failed, or the API refused the credentials.
+    Auth = 3,
+    /// No channel has the handle.
+    ChannelNotFound = 4,
+    /// Every credential ran out of quota.
+    QuotaExceeded = 5,
+    /// The run ended without fetching every video, because some failed or it was interrupted.
+    Partial = 6,
+    /// A file couldn't be read or written.
+    Io = 7,
+}
+
+impl Failure {
+    /// Mark the error as being this failure.
+    pub fn wrap(self, error: impl Into<Error>) -> Error {
+        Marked {
+            failure: self,
+            error: error.into(),
+        }
+        .into()
+    }
+}
+
+/// An error marked with why it happened, where that's known where it's raised. It reads as the
+/// error it wraps.
+#[derive(Debug)]
+struct Marked {
+    failure: Failure,
+    error: Error,
+}
+
+impl fmt::Display for Marked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl StdError for Marked {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.error.source()
+    }
+}
+
+/// The exit code for a command that failed with the error.
+pub fn exit_code(error: &Error) -> ExitCode {
+    ExitCode::from(code(error))
+}
+
+/// The number of the exit code for the error.
+pub fn code(error: &Error) -> u8 {
+    failure(error).map_or(1, |failure| failure as u8)
+}
+
+/// What the error was, from how it was marked or otherwise from its causes.
+fn failure(error: &Error) -> Option<Failure> {
+    for cause in error.chain() {
+        if let Some(marked) = cause.downcast_ref::<Marked>() {
+            return Some(marked.failure);
+        }
+        if let Some(e) = cause.downcast_ref::<ApiError>() {
+            if e.is_quota_exceeded() {
+                return Some(Failure::QuotaExceeded);
+            }
+            if e.status == StatusCode::UNAUTHORIZED {
+                return Some(Failure::Auth);
+            }
+        }
+        if cause.is::<oauth2::Error>() {
+            return Some(Failure::Auth);
+        }
+    }
+    error
+        .chain()
+        .any(|cause| cause.is::<std::io::Error>())
+        .then_some(Failure::Io)
+}
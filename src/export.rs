@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use clap::{Args, ValueHint};
+
+use crate::{
+    analysis::links::LinkExtractor,
+    by_author, file_sink,
+    output::{self, Header, SCHEMA_VERSION},
+    sink::Column,
+    site, template_sink, FileLayout, Format,
+};
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Name of a JSON file previously written by this tool.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+
+    /// Name of the file to write, or of the directory with --site or --format hf-dataset.
+    #[arg(value_hint = ValueHint::AnyPath)]
+    output_name: String,
+
+    /// Format to write. Defaults to the one matching the extension of the output file: .json, .ndjson, .html, .md, .xlsx, .xml or .csv.
+    #[arg(short, long, value_enum)]
+    format: Option<Format>,
+
+    /// Write JSON or NDJSON as a record for every comment and reply instead of videos with nested threads, as with the flat option of a download.
+    #[arg(long, conflicts_with = "template")]
+    flat: bool,
+
+    /// Write JSON without indentation or line breaks.
+    #[arg(long, conflicts_with = "template")]
+    compact: bool,
+
+    /// Comma-separated columns to write to CSV or Excel output, in that order. Defaults to all of them.
+    #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "template")]
+    columns: Vec<Column>,
+
+    /// Handlebars template to render every video through instead of writing one of the formats, as with the template option of a download.
+    #[arg(long, conflicts_with = "format")]
+    template: Option<String>,
+
+    /// Write JSON listing every commenter, most active first, with all of their comments and replies across the videos, oldest first. The links in each are found along the way.
+    #[arg(long, conflicts_with_all = ["template", "flat", "columns"])]
+    by_author: bool,
+
+    /// Write the source of a static site for Hugo or Jekyll to the output directory instead of a file: a Markdown page with front matter for each video under content/videos, and its comments as YAML under data/comments.
+    #[arg(long, conflicts_with_all = ["format", "template", "flat", "compact", "columns", "by_author"])]
+    site: bool,
+
+    /// Channel handle shown in the title of HTML and Markdown output and on the root element of XML. Defaults to the one recorded in the input file, or for files written before it was recorded, the input file name.
+    #[arg(long)]
+    channel_handle: Option<String>,
+}
+
+pub async fn run(args: &ExportArgs) -> Result<()> {
+    let output_path = Path::new(&args.output_name);
+    let format = match (&args.template, args.format) {
+        (Some(_), _) => None,
+        (None, Some(format)) => Some(format),
+        // The output is a directory, named however the site is.
+        (None, None) if args.site => None,
+        (None, None) => Some(format_for(output_path)?),
+    };
+    if !args.columns.is_empty() && !matches!(format, Some(Format::Csv | Format::Xlsx)) {
+        bail!("--columns can only be used with CSV or Excel output");
+    }
+    if args.flat && !matches!(format, Some(Format::Json | Format::Ndjson)) {
+        bail!("--flat can only be used with JSON or NDJSON output");
+    }
+    if args.compact && format != Some(Format::Json) {
+        bail!("--compact can only be used with JSON output");
+    }
+    if args.by_author && format != Some(Format::Json) {
+        bail!("--by-author can only be used with JSON output");
+    }
+
+    let output = output::read(&args.input_name)?;
+    let mut header = output.header.unwrap_or_else(|| {
+        let stem = Path::new(&args.input_name).file_stem().unwrap_or_default();
+        Header::new(&stem.to_string_lossy())
+    });
+    // The videos are written in the current layout, whatever the version of the input.
+    header.schema_version = SCHEMA_VERSION;
+    if let Some(handle) = &args.channel_handle {
+        header.channel_handle = handle.clone();
+    }
+    if args.by_author {
+        let mut videos = output.videos;
+        let mut links = LinkExtractor::default();
+        for video in &mut videos {
+            links.analyze(video);
+        }
+        return by_author::write(&header, &videos, output_path, args.compact);
+    }
+    if args.site {
+        return site::write(&header, &output.videos, output_path);
+    }
+
+    let mut sink = match (&args.template, format) {
+        (Some(template), _) => template_sink(template, &header, output_path)?,
+        (None, Some(format)) => {
+            let layout = FileLayout {
+                format,
+                columns: args.columns.clone(),
+                flat: args.flat,
+                compact: args.compact,
+            };
+            file_sink(&layout, &header, output_path)?
+        }
+        (None, None) => unreachable!("the format is known unless a template is used"),
+    };
+    for video in &output.videos {
+        sink.write_video(video).await?;
+    }
+    sink.write_errors(&output.errors).await?;
+    sink.finish().await
+}
+
+pub fn format_for(path: &Path) -> Result<Format> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    Ok(match extension.as_str() {
+        "json" => Format::Json,
+        "ndjson" | "jsonl" => Format::Ndjson,
+        "html" | "htm" => Format::Html,
+        "md" | "markdown" => Format::Markdown,
+        "xlsx" => Format::Xlsx,
+        "xml" => Format::Xml,
+        "csv" => Format::Csv,
+        "graphml" => Format::Graphml,
+        "dot" | "gv" => Format::Dot,
+        _ => bail!(
+            "Unable to tell the format of {} from its extension, pass --format",
+            path.display()
+        ),
+    })
+}
@@ -0,0 +1,134 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use google_youtube3::chrono::{SecondsFormat, Utc};
+
+use crate::{report::comment_url, xml::escape_xml, Video};
+
+/// Most entries kept in the feed. Older ones drop off the end as new comments arrive.
+const MAX_ENTRIES: usize = 200;
+
+/// An Atom feed of the comments discovered in watch mode, newest first, rewritten after every
+/// video that has any.
+pub struct Feed {
+    path: PathBuf,
+    channel: String,
+    /// Rendered `<entry>` elements, one per line.
+    entries: VecDeque<String>,
+}
+
+impl Feed {
+    /// Entries already in the file are kept, so restarting watch mode doesn't empty the feed.
+    pub fn open(path: &Path, channel: &str) -> Result<Self> {
+        let entries = match fs::read_to_string(path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| line.starts_with("  <entry>"))
+                .map(str::to_string)
+                .collect(),
+            Err(_) => VecDeque::new(),
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            channel: channel.to_string(),
+            entries,
+        })
+    }
+
+    /// Add every comment and reply on the videos, then rewrite the file.
+    pub fn add(&mut self, videos: &[Video]) -> Result<()> {
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut new_entries = vec![];
+        for video in videos {
+            for comment in &video.comments {
+                new_entries.push(entry(
+                    video,
+                    &comment.id,
+                    &comment.author_name,
+                    comment.author_channel_url.as_deref(),
+                    &comment.text,
+                    &now,
+                ));
+                for child in &comment.children {
+                    new_entries.push(entry(
+                        video,
+                        &child.id,
+                        &child.author_name,
+                        child.author_channel_url.as_deref(),
+                        &child.text,
+                        &now,
+                    ));
+                }
+            }
+        }
+        if new_entries.is_empty() {
+            return Ok(());
+        }
+
+        for entry in new_entries.into_iter().rev() {
+            self.entries.push_front(entry);
+        }
+        self.entries.truncate(MAX_ENTRIES);
+        self.write(&now)
+    }
+
+    fn write(&self, updated: &str) -> Result<()> {
+        let channel_url = format!("https://www.youtube.com/{}", self.channel);
+        let mut contents = String::new();
+        contents.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        contents.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        contents.push_str(&format!(
+            "  <title>New comments on {}</title>\n",
+            escape_xml(&self.channel)
+        ));
+        contents.push_str(&format!("  <id>{}</id>\n", escape_xml(&channel_url)));
+        contents.push_str(&format!(
+            "  <link href=\"{}\"/>\n",
+            escape_xml(&channel_url)
+        ));
+        contents.push_str(&format!("  <updated>{updated}</updated>\n"));
+        for entry in &self.entries {
+            contents.push_str(entry);
+            contents.push('\n');
+        }
+        contents.push_str("</feed>\n");
+
+        // Write to a temporary file first, so feed readers never see a partial feed.
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, contents)
+            .with_context(|| format!("Unable to write feed {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("Unable to write feed {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// Render one comment as an `<entry>` on a single line, which is how entries are found again when
+/// the feed is reopened.
+fn entry(
+    video: &Video,
+    id: &str,
+    author_name: &str,
+    author_channel_url: Option<&str>,
+    text: &str,
+    updated: &str,
+) -> String {
+    let link = escape_xml(&comment_url(&video.id, id));
+    let author_uri = author_channel_url
+        .map(|url| format!("<uri>{}</uri>", escape_xml(url)))
+        .unwrap_or_default();
+    // Newlines in the text are escaped so the entry stays on one line.
+    let content = escape_xml(text)
+        .replace('\r', "&#13;")
+        .replace('\n', "&#10;");
+    format!(
+        "  <entry><id>{link}</id><title>{} on {}</title><link href=\"{link}\"/><author><name>{}</name>{author_uri}</author><updated>{updated}</updated><content type=\"text\">{content}</content></entry>",
+        escape_xml(author_name),
+        escape_xml(&video.title),
+        escape_xml(author_name),
+    )
+}
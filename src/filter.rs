@@ -0,0 +1,180 @@
+use std::{collections::HashSet, fs, time::Duration};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::{PlaylistItem, Video};
+
+/// Filters applied to each video's comments as they are fetched.
+#[derive(Debug, Default)]
+pub struct Filters {
+    /// Only comments matching this pattern are kept, along with the parents of matching replies.
+    pub comment_pattern: Option<Regex>,
+    /// Only comments with at least this many likes are kept, along with the parents of kept replies.
+    pub min_likes: Option<u32>,
+    /// If not empty, only comments detected in one of these languages are kept, along with the
+    /// parents of kept replies. Requires comments to have been tagged with their language first.
+    pub languages: Vec<String>,
+    /// Comments by these authors are dropped, unless they have replies that are kept.
+    pub exclude_authors: Option<AuthorList>,
+    /// If set, only comments by these authors are kept, along with the parents of kept replies.
+    pub only_authors: Option<AuthorList>,
+    /// Comments marked as the channel owner's are dropped, unless they have replies that are kept.
+    pub exclude_owner: bool,
+}
+
+/// Filters deciding which of the channel's videos are fetched, from the details looked up by
+/// `add_statistics`.
+#[derive(Debug, Default)]
+pub struct VideoFilters {
+    pub min_duration: Option<Duration>,
+    pub max_duration: Option<Duration>,
+    /// If not empty, only videos in one of these categories are kept.
+    pub category_ids: Vec<String>,
+}
+
+impl VideoFilters {
+    pub fn is_empty(&self) -> bool {
+        self.min_duration.is_none() && self.max_duration.is_none() && self.category_ids.is_empty()
+    }
+
+    /// Videos whose details couldn't be looked up are kept.
+    pub fn keep(&self, item: &PlaylistItem) -> bool {
+        let duration_kept = item.duration.is_none_or(|duration| {
+            self.min_duration.is_none_or(|min| duration >= min)
+                && self.max_duration.is_none_or(|max| duration <= max)
+        });
+        let category_kept = self.category_ids.is_empty()
+            || item
+                .category_id
+                .as_ref()
+                .is_none_or(|id| self.category_ids.contains(id));
+        duration_kept && category_kept
+    }
+}
+
+/// Authors listed in a file, one channel id or display name per line. Blank lines and lines
+/// starting with `#` are skipped.
+#[derive(Debug, Default)]
+pub struct AuthorList {
+    entries: HashSet<String>,
+}
+
+impl AuthorList {
+    pub fn read(path: &str) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read author list {path}"))?;
+        let entries = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Ok(Self { entries })
+    }
+
+    fn contains(&self, author: &Author) -> bool {
+        self.entries.contains(author.name)
+            || author
+                .channel_id
+                .is_some_and(|id| self.entries.contains(id))
+    }
+}
+
+struct Author<'a> {
+    name: &'a str,
+    channel_id: Option<&'a str>,
+}
+
+/// Mark the comments and replies on the video written by the channel it was downloaded from.
+/// Authors are compared by channel id, since the owner's display name can change or be shared.
+pub fn mark_owner(video: &mut Video, channel_id: &str) {
+    for comment in &mut video.comments {
+        comment.is_owner = comment.author_channel_id.as_deref() == Some(channel_id);
+        for child in &mut comment.children {
+            child.is_owner = child.author_channel_id.as_deref() == Some(channel_id);
+        }
+    }
+}
+
+impl Filters {
+    /// Drop comments that don't pass every filter. A top level comment that doesn't pass is still
+    /// kept if any of its replies do, so the replies keep their context.
+    pub fn apply(&self, video: &mut Video) {
+        video.comments.retain_mut(|comment| {
+            comment.children.retain(|c| {
+                let author = Author {
+                    name: &c.author_name,
+                    channel_id: c.author_channel_id.as_deref(),
+                };
+                self.keep(
+                    &c.text,
+                    c.like_count,
+                    c.language.as_deref(),
+                    &author,
+                    c.is_owner,
+                )
+            });
+            let author = Author {
+                name: &comment.author_name,
+                channel_id: comment.author_channel_id.as_deref(),
+            };
+            self.keep(
+                &comment.text,
+                comment.like_count,
+                comment.language.as_deref(),
+                &author,
+                comment.is_owner,
+            ) || !comment.children.is_empty()
+        });
+    }
+
+    fn keep(
+        &self,
+        text: &str,
+        like_count: u32,
+        language: Option<&str>,
+        author: &Author,
+        owner: bool,
+    ) -> bool {
+        if owner && self.exclude_owner {
+            return false;
+        }
+
+        if self
+            .exclude_authors
+            .as_ref()
+            .is_some_and(|list| list.contains(author))
+        {
+            return false;
+        }
+
+        if self
+            .only_authors
+            .as_ref()
+            .is_some_and(|list| !list.contains(author))
+        {
+            return false;
+        }
+
+        if let Some(pattern) = &self.comment_pattern {
+            if !pattern.is_match(text) {
+                return false;
+            }
+        }
+
+        if let Some(min_likes) = self.min_likes {
+            if like_count < min_likes {
+                return false;
+            }
+        }
+
+        if !self.languages.is_empty()
+            && !language.is_some_and(|l| self.languages.iter().any(|allowed| allowed == l))
+        {
+            return false;
+        }
+
+        true
+    }
+}
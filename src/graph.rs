@@ -0,0 +1,190 @@
+use std::{collections::BTreeMap, io::Write};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{sink::Sink, xml::escape_xml, Video};
+
+/// File format of the interaction graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Graphml,
+    Dot,
+}
+
+/// A commenter in the graph.
+struct Node {
+    name: String,
+    comments: usize,
+    replies: usize,
+}
+
+/// Builds a graph of who replies to whom, with an edge from every replier to the author they
+/// replied to, weighted by how many times they did. Authors are told apart by channel id where
+/// they have one. A reply goes to the thread's author, unless it starts with an @mention of
+/// someone else who posted earlier in the thread, since YouTube threads replies to replies under
+/// the top-level comment. The graph is written once every video has been added.
+pub struct GraphSink<W: Write> {
+    w: W,
+    format: GraphFormat,
+    nodes: BTreeMap<String, Node>,
+    edges: BTreeMap<(String, String), usize>,
+}
+
+impl<W: Write> GraphSink<W> {
+    pub fn new(format: GraphFormat, w: W) -> Self {
+        Self {
+            w,
+            format,
+            nodes: BTreeMap::new(),
+            edges: BTreeMap::new(),
+        }
+    }
+
+    fn node(&mut self, name: &str, channel_id: Option<&str>) -> &mut Node {
+        self.nodes
+            .entry(key(name, channel_id))
+            .or_insert_with(|| Node {
+                name: name.to_string(),
+                comments: 0,
+                replies: 0,
+            })
+    }
+
+    fn write_graphml(&mut self) -> Result<()> {
+        let w = &mut self.w;
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            w,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        writeln!(
+            w,
+            r#"  <key id="name" for="node" attr.name="name" attr.type="string"/>"#
+        )?;
+        writeln!(
+            w,
+            r#"  <key id="comments" for="node" attr.name="comments" attr.type="int"/>"#
+        )?;
+        writeln!(
+            w,
+            r#"  <key id="replies" for="node" attr.name="replies" attr.type="int"/>"#
+        )?;
+        writeln!(
+            w,
+            r#"  <key id="weight" for="edge" attr.name="weight" attr.type="int"/>"#
+        )?;
+        writeln!(w, r#"  <graph id="replies" edgedefault="directed">"#)?;
+        for (id, node) in &self.nodes {
+            writeln!(w, r#"    <node id="{}">"#, escape_xml(id))?;
+            writeln!(
+                w,
+                r#"      <data key="name">{}</data>"#,
+                escape_xml(&node.name)
+            )?;
+            writeln!(w, r#"      <data key="comments">{}</data>"#, node.comments)?;
+            writeln!(w, r#"      <data key="replies">{}</data>"#, node.replies)?;
+            writeln!(w, "    </node>")?;
+        }
+        for ((from, to), weight) in &self.edges {
+            writeln!(
+                w,
+                r#"    <edge source="{}" target="{}"><data key="weight">{weight}</data></edge>"#,
+                escape_xml(from),
+                escape_xml(to)
+            )?;
+        }
+        writeln!(w, "  </graph>")?;
+        writeln!(w, "</graphml>")?;
+        Ok(())
+    }
+
+    fn write_dot(&mut self) -> Result<()> {
+        let w = &mut self.w;
+        writeln!(w, "digraph replies {{")?;
+        for (id, node) in &self.nodes {
+            writeln!(
+                w,
+                "  {} [label={}, comments={}, replies={}];",
+                quote_dot(id),
+                quote_dot(&node.name),
+                node.comments,
+                node.replies
+            )?;
+        }
+        for ((from, to), weight) in &self.edges {
+            writeln!(
+                w,
+                "  {} -> {} [weight={weight}, penwidth={:.1}];",
+                quote_dot(from),
+                quote_dot(to),
+                1.0 + (*weight as f64).ln()
+            )?;
+        }
+        writeln!(w, "}}")?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for GraphSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        for comment in &video.comments {
+            let thread_author = key(&comment.author_name, comment.author_channel_id.as_deref());
+            self.node(&comment.author_name, comment.author_channel_id.as_deref())
+                .comments += 1;
+
+            // Everyone who has posted in the thread so far, by display name, for @mentions.
+            let mut names = vec![comment.author_name.as_str()];
+            let mut keys = vec![thread_author.clone()];
+            for child in &comment.children {
+                let author = key(&child.author_name, child.author_channel_id.as_deref());
+                self.node(&child.author_name, child.author_channel_id.as_deref())
+                    .replies += 1;
+
+                let target = match replied_to(&child.text, &names) {
+                    Some(index) => keys[index].clone(),
+                    None => thread_author.clone(),
+                };
+                if target != author {
+                    *self.edges.entry((author.clone(), target)).or_default() += 1;
+                }
+                names.push(child.author_name.as_str());
+                keys.push(author);
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        match self.format {
+            GraphFormat::Graphml => self.write_graphml()?,
+            GraphFormat::Dot => self.write_dot()?,
+        }
+        self.w.flush()?;
+        Ok(())
+    }
+}
+
+/// Which of the earlier posters in a thread, by display name and oldest first, a reply is
+/// answering, when it starts with an @mention of one of them. The latest post by that name is
+/// taken. Replies without one answer the thread's top-level comment.
+pub fn replied_to(text: &str, posters: &[&str]) -> Option<usize> {
+    let rest = text.strip_prefix('@')?;
+    posters.iter().rposition(|name| {
+        let name = name.strip_prefix('@').unwrap_or(name);
+        rest.starts_with(name)
+    })
+}
+
+/// The id an author has in the graph.
+fn key(name: &str, channel_id: Option<&str>) -> String {
+    match channel_id {
+        Some(id) => id.to_string(),
+        None => format!("name:{name}"),
+    }
+}
+
+fn quote_dot(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use google_youtube3::{
+    api::ChannelListResponse,
+    chrono::{DateTime, Duration, Utc},
+};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{
+    api,
+    warnings::{WarningCode, Warnings},
+    Client, Video,
+};
+
+/// Most channels `channels.list` takes at once.
+const BATCH_SIZE: usize = 50;
+
+/// Age after which a cached channel is looked up again, since handles and titles can change.
+const MAX_AGE_DAYS: i64 = 30;
+
+/// An author's channel as it is now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChannelName {
+    /// Missing for the few channels without a handle, and for channels that were deleted.
+    handle: Option<String>,
+    /// Missing for channels that were deleted.
+    title: Option<String>,
+    resolved_at: DateTime<Utc>,
+}
+
+/// Looks up the current @handle and title of the channel behind every comment and reply, which
+/// unlike display names identify authors the same way over time. Channels are looked up once per
+/// run, or per `MAX_AGE_DAYS` with a cache file.
+pub struct HandleResolver {
+    channels: HashMap<String, ChannelName>,
+    cache_path: Option<PathBuf>,
+}
+
+impl HandleResolver {
+    /// Start with the channels in the cache file, if one is given and it exists.
+    pub fn new(cache_path: Option<&Path>) -> Result<Self> {
+        let mut channels: HashMap<String, ChannelName> = match cache_path {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(contents) => serde_json::from_str(&contents)
+                    .with_context(|| format!("Unable to parse handle cache {}", path.display()))?,
+                Err(e) if e.kind() == ErrorKind::NotFound => HashMap::new(),
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Unable to read handle cache {}", path.display()))
+                }
+            },
+            None => HashMap::new(),
+        };
+        let oldest = Utc::now() - Duration::days(MAX_AGE_DAYS);
+        channels.retain(|_, channel| channel.resolved_at > oldest);
+        Ok(Self {
+            channels,
+            cache_path: cache_path.map(Path::to_path_buf),
+        })
+    }
+
+    /// Set `author_handle` and `author_channel_title` on every comment and reply on the video
+    /// whose author has a channel id. If the lookup fails, the authors that weren't known already
+    /// are left without them and a warning is recorded.
+    pub async fn resolve_video(
+        &mut self,
+        video: &mut Video,
+        client: &Client,
+        warnings: &mut Warnings,
+    ) -> Result<()> {
+        match self.look_up(video, client).await {
+            Ok(true) => self.save()?,
+            Ok(false) => {}
+            Err(e) => warnings.push(
+                WarningCode::HandlesUnavailable,
+                Some(&video.id),
+                None,
+                format!("Unable to look up the handles of commenters: {e:#}"),
+            ),
+        }
+
+        let name = |channel_id: &Option<String>| {
+            channel_id
+                .as_ref()
+                .and_then(|id| self.channels.get(id))
+                .map_or((None, None), |c| (c.handle.clone(), c.title.clone()))
+        };
+        for comment in &mut video.comments {
+            (comment.author_handle, comment.author_channel_title) =
+                name(&comment.author_channel_id);
+            for child in &mut comment.children {
+                (child.author_handle, child.author_channel_title) = name(&child.author_channel_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up the channels on the video that aren't known yet, returning whether there were any.
+    async fn look_up(&mut self, video: &Video, client: &Client) -> Result<bool> {
+        let unknown: HashSet<&str> = video
+            .comments
+            .iter()
+            .flat_map(|c| {
+                std::iter::once(&c.author_channel_id)
+                    .chain(c.children.iter().map(|c| &c.author_channel_id))
+            })
+            .filter_map(Option::as_deref)
+            .filter(|id| !self.channels.contains_key(*id))
+            .collect();
+        if unknown.is_empty() {
+            return Ok(false);
+        }
+
+        let unknown: Vec<&str> = unknown.into_iter().collect();
+        for batch in unknown.chunks(BATCH_SIZE) {
+            client.before_request().await;
+            let response: ChannelListResponse = api::list(
+                client,
+                "channels",
+                &[
+                    ("part", "snippet"),
+                    ("id", &batch.join(",")),
+                    ("maxResults", "50"),
+                ],
+            )
+            .await?;
+            debug!(
+                endpoint = "channels.list",
+                items = response.items.as_ref().map_or(0, |i| i.len()),
+                "Fetched page"
+            );
+            let resolved_at = Utc::now();
+            for channel in response.items.unwrap_or_default() {
+                let (Some(id), Some(snippet)) = (channel.id, channel.snippet) else {
+                    continue;
+                };
+                let name = ChannelName {
+                    handle: snippet.custom_url,
+                    title: snippet.title,
+                    resolved_at,
+                };
+                self.channels.insert(id, name);
+            }
+            // Channels that weren't returned were deleted or terminated, and are remembered as
+            // such so they aren't looked up again.
+            for id in batch {
+                self.channels.entry(id.to_string()).or_insert(ChannelName {
+                    handle: None,
+                    title: None,
+                    resolved_at,
+                });
+            }
+        }
+        Ok(true)
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.cache_path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string(&self.channels)?;
+        fs::write(path, contents)
+            .with_context(|| format!("Unable to write handle cache {}", path.display()))
+    }
+}
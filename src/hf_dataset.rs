@@ -0,0 +1,269 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    output::Header,
+    sink::{comment_records, Sink},
+    Video,
+};
+
+/// Where the rows go in the dataset directory, which is where `datasets` looks for the train split.
+const TRAIN_PATH: &str = "data/train.jsonl";
+
+/// Type of a column of the dataset, as the dataset card declares it.
+#[derive(Clone, Copy)]
+enum Feature {
+    String,
+    Int,
+    Float,
+    Bool,
+    Strings,
+    Ints,
+}
+
+/// Every column of the dataset, in the order the card lists them. They're the fields of flat
+/// output, written on every row even when empty, so that the columns are the same throughout.
+const FEATURES: &[(&str, Feature)] = &[
+    ("video_id", Feature::String),
+    ("video_title", Feature::String),
+    ("comment_id", Feature::String),
+    ("url", Feature::String),
+    ("parent_id", Feature::String),
+    ("depth", Feature::Int),
+    ("author_name", Feature::String),
+    ("author_channel_id", Feature::String),
+    ("author_channel_url", Feature::String),
+    ("author_profile_image_url", Feature::String),
+    ("is_owner", Feature::Bool),
+    ("text", Feature::String),
+    ("published_at", Feature::String),
+    ("like_count", Feature::Int),
+    ("sentiment", Feature::Float),
+    ("language", Feature::String),
+    ("duplicate_of", Feature::String),
+    ("spam_score", Feature::Float),
+    ("mentions", Feature::Strings),
+    ("links", Feature::Strings),
+    ("timestamps", Feature::Ints),
+    ("text_translated", Feature::String),
+];
+
+impl Feature {
+    /// The value written for a row without one.
+    fn missing(self) -> Value {
+        match self {
+            Feature::Bool => Value::Bool(false),
+            Feature::Strings | Feature::Ints => Value::Array(vec![]),
+            Feature::String | Feature::Int | Feature::Float => Value::Null,
+        }
+    }
+
+    fn describe(self, name: &'static str) -> FeatureInfo {
+        let (dtype, sequence) = match self {
+            Feature::String => (Some("string"), None),
+            Feature::Int => (Some("int64"), None),
+            Feature::Float => (Some("float64"), None),
+            Feature::Bool => (Some("bool"), None),
+            Feature::Strings => (None, Some("string")),
+            Feature::Ints => (None, Some("int64")),
+        };
+        FeatureInfo {
+            name,
+            dtype,
+            sequence,
+        }
+    }
+}
+
+/// The metadata at the top of the dataset card, which `datasets` reads the files and column types
+/// from.
+#[derive(Serialize)]
+struct CardMetadata {
+    pretty_name: String,
+    configs: Vec<Config>,
+    dataset_info: DatasetInfo,
+}
+
+#[derive(Serialize)]
+struct Config {
+    config_name: &'static str,
+    data_files: Vec<DataFiles>,
+}
+
+#[derive(Serialize)]
+struct DataFiles {
+    split: &'static str,
+    path: &'static str,
+}
+
+#[derive(Serialize)]
+struct DatasetInfo {
+    features: Vec<FeatureInfo>,
+    splits: Vec<SplitInfo>,
+}
+
+#[derive(Serialize)]
+struct FeatureInfo {
+    name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dtype: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct SplitInfo {
+    name: &'static str,
+    num_examples: usize,
+}
+
+/// Writes a directory that the Hugging Face `datasets` library loads as it is: a row for every
+/// comment and reply in the train split, and a `README.md` dataset card declaring the columns,
+/// with a stub of a description to fill in before publishing it.
+pub struct HfDatasetSink {
+    dir: PathBuf,
+    header: Header,
+    train: BufWriter<File>,
+    videos: usize,
+    rows: usize,
+}
+
+impl HfDatasetSink {
+    pub fn new(dir: &Path, header: &Header) -> Result<Self> {
+        let path = dir.join(TRAIN_PATH);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Unable to create directory {}", parent.display()))?;
+        }
+        let train =
+            File::create(&path).with_context(|| format!("Unable to create {}", path.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            header: header.clone(),
+            train: BufWriter::new(train),
+            videos: 0,
+            rows: 0,
+        })
+    }
+
+    fn write_card(&self) -> Result<()> {
+        let handle = &self.header.channel_handle;
+        let metadata = CardMetadata {
+            pretty_name: format!("Comments on {handle}"),
+            configs: vec![Config {
+                config_name: "default",
+                data_files: vec![DataFiles {
+                    split: "train",
+                    path: TRAIN_PATH,
+                }],
+            }],
+            dataset_info: DatasetInfo {
+                features: FEATURES
+                    .iter()
+                    .map(|&(name, feature)| feature.describe(name))
+                    .collect(),
+                splits: vec![SplitInfo {
+                    name: "train",
+                    num_examples: self.rows,
+                }],
+            },
+        };
+        let path = self.dir.join("README.md");
+        let file =
+            File::create(&path).with_context(|| format!("Unable to create {}", path.display()))?;
+        let mut w = BufWriter::new(file);
+        // serde_yaml starts documents without the leading `---` that the card's metadata needs.
+        writeln!(w, "---")?;
+        serde_yaml::to_writer(&mut w, &metadata)?;
+        writeln!(w, "---")?;
+        writeln!(w)?;
+        writeln!(w, "# Comments on {handle}")?;
+        writeln!(w)?;
+        let fetched = match self.header.fetched_at {
+            Some(at) => format!(" on {}", at.format("%Y-%m-%d")),
+            None => String::new(),
+        };
+        writeln!(
+            w,
+            "{} comments and replies on {} videos of the YouTube channel {handle}, downloaded with youtube-comments {}{fetched}. \
+            Each row is a comment or a reply. Replies have the id of the comment they answer in `parent_id` and a `depth` of 1.",
+            self.rows, self.videos, self.header.tool_version
+        )?;
+        writeln!(w)?;
+        writeln!(w, "```python")?;
+        writeln!(w, "from datasets import load_dataset")?;
+        writeln!(w)?;
+        writeln!(w, "dataset = load_dataset(\"path/to/this/directory\")")?;
+        writeln!(w, "```")?;
+        writeln!(w)?;
+        writeln!(w, "## Dataset Creation")?;
+        writeln!(w)?;
+        writeln!(
+            w,
+            "<!-- Why the comments were collected, and which videos and comments were kept. -->"
+        )?;
+        writeln!(w)?;
+        writeln!(w, "## Personal Information")?;
+        writeln!(w)?;
+        let anonymized = if self.header.filters.anonymized {
+            "Authors were replaced with pseudonyms."
+        } else {
+            "Authors are identified by their display names and channel ids."
+        };
+        let redacted = if self.header.filters.redact_pii {
+            " Email addresses and phone numbers in the text were masked."
+        } else {
+            ""
+        };
+        writeln!(
+            w,
+            "The comments were written by YouTube users. {anonymized}{redacted}"
+        )?;
+        writeln!(w)?;
+        writeln!(w, "## License")?;
+        writeln!(w)?;
+        writeln!(
+            w,
+            "<!-- The terms the comments may be used and shared under. -->"
+        )?;
+        w.flush()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for HfDatasetSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        for record in comment_records(video) {
+            let Value::Object(mut fields) = serde_json::to_value(&record)? else {
+                unreachable!("records are written as objects");
+            };
+            let row: Map<String, Value> = FEATURES
+                .iter()
+                .map(|&(name, feature)| {
+                    let value = fields.remove(name).unwrap_or_else(|| feature.missing());
+                    (name.to_string(), value)
+                })
+                .collect();
+            serde_json::to_writer(&mut self.train, &row)?;
+            writeln!(self.train)?;
+            self.rows += 1;
+        }
+        self.videos += 1;
+        self.train.flush()?;
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.train.flush()?;
+        self.write_card()
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum, ValueHint};
+use google_youtube3::chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::info;
+
+use crate::{
+    add_statistics, channel_picker, connect, csv::quote, get_channel, get_channel_ids,
+    get_playlist_items, sort_videos, warnings::Warnings, AccessScope, ClientArgs, VideoOrder,
+};
+
+#[derive(Args)]
+pub struct ListVideosArgs {
+    /// Handle of the channel whose uploads are listed. Ex: @smartereveryday.
+    #[arg(value_name = "@HANDLE", value_hint = ValueHint::Other)]
+    channel_handle: String,
+
+    /// Order to list the videos in. Defaults to the order of the channel's uploads, newest first.
+    #[arg(long, value_enum)]
+    video_order: Option<VideoOrder>,
+
+    /// Only list this many videos, taken from the start of --video-order.
+    #[arg(long)]
+    max_videos: Option<usize>,
+
+    /// How to print the videos.
+    #[arg(short, long, value_enum, default_value_t = ListFormat::Table)]
+    format: ListFormat,
+
+    /// File to write the list to instead of printing it.
+    #[arg(short, long, value_hint = ValueHint::FilePath)]
+    output_name: Option<String>,
+
+    #[command(flatten)]
+    pub client: ClientArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ListFormat {
+    /// Aligned columns to read in the terminal.
+    Table,
+    /// A CSV row for every video, with a header row.
+    Csv,
+    /// A JSON array of videos.
+    Json,
+    /// Only the id of every video, one per line.
+    Ids,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ListedVideo {
+    video_id: String,
+    title: String,
+    published_at: Option<DateTime<Utc>>,
+    /// Missing when the channel hides it or comments are turned off.
+    comment_count: Option<u64>,
+}
+
+/// List the channel's uploads with their publish dates and comment counts, without fetching any
+/// comments. This costs a unit of quota per 50 videos, twice over: once to list them and once to
+/// look up their comment counts.
+pub async fn run(args: &ListVideosArgs) -> Result<()> {
+    let client = connect(&args.client, AccessScope::Readonly).await?;
+    let handle = &args.channel_handle;
+    let ids = get_channel_ids(handle, &client.http).await?;
+    let channel_id = match ids.as_slice() {
+        [id] => id.clone(),
+        _ => {
+            let candidates = channel_picker::lookup(&ids, &client).await?;
+            channel_picker::pick(handle, candidates)?.id
+        }
+    };
+    info!(channel_handle = handle, channel_id, "Resolved channel");
+    let (playlist_id, _) = get_channel(&channel_id, &client).await?;
+
+    let mut warnings = Warnings::default();
+    let mut items = get_playlist_items(&playlist_id, &client, &mut warnings).await?;
+    add_statistics(&mut items, &client).await?;
+    if let Some(order) = args.video_order {
+        sort_videos(&mut items, order);
+    }
+    if let Some(max_videos) = args.max_videos {
+        items.truncate(max_videos);
+    }
+    info!(videos = items.len(), "Listed videos");
+
+    let videos: Vec<ListedVideo> = items
+        .into_iter()
+        .map(|item| ListedVideo {
+            video_id: item.video_id,
+            title: item.title,
+            published_at: item.published_at,
+            comment_count: item.comment_count,
+        })
+        .collect();
+    match &args.output_name {
+        Some(name) => {
+            let file = File::create(name).with_context(|| format!("Unable to create {name}"))?;
+            let mut w = BufWriter::new(file);
+            write_videos(&mut w, &videos, args.format)?;
+            w.flush()?;
+        }
+        None => write_videos(&mut io::stdout().lock(), &videos, args.format)?,
+    }
+    Ok(())
+}
+
+fn write_videos(w: &mut impl Write, videos: &[ListedVideo], format: ListFormat) -> Result<()> {
+    let published = |video: &ListedVideo| {
+        video
+            .published_at
+            .map(|at| at.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    };
+    let comments = |video: &ListedVideo| {
+        video
+            .comment_count
+            .map(|count| count.to_string())
+            .unwrap_or_default()
+    };
+    match format {
+        ListFormat::Table => {
+            writeln!(
+                w,
+                "{:<11}  {:<10}  {:>8}  Title",
+                "Video", "Published", "Comments"
+            )?;
+            for video in videos {
+                writeln!(
+                    w,
+                    "{:<11}  {:<10}  {:>8}  {}",
+                    video.video_id,
+                    published(video),
+                    comments(video),
+                    video.title
+                )?;
+            }
+        }
+        ListFormat::Csv => {
+            writeln!(w, "video_id,title,published_at,comment_count")?;
+            for video in videos {
+                writeln!(
+                    w,
+                    "{},{},{},{}",
+                    video.video_id,
+                    quote(&video.title),
+                    video
+                        .published_at
+                        .map(|at| at.to_rfc3339())
+                        .unwrap_or_default(),
+                    comments(video)
+                )?;
+            }
+        }
+        ListFormat::Json => {
+            serde_json::to_writer_pretty(&mut *w, videos)?;
+            writeln!(w)?;
+        }
+        ListFormat::Ids => {
+            for video in videos {
+                writeln!(w, "{}", video.video_id)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use tokio::sync::mpsc::Sender;
+use tracing::{debug, info};
+
+use crate::{
+    filter::VideoFilters,
+    metadata::MetadataCache,
+    progress::Progress,
+    source::CommentSource,
+    warnings::{WarningCode, Warnings},
+    Client, PlaylistItem, Thumbnails,
+};
+
+/// Videos listed ahead of the one whose comments are being fetched, two pages' worth, so the next
+/// page is already on its way by the time it's needed.
+pub const BUFFER: usize = 100;
+
+/// The items of a playlist, a page at a time.
+pub struct PlaylistPages<'a, S> {
+    playlist_id: &'a str,
+    source: &'a S,
+    /// Missing once the last page has been fetched.
+    page_token: Option<String>,
+}
+
+impl<'a, S: CommentSource> PlaylistPages<'a, S> {
+    pub fn new(playlist_id: &'a str, source: &'a S) -> Self {
+        Self {
+            playlist_id,
+            source,
+            page_token: Some(String::new()),
+        }
+    }
+
+    /// Fetch the next page, or return `None` after the last one. Items without a video id or
+    /// title are left out with a warning.
+    pub async fn next(&mut self, warnings: &mut Warnings) -> Result<Option<Vec<PlaylistItem>>> {
+        let Some(page_token) = self.page_token.take() else {
+            return Ok(None);
+        };
+        let playlist_items = self
+            .source
+            .playlist_items(self.playlist_id, &page_token)
+            .await?;
+        debug!(
+            endpoint = "playlistItems.list",
+            playlist_id = self.playlist_id,
+            page_token,
+            items = playlist_items.items.as_ref().map_or(0, |i| i.len()),
+            "Fetched page"
+        );
+        self.page_token = playlist_items.next_page_token;
+
+        let mut items = vec![];
+        for item in playlist_items.items.unwrap_or_default() {
+            let Some(video_id) = item
+                .content_details
+                .as_ref()
+                .and_then(|cd| cd.video_id.as_ref())
+                .cloned()
+            else {
+                warnings.push(
+                    WarningCode::MalformedPlaylistItem,
+                    None,
+                    None,
+                    format!("Playlist item {:?} has no video id", item.id),
+                );
+                continue;
+            };
+
+            let Some(title) = item
+                .snippet
+                .as_ref()
+                .and_then(|s| s.title.as_ref())
+                .cloned()
+            else {
+                warnings.push(
+                    WarningCode::MalformedPlaylistItem,
+                    Some(&video_id),
+                    None,
+                    "Playlist item has no title",
+                );
+                continue;
+            };
+
+            let published_at = item
+                .content_details
+                .as_ref()
+                .and_then(|cd| cd.video_published_at);
+            let thumbnails = item
+                .snippet
+                .and_then(|s| s.thumbnails)
+                .map(Thumbnails::from_api);
+            items.push(PlaylistItem {
+                title,
+                video_id,
+                published_at,
+                thumbnails,
+                view_count: None,
+                comment_count: None,
+                duration: None,
+                category_id: None,
+            })
+        }
+        Ok(Some(items))
+    }
+}
+
+/// Lists a playlist into a bounded queue while the download fetches comments from the other end,
+/// so the first video starts as soon as the first page is listed instead of once the whole channel
+/// is. Only used when nothing needs every video up front, such as sorting them or asking to confirm.
+pub struct PlaylistStream<'a> {
+    pub playlist_id: &'a str,
+    pub client: &'a Client,
+    pub video_filters: &'a VideoFilters,
+    pub max_videos: Option<usize>,
+    /// Videos already written from the checkpoint or an archive being backfilled, which still
+    /// count towards `max_videos` so a resumed run picks the same videos as the original.
+    pub done: &'a HashSet<String>,
+    pub progress: &'a Progress,
+}
+
+impl PlaylistStream<'_> {
+    /// Send the videos to `tx` a page at a time, after looking up their details and applying the
+    /// video filters. Stops early without an error once nothing is receiving them, including when
+    /// a request fails after that, since the download has already stopped for its own reasons.
+    pub async fn run(
+        &self,
+        metadata: &mut MetadataCache,
+        warnings: &mut Warnings,
+        tx: Sender<PlaylistItem>,
+    ) -> Result<()> {
+        let result = self.list(metadata, warnings, &tx).await;
+        if tx.is_closed() {
+            return Ok(());
+        }
+        result.context("Unable to list the channel's videos")
+    }
+
+    async fn list(
+        &self,
+        metadata: &mut MetadataCache,
+        warnings: &mut Warnings,
+        tx: &Sender<PlaylistItem>,
+    ) -> Result<()> {
+        let mut pages = PlaylistPages::new(self.playlist_id, self.client);
+        let (mut listed, mut empty) = (0, 0);
+        while let Some(mut page) = pages.next(warnings).await? {
+            metadata.add_details(&mut page, self.client).await?;
+            page.retain(|item| self.video_filters.keep(item));
+            if let Some(max_videos) = self.max_videos {
+                page.truncate(max_videos - listed);
+            }
+            listed += page.len();
+            empty += page
+                .iter()
+                .filter(|item| item.comment_count == Some(0))
+                .count();
+            page.retain(|item| !self.done.contains(&item.video_id));
+
+            let expected_pages: Vec<_> = page.iter().map(PlaylistItem::expected_pages).collect();
+            self.progress.videos_listed(&expected_pages);
+            for item in page {
+                if tx.send(item).await.is_err() {
+                    return Ok(());
+                }
+            }
+            if self
+                .max_videos
+                .is_some_and(|max_videos| listed >= max_videos)
+            {
+                break;
+            }
+        }
+        info!(videos = listed, without_comments = empty, "Listed videos");
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::{bail, Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::{
+    warnings::{WarningCode, Warnings},
+    watch_page,
+};
+
+/// Endpoint the YouTube web player pages through chat replays with. The Data API only serves the
+/// chat of broadcasts that are still live, so replays have to come from here instead.
+const REPLAY_URL: &str = "https://www.youtube.com/youtubei/v1/live_chat/get_live_chat_replay";
+
+/// Web client the replay endpoint expects requests to come from.
+const CLIENT_VERSION: &str = "2.20240101.00.00";
+
+/// A message from the live chat of a stream or premiere.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChatMessage {
+    pub id: String,
+    pub author_name: String,
+    pub author_channel_id: Option<String>,
+    pub text: String,
+    /// Position in the video the message was shown at.
+    pub video_offset_ms: Option<i64>,
+    /// Amount paid for a Super Chat, as displayed, e.g. "$5.00".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_amount: Option<String>,
+}
+
+/// Fetch the chat replay of a video. Returns None for videos that weren't streamed with chat, and
+/// records a warning if the replay couldn't be fetched.
+pub async fn fetch(
+    http_client: &reqwest::Client,
+    video_id: &str,
+    warnings: &mut Warnings,
+) -> Option<Vec<ChatMessage>> {
+    match fetch_replay(http_client, video_id).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            warnings.push(
+                WarningCode::LiveChatUnavailable,
+                Some(video_id),
+                None,
+                format!("Unable to fetch live chat replay: {e:#}"),
+            );
+            None
+        }
+    }
+}
+
+async fn fetch_replay(
+    http_client: &reqwest::Client,
+    video_id: &str,
+) -> Result<Option<Vec<ChatMessage>>> {
+    let page = watch_page::fetch(http_client, video_id).await?;
+    let initial_data = watch_page::embedded_json(&page, "ytInitialData")
+        .context("Watch page has no ytInitialData")?;
+    let Some(mut continuation) = initial_data
+        .pointer("/contents/twoColumnWatchNextResults/conversationBar/liveChatRenderer/continuations/0/reloadContinuationData/continuation")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+    else {
+        return Ok(None);
+    };
+
+    let mut messages = vec![];
+    loop {
+        let response: Value = http_client
+            .post(REPLAY_URL)
+            .json(&json!({
+                "context": { "client": { "clientName": "WEB", "clientVersion": CLIENT_VERSION } },
+                "continuation": continuation,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let Some(chat) = response.pointer("/continuationContents/liveChatContinuation") else {
+            bail!("Unexpected live chat replay response");
+        };
+
+        let actions = chat["actions"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for action in actions {
+            let replay = &action["replayChatItemAction"];
+            let video_offset_ms = replay["videoOffsetTimeMsec"]
+                .as_str()
+                .and_then(|offset| offset.parse().ok());
+            let items = replay["actions"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            for item in items {
+                if let Some(message) = parse_message(&item["addChatItemAction"]["item"]) {
+                    messages.push(ChatMessage {
+                        video_offset_ms,
+                        ..message
+                    });
+                }
+            }
+        }
+        debug!(
+            video_id,
+            actions = actions.len(),
+            "Fetched live chat replay page"
+        );
+
+        // The last page has a seek continuation instead of a replay one.
+        match chat
+            .pointer("/continuations/0/liveChatReplayContinuationData/continuation")
+            .and_then(Value::as_str)
+        {
+            Some(next) if !actions.is_empty() => continuation = next.to_string(),
+            _ => break,
+        }
+    }
+
+    Ok(Some(messages))
+}
+
+/// Text and Super Chat messages are kept, while membership and other system messages are skipped.
+fn parse_message(item: &Value) -> Option<ChatMessage> {
+    let (renderer, paid_amount) = if let Some(r) = item.get("liveChatTextMessageRenderer") {
+        (r, None)
+    } else if let Some(r) = item.get("liveChatPaidMessageRenderer") {
+        let amount = r["purchaseAmountText"]["simpleText"]
+            .as_str()
+            .map(str::to_string);
+        (r, amount)
+    } else {
+        return None;
+    };
+
+    let text = renderer["message"]["runs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|run| {
+            run["text"]
+                .as_str()
+                .or_else(|| run["emoji"]["shortcuts"][0].as_str())
+                .or_else(|| run["emoji"]["emojiId"].as_str())
+        })
+        .collect();
+
+    Some(ChatMessage {
+        id: renderer["id"].as_str()?.to_string(),
+        author_name: renderer["authorName"]["simpleText"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        author_channel_id: renderer["authorExternalChannelId"]
+            .as_str()
+            .map(str::to_string),
+        text,
+        video_offset_ms: None,
+        paid_amount,
+    })
+}
@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+use crate::{
+    analysis::AnalysisReport, errors::VideoError, profanity::ProfanitySummary, warnings::Warning,
+    Video,
+};
+
+/// Written next to the output file to describe how the run went.
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    /// False if the run was interrupted or some videos couldn't be fetched.
+    pub complete: bool,
+    pub warnings: Vec<Warning>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<VideoError>,
+    pub integrity: Integrity,
+    #[serde(skip_serializing_if = "AnalysisReport::is_empty")]
+    pub analysis: AnalysisReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profanity: Option<ProfanitySummary>,
+    /// Comments and replies left out for being in the --seen-store already.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub already_archived: Option<usize>,
+}
+
+/// How complete the threads in the output are, judged by the reply counts YouTube reports for
+/// them. Only covers threads kept by the filters.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Integrity {
+    pub threads: usize,
+    /// Threads with fewer replies than YouTube reports.
+    pub incomplete_threads: usize,
+    pub missing_replies: u64,
+    /// Videos with comments turned off, which have no threads to check.
+    pub comments_disabled: usize,
+}
+
+impl Integrity {
+    pub fn add(&mut self, video: &Video) {
+        if video.comments_disabled {
+            self.comments_disabled += 1;
+        }
+        for thread in &video.comments {
+            self.threads += 1;
+            if thread.missing_replies > 0 {
+                self.incomplete_threads += 1;
+                self.missing_replies += u64::from(thread.missing_replies);
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use anyhow::{bail, Result};
+use clap::{Args, ValueHint};
+use google_youtube3::chrono::{DateTime, Utc};
+
+use crate::{
+    diff::{child_key, parent_key},
+    edits::record_edits,
+    errors::VideoError,
+    file_sink,
+    output::{self, Header, Output, SCHEMA_VERSION},
+    FileLayout, Format, ParentComment, Video,
+};
+
+#[derive(Args)]
+pub struct MergeArgs {
+    /// Names of the JSON files to combine.
+    #[arg(required = true, num_args = 2.., value_hint = ValueHint::FilePath)]
+    input_names: Vec<String>,
+
+    /// Name of the JSON file to write the combined output to. It may be one of the inputs.
+    #[arg(short, long, value_hint = ValueHint::FilePath)]
+    output_name: String,
+}
+
+/// Combine several output files of the same channel into one, keeping every video, thread and
+/// reply found in any of them. Where they overlap, the data from the most recently fetched file
+/// wins. Files without a fetch time count as older than those with one, and otherwise later
+/// files on the command line count as newer.
+pub async fn run(args: &MergeArgs) -> Result<()> {
+    let mut outputs = args
+        .input_names
+        .iter()
+        .map(|name| output::read(name))
+        .collect::<Result<Vec<_>>>()?;
+    outputs.sort_by_key(|o| o.header.as_ref().and_then(|h| h.fetched_at));
+
+    let channels: HashSet<&str> = outputs
+        .iter()
+        .filter_map(|o| o.header.as_ref()?.channel_id.as_deref())
+        .collect();
+    if channels.len() > 1 {
+        bail!("The files hold comments from different channels");
+    }
+
+    let mut header = outputs
+        .iter()
+        .rev()
+        .find_map(|o| o.header.clone())
+        .unwrap_or_else(|| Header::new(""));
+    header.schema_version = SCHEMA_VERSION;
+
+    let (videos, errors) = merge(outputs);
+    let mut sink = file_sink(
+        &FileLayout::new(Format::Json),
+        &header,
+        Path::new(&args.output_name),
+    )?;
+    for video in &videos {
+        sink.write_video(video).await?;
+    }
+    sink.write_errors(&errors).await?;
+    sink.finish().await?;
+
+    eprintln!(
+        "Merged {} files into {} videos",
+        args.input_names.len(),
+        videos.len()
+    );
+    Ok(())
+}
+
+/// Merge outputs ordered from oldest to newest. Videos keep the order they first appear in, and
+/// errors are only kept for videos no file has.
+fn merge(outputs: Vec<Output>) -> (Vec<Video>, Vec<VideoError>) {
+    let mut videos: Vec<Video> = vec![];
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut errors: Vec<VideoError> = vec![];
+    for output in outputs {
+        let fetched_at = output.header.as_ref().and_then(|h| h.fetched_at);
+        for video in output.videos {
+            match indices.get(&video.id) {
+                Some(&i) => {
+                    let older = std::mem::replace(&mut videos[i], video);
+                    merge_comments(&mut videos[i], older, fetched_at);
+                }
+                None => {
+                    indices.insert(video.id.clone(), videos.len());
+                    videos.push(video);
+                }
+            }
+        }
+        errors.extend(output.errors);
+    }
+
+    errors.retain(|e| !indices.contains_key(&e.video_id));
+    let mut failed = HashSet::new();
+    errors.reverse();
+    errors.retain(|e| failed.insert(e.video_id.clone()));
+    errors.reverse();
+    (videos, errors)
+}
+
+/// Add the threads and replies of an older copy of a video that are missing from the newer one,
+/// such as comments deleted in between or left out by filters. Those in both copies keep the edit
+/// histories of both, with edits made in between noted as detected at `fetched_at`, when the newer
+/// copy was fetched.
+pub fn merge_comments(newer: &mut Video, older: Video, fetched_at: Option<DateTime<Utc>>) {
+    let mut threads: HashMap<String, usize> = newer
+        .comments
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (parent_key(c), i))
+        .collect();
+    for old_thread in older.comments {
+        let key = parent_key(&old_thread);
+        match threads.get(&key) {
+            Some(&i) => merge_replies(&mut newer.comments[i], old_thread, fetched_at),
+            None => {
+                threads.insert(key, newer.comments.len());
+                newer.comments.push(old_thread);
+            }
+        }
+    }
+}
+
+fn merge_replies(
+    newer: &mut ParentComment,
+    older: ParentComment,
+    fetched_at: Option<DateTime<Utc>>,
+) {
+    record_edits(
+        &newer.text,
+        &mut newer.edit_history,
+        older.text,
+        older.updated_at,
+        older.edit_history,
+        fetched_at,
+    );
+    let replies: HashMap<String, usize> = newer
+        .children
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (child_key(c), i))
+        .collect();
+    for old_reply in older.children {
+        match replies.get(&child_key(&old_reply)) {
+            Some(&i) => {
+                let reply = &mut newer.children[i];
+                record_edits(
+                    &reply.text,
+                    &mut reply.edit_history,
+                    old_reply.text,
+                    old_reply.updated_at,
+                    old_reply.edit_history,
+                    fetched_at,
+                );
+            }
+            None => newer.children.push(old_reply),
+        }
+    }
+    // Replies only the older copy had may fill in some of the newer one's gaps.
+    newer.missing_replies = newer.missing_replies.min(
+        newer
+            .reply_count
+            .saturating_sub(newer.children.len() as u32),
+    );
+}
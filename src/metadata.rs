@@ -0,0 +1,53 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Result;
+
+use crate::{add_statistics, Client, PlaylistItem};
+
+/// The details of a video the video filters go by, which don't change once it's uploaded.
+struct Details {
+    duration: Option<Duration>,
+    category_id: Option<String>,
+}
+
+/// Details of every video looked up so far, shared by the download and the polls of watch mode,
+/// so that listing a video again doesn't cost another lookup.
+#[derive(Default)]
+pub struct MetadataCache {
+    videos: HashMap<String, Details>,
+}
+
+impl MetadataCache {
+    /// Fill in the details of the items, looking up those not seen before with [`add_statistics`],
+    /// 50 to a request. Items seen before get their details from the cache, but no view or
+    /// comment counts, since those go stale.
+    pub async fn add_details(&mut self, items: &mut [PlaylistItem], client: &Client) -> Result<()> {
+        let mut missing: Vec<PlaylistItem> = items
+            .iter()
+            .filter(|item| !self.videos.contains_key(&item.video_id))
+            .cloned()
+            .collect();
+        add_statistics(&mut missing, client).await?;
+        let mut looked_up: HashMap<String, PlaylistItem> = missing
+            .into_iter()
+            .map(|item| (item.video_id.clone(), item))
+            .collect();
+
+        for item in items {
+            if let Some(fresh) = looked_up.remove(&item.video_id) {
+                self.videos.insert(
+                    fresh.video_id.clone(),
+                    Details {
+                        duration: fresh.duration,
+                        category_id: fresh.category_id.clone(),
+                    },
+                );
+                *item = fresh;
+            } else if let Some(details) = self.videos.get(&item.video_id) {
+                item.duration = details.duration;
+                item.category_id = details.category_id.clone();
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    convert::Infallible,
+    fmt::Write,
+    net::SocketAddr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::{Context, Result};
+use google_youtube3::{
+    chrono::Utc,
+    hyper::{
+        self,
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, StatusCode,
+    },
+};
+use tracing::{error, info};
+
+use crate::{quota, Video};
+
+/// Comments and replies written by this run, including those found by later polls in watch mode.
+static COMMENTS_FETCHED: AtomicU64 = AtomicU64::new(0);
+
+/// Videos that couldn't be fetched, plus polls that failed in watch mode.
+static ERRORS: AtomicU64 = AtomicU64::new(0);
+
+/// Unix time the download or the last poll finished without an error, or zero if none has yet.
+static LAST_SUCCESS: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_video(video: &Video) {
+    let replies: usize = video.comments.iter().map(|c| c.children.len()).sum();
+    COMMENTS_FETCHED.fetch_add((video.comments.len() + replies) as u64, Ordering::Relaxed);
+}
+
+pub fn record_error() {
+    ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_success() {
+    LAST_SUCCESS.store(Utc::now().timestamp() as u64, Ordering::Relaxed);
+}
+
+/// Start serving the metrics at `/metrics` on the address in the background, in the Prometheus
+/// text format. Fails straight away if the address can't be bound.
+pub fn serve(addr: SocketAddr) -> Result<()> {
+    let server = hyper::Server::try_bind(&addr)
+        .with_context(|| format!("Unable to serve metrics on {addr}"))?
+        .serve(make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(handle))
+        }));
+    info!(%addr, "Serving metrics");
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            error!("Metrics server failed: {e:#}");
+        }
+    });
+    Ok(())
+}
+
+async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let response = if request.uri().path() == "/metrics" {
+        Response::builder()
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .body(Body::from(render()))
+    } else {
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+    };
+    Ok(response.expect("the response is valid"))
+}
+
+fn render() -> String {
+    let metrics = [
+        (
+            "youtube_comments_api_requests_total",
+            "counter",
+            "API requests made.",
+            quota::requests(),
+        ),
+        (
+            "youtube_comments_quota_units_total",
+            "counter",
+            "Estimated quota units spent.",
+            quota::used(),
+        ),
+        (
+            "youtube_comments_comments_fetched_total",
+            "counter",
+            "Comments and replies written.",
+            COMMENTS_FETCHED.load(Ordering::Relaxed),
+        ),
+        (
+            "youtube_comments_errors_total",
+            "counter",
+            "Videos that couldn't be fetched and polls that failed.",
+            ERRORS.load(Ordering::Relaxed),
+        ),
+        (
+            "youtube_comments_last_success_timestamp_seconds",
+            "gauge",
+            "Unix time the download or the last poll succeeded.",
+            LAST_SUCCESS.load(Ordering::Relaxed),
+        ),
+    ];
+    let mut text = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(text, "# HELP {name} {help}");
+        let _ = writeln!(text, "# TYPE {name} {kind}");
+        let _ = writeln!(text, "{name} {value}");
+    }
+    text
+}
@@ -0,0 +1,155 @@
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum, ValueHint};
+use tracing::info;
+
+use crate::{connect, output, quota, AccessScope, ClientArgs};
+
+/// Most comments the API accepts in one moderation status change.
+const BATCH_SIZE: usize = 50;
+
+#[derive(Args)]
+pub struct ModerateArgs {
+    /// Name of a file of comment ids, one per line, or of a JSON output file, in which case every comment and reply in it is moderated. Use - to read from stdin.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+
+    /// What to do with the comments.
+    #[arg(short, long, value_enum)]
+    action: ModerationAction,
+
+    /// Also ban the authors of the comments from commenting on the channel. Only valid with --action reject.
+    #[arg(long)]
+    ban_author: bool,
+
+    /// Print what would be done without changing anything.
+    #[arg(long)]
+    dry_run: bool,
+
+    #[command(flatten)]
+    pub client: ClientArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ModerationAction {
+    /// Make the comments visible to everyone.
+    Publish,
+    /// Hide the comments until they're reviewed.
+    HoldForReview,
+    /// Hide the comments, as for spam. YouTube no longer supports flagging comments as spam through the API, so this is the closest equivalent.
+    Reject,
+    /// Permanently delete the comments. Only comments written by the authenticated account can be deleted.
+    Delete,
+}
+
+impl ModerationAction {
+    fn moderation_status(self) -> Option<&'static str> {
+        match self {
+            ModerationAction::Publish => Some("published"),
+            ModerationAction::HoldForReview => Some("heldForReview"),
+            ModerationAction::Reject => Some("rejected"),
+            ModerationAction::Delete => None,
+        }
+    }
+}
+
+/// Apply a moderation action to every comment in the input. Channel owners need to sign in with
+/// OAuth, so an API key can't be used.
+pub async fn run(args: &ModerateArgs) -> Result<()> {
+    if args.ban_author && args.action != ModerationAction::Reject {
+        bail!("--ban-author can only be used with --action reject");
+    }
+    if args.client.uses_api_key() {
+        bail!("Moderating comments requires OAuth, so an API key can't be used");
+    }
+
+    let ids = read_comment_ids(&args.input_name)?;
+    if ids.is_empty() {
+        bail!("No comment ids found in {}", args.input_name);
+    }
+    if args.dry_run {
+        for id in &ids {
+            println!("Would {:?} {id}", args.action);
+        }
+        println!("{} comments would be changed", ids.len());
+        return Ok(());
+    }
+
+    let client = connect(&args.client, AccessScope::Full).await?;
+    match args.action.moderation_status() {
+        Some(status) => {
+            for batch in ids.chunks(BATCH_SIZE) {
+                client.before_request().await;
+                quota::record(quota::WRITE_COST - 1);
+                client
+                    .credential()
+                    .1
+                    .youtube
+                    .comments()
+                    .set_moderation_status(&batch.to_vec(), status)
+                    .ban_author(args.ban_author)
+                    .doit()
+                    .await
+                    .with_context(|| format!("Unable to set comments to {status}"))?;
+                info!(comments = batch.len(), status, "Set moderation status");
+            }
+        }
+        None => {
+            for id in &ids {
+                client.before_request().await;
+                quota::record(quota::WRITE_COST - 1);
+                client
+                    .credential()
+                    .1
+                    .youtube
+                    .comments()
+                    .delete(id)
+                    .doit()
+                    .await
+                    .with_context(|| format!("Unable to delete comment {id}"))?;
+                info!(id, "Deleted comment");
+            }
+        }
+    }
+
+    println!(
+        "{:?} applied to {} comments, using {} quota units",
+        args.action,
+        ids.len(),
+        quota::used()
+    );
+    Ok(())
+}
+
+/// Blank lines and lines starting with # are ignored in a list of ids.
+pub fn read_comment_ids(name: &str) -> Result<Vec<String>> {
+    let contents = if name == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        fs::read_to_string(name).with_context(|| format!("Unable to read {name}"))?
+    };
+
+    if contents.trim_start().starts_with(['[', '{']) {
+        let videos = output::parse(name, &contents)?.videos;
+        return Ok(videos
+            .iter()
+            .flat_map(|v| &v.comments)
+            .flat_map(|c| std::iter::once(&c.id).chain(c.children.iter().map(|r| &r.id)))
+            .filter(|id| !id.is_empty())
+            .cloned()
+            .collect());
+    }
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
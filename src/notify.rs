@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use serde_json::json;
+use tracing::warn;
+
+use indicatif::HumanDuration;
+
+use crate::{exit, quota, smtp::Smtp};
+
+/// Somewhere to say how a run ended, as given to `--notify`.
+enum Target {
+    /// A Slack incoming webhook URL.
+    Slack(String),
+    /// An email address, sent to through the SMTP server from the environment.
+    Email(String),
+}
+
+impl Target {
+    fn parse(target: &str) -> Result<Self> {
+        match target.split_once(':') {
+            Some(("slack", url)) if url.starts_with("https://") => Ok(Target::Slack(url.into())),
+            Some(("email", address)) if address.contains('@') => Ok(Target::Email(address.into())),
+            _ => bail!(
+                "Invalid --notify target {target}, expected slack:<webhook URL> or email:<address>"
+            ),
+        }
+    }
+}
+
+/// How a download ended, to notify about.
+pub struct Summary<'a> {
+    pub channel: &'a str,
+    pub videos: usize,
+    pub comments: usize,
+    pub replies: usize,
+    pub elapsed: Duration,
+}
+
+/// Sends a message to every target when a run finishes or fails.
+pub struct Notifier {
+    http: reqwest::Client,
+    targets: Vec<Target>,
+    smtp: Option<Smtp>,
+}
+
+impl Notifier {
+    /// Parse the targets, reading the SMTP settings if any of them is an email address so that
+    /// missing ones are noticed before the run rather than after it.
+    pub fn new(targets: &[String], proxy: Option<&str>) -> Result<Self> {
+        let targets = targets
+            .iter()
+            .map(|t| Target::parse(t))
+            .collect::<Result<Vec<_>>>()?;
+        let smtp = if targets.iter().any(|t| matches!(t, Target::Email(_))) {
+            Some(Smtp::from_env()?)
+        } else {
+            None
+        };
+        let mut http = reqwest::Client::builder();
+        if let Some(proxy) = proxy {
+            http = http.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        Ok(Self {
+            http: http.build()?,
+            targets,
+            smtp,
+        })
+    }
+
+    /// Tell every target how the run ended. Failing to reach one is only logged, so that it
+    /// doesn't hide how the run itself went.
+    pub async fn send(&self, summary: &Summary<'_>, result: &Result<()>) {
+        let counts = format!(
+            "{} videos, {} comments and {} replies, using about {} quota units",
+            summary.videos,
+            summary.comments,
+            summary.replies,
+            quota::used()
+        );
+        let elapsed = HumanDuration(summary.elapsed);
+        let (subject, text) = match result {
+            Ok(()) => (
+                format!("Downloaded the comments of {}", summary.channel),
+                format!(
+                    "Downloaded the comments of {} in {elapsed}: {counts}.",
+                    summary.channel
+                ),
+            ),
+            Err(e) => (
+                format!("Downloading the comments of {} failed", summary.channel),
+                format!(
+                    "Downloading the comments of {} failed after {elapsed}, having written {counts}, exiting with code {}:\n{e:#}",
+                    summary.channel,
+                    exit::code(e)
+                ),
+            ),
+        };
+
+        for target in &self.targets {
+            let sent = match target {
+                Target::Slack(url) => self
+                    .http
+                    .post(url)
+                    .json(&json!({ "text": text }))
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map(|_| ())
+                    .map_err(anyhow::Error::from),
+                Target::Email(address) => match &self.smtp {
+                    Some(smtp) => smtp.send(address, &subject, &text).await,
+                    None => unreachable!("SMTP is set up when there's an email target"),
+                },
+            };
+            if let Err(e) = sent {
+                warn!(error = format!("{e:#}"), "Unable to send notification");
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use anyhow::{bail, Context, Result};
+use google_youtube3::chrono::{DateTime, Utc};
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::VideoError,
+    profanity::ProfanityMode,
+    report::{comment_url, video_url},
+    ModerationStatus, Video, VideoOrder,
+};
+
+/// Version of the layout of the JSON output, increased whenever a field is removed or renamed or
+/// its meaning changes. Version 1 was a bare array of videos, without a header.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Written at the top of the JSON output, ahead of the videos, to describe how it was produced.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Header {
+    pub schema_version: u32,
+    /// Version of this tool that wrote the file.
+    pub tool_version: String,
+    /// Missing from files converted by `export` from the bare array of version 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    pub channel_handle: String,
+    /// Missing from files written before channel details were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<ChannelInfo>,
+    /// When the download started. Missing from files converted from version 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub filters: FilterSettings,
+}
+
+impl Header {
+    pub fn new(channel_handle: &str) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            channel_id: None,
+            channel_handle: channel_handle.to_string(),
+            channel: None,
+            fetched_at: None,
+            filters: FilterSettings::default(),
+        }
+    }
+}
+
+/// The channel as it was when the download started.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChannelInfo {
+    pub title: String,
+    /// The channel's own handle, which the one it was downloaded with may differ from in case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handle: Option<String>,
+    /// Rounded by YouTube to three significant figures. Missing if the channel hides it, and left
+    /// out of `--stable` output along with the video count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscriber_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video_count: Option<u64>,
+}
+
+/// The options of the download that decide which videos and comments made it into the output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FilterSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video_order: Option<VideoOrder>,
+    /// Title of the channel's playlist whose videos were fetched, if not all its uploads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_playlist: Option<String>,
+    /// Whether the videos in the channel's playlists were fetched along with its uploads.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_playlists: bool,
+    /// The search the videos were found with, instead of listing a channel's videos.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_after: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_before: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_videos: Option<usize>,
+    /// The video, or position in the list of videos, that the download skipped ahead to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_from: Option<String>,
+    /// Shortest video fetched, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_duration: Option<u64>,
+    /// Longest video fetched, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u64>,
+    /// Categories fetched, by id or name as they were given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_terms: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation_status: Option<ModerationStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment_filter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_likes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// Name of the file listing the authors whose comments were dropped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_authors: Option<String>,
+    /// Name of the file listing the only authors whose comments were kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only_authors: Option<String>,
+    /// Whether the comments the channel wrote itself were dropped.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exclude_owner_comments: bool,
+    #[serde(default)]
+    pub no_replies: bool,
+    #[serde(default)]
+    pub dedup: bool,
+    /// Spam score at or above which comments were dropped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drop_spam: Option<f64>,
+    /// What was done to comments containing a word from the wordlist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profanity: Option<ProfanityMode>,
+    /// Name of the file listing the words, if the built in list wasn't used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profanity_words: Option<String>,
+    #[serde(default)]
+    pub anonymized: bool,
+    /// Whether email addresses and phone numbers were masked in comment text.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redact_pii: bool,
+    /// Whether comment text was normalized to Unicode NFC.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub nfc: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strip_emoji: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub emoji_to_shortcode: bool,
+}
+
+/// A JSON output file. Files of version 1 have no header.
+pub struct Output {
+    pub header: Option<Header>,
+    pub videos: Vec<Video>,
+    pub errors: Vec<VideoError>,
+}
+
+/// The JSON output of a download: a header, followed by every video fetched.
+#[derive(Deserialize, JsonSchema)]
+// Named for the types generated from the schema, where Document would clash in TypeScript.
+#[schemars(rename = "CommentOutput")]
+struct Document {
+    #[serde(flatten)]
+    header: Header,
+    videos: Vec<Video>,
+    /// Videos that couldn't be fetched and are missing from `videos`.
+    #[serde(default)]
+    errors: Vec<VideoError>,
+}
+
+/// Print a JSON Schema describing the JSON output, generated from the same types it is written
+/// from.
+pub fn print_schema() -> Result<()> {
+    let schema = schema_for!(Document);
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+/// Read a JSON output file of any version, or stdin when the name is `-`.
+pub fn read(name: &str) -> Result<Output> {
+    let contents = if name == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        fs::read_to_string(name).with_context(|| format!("Unable to open {name}"))?
+    };
+    parse(name, &contents)
+}
+
+pub fn parse(name: &str, contents: &str) -> Result<Output> {
+    if contents.trim_start().starts_with('[') {
+        let mut videos: Vec<Video> = serde_json::from_str(contents)
+            .with_context(|| format!("Unable to parse {name} as comment JSON"))?;
+        add_missing_urls(&mut videos);
+        return Ok(Output {
+            header: None,
+            videos,
+            errors: vec![],
+        });
+    }
+
+    let mut document: Document = serde_json::from_str(contents)
+        .with_context(|| format!("Unable to parse {name} as comment JSON"))?;
+    if document.header.schema_version > SCHEMA_VERSION {
+        bail!(
+            "{name} has schema version {}, but this version of the tool only reads up to {SCHEMA_VERSION}",
+            document.header.schema_version
+        );
+    }
+    add_missing_urls(&mut document.videos);
+    Ok(Output {
+        header: Some(document.header),
+        videos: document.videos,
+        errors: document.errors,
+    })
+}
+
+/// Fill in the links of videos, comments and replies from files written before they were recorded.
+fn add_missing_urls(videos: &mut [Video]) {
+    for video in videos {
+        if video.url.is_empty() {
+            video.url = video_url(&video.id);
+        }
+        for comment in &mut video.comments {
+            if comment.url.is_empty() {
+                comment.url = comment_url(&video.id, &comment.id);
+            }
+            for child in &mut comment.children {
+                if child.url.is_empty() {
+                    child.url = comment_url(&video.id, &child.id);
+                }
+            }
+        }
+    }
+}
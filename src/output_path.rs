@@ -0,0 +1,158 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use google_youtube3::chrono::{DateTime, Utc};
+use regex::{Captures, Regex};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::Video;
+
+/// Placeholders filled in once for the whole run.
+const RUN_PLACEHOLDERS: [&str; 3] = ["channel", "channel_id", "date"];
+
+/// Placeholders filled in for each video, which write every video to its own file.
+const VIDEO_PLACEHOLDERS: [&str; 3] = ["video_id", "video_title", "published"];
+
+/// Placeholder for the month or year of split output.
+pub const PERIOD_PLACEHOLDER: &str = "{period}";
+
+/// Longest a placeholder's value can be, in bytes, leaving room for the rest of the name within the
+/// 255 bytes most filesystems allow.
+const MAX_VALUE_LEN: usize = 150;
+
+/// Names Windows reserves for devices, with or without an extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+fn placeholder() -> Regex {
+    Regex::new(r"\{(\w+)\}").unwrap()
+}
+
+/// Replace a placeholder's value with something that can be part of a file name on any platform:
+/// without the characters Windows doesn't allow, not ending in a period or space, not one of its
+/// device names, short enough, and in Unicode NFC, the form Windows and Linux mostly use, so that
+/// the same title always gives the same name.
+fn sanitize(value: &str) -> String {
+    let mut value: String = value
+        .nfc()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    if value.len() > MAX_VALUE_LEN {
+        let mut end = MAX_VALUE_LEN;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        value.truncate(end);
+    }
+    // Windows drops trailing periods and spaces, so names differing only in them would collide.
+    let value = value.trim().trim_end_matches(['.', ' ']);
+    if value.is_empty() {
+        return "_".to_string();
+    }
+    let stem = value.split('.').next().unwrap_or_default().trim_end();
+    if RESERVED_NAMES.contains(&stem.to_lowercase().as_str()) {
+        return format!("_{value}");
+    }
+    value.to_string()
+}
+
+/// Fill in the run placeholders of an output name: `{channel}`, the handle without its @,
+/// `{channel_id}` and `{date}`, the day the run started. Video placeholders and `{period}` are left
+/// for later.
+pub fn expand_run(
+    name: &str,
+    channel_handle: &str,
+    channel_id: &str,
+    started_at: DateTime<Utc>,
+) -> Result<String> {
+    let regex = placeholder();
+    for captures in regex.captures_iter(name) {
+        let key = &captures[1];
+        let known =
+            RUN_PLACEHOLDERS.contains(&key) || VIDEO_PLACEHOLDERS.contains(&key) || key == "period";
+        if !known {
+            bail!(
+                "Unknown placeholder {{{key}}} in the output name, use one of {}, {} or {{period}}",
+                RUN_PLACEHOLDERS.map(|p| format!("{{{p}}}")).join(", "),
+                VIDEO_PLACEHOLDERS.map(|p| format!("{{{p}}}")).join(", "),
+            );
+        }
+    }
+
+    let expanded = regex.replace_all(name, |captures: &Captures| match &captures[1] {
+        "channel" => sanitize(channel_handle.strip_prefix('@').unwrap_or(channel_handle)),
+        "channel_id" => sanitize(channel_id),
+        "date" => started_at.format("%Y-%m-%d").to_string(),
+        _ => captures[0].to_string(),
+    });
+    Ok(expanded.into_owned())
+}
+
+/// Whether an output name writes every video to its own file.
+pub fn has_video_placeholders(name: &str) -> bool {
+    placeholder()
+        .captures_iter(name)
+        .any(|captures| VIDEO_PLACEHOLDERS.contains(&&captures[1]))
+}
+
+/// Whether an output name gives every video a file of its own, rather than only some of them, as
+/// `{published}` or `{video_title}` alone can.
+pub fn names_each_video(name: &str) -> bool {
+    placeholder()
+        .captures_iter(name)
+        .any(|captures| &captures[1] == "video_id")
+}
+
+/// Where an output file is written before being renamed over `path`, next to it so that the rename
+/// stays on one filesystem.
+pub fn temporary(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Fill in the video placeholders of an output name: `{video_id}`, `{video_title}` and
+/// `{published}`, the day the video was published, or `undated` in files written before publish
+/// dates were recorded.
+pub fn expand_video(name: &str, video: &Video) -> String {
+    fill_video(name, video, sanitize)
+}
+
+/// The output name with the video placeholders filled in as they are, without making them safe for
+/// file names. Videos with the same key are meant to share a file, such as those published on the
+/// same day with `{published}`, whereas videos with different keys whose names only come out the
+/// same once sanitized are not.
+pub fn video_key(name: &str, video: &Video) -> String {
+    fill_video(name, video, str::to_string)
+}
+
+fn fill_video(name: &str, video: &Video, value: impl Fn(&str) -> String) -> String {
+    placeholder()
+        .replace_all(name, |captures: &Captures| match &captures[1] {
+            "video_id" => value(&video.id),
+            "video_title" => value(&video.title),
+            "published" => match video.published_at {
+                Some(published_at) => published_at.format("%Y-%m-%d").to_string(),
+                None => "undated".to_string(),
+            },
+            _ => captures[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// The path with ` (n)` added to the end of its file name, before the extension, to tell it apart
+/// from another file with the same name.
+pub fn numbered(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{stem} ({n}).{}", extension.to_string_lossy()),
+        None => format!("{stem} ({n})"),
+    };
+    path.with_file_name(name)
+}
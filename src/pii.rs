@@ -0,0 +1,113 @@
+use regex::Regex;
+
+use crate::Video;
+
+/// Written in place of an email address.
+const EMAIL_MASK: &str = "[email]";
+/// Written in place of a phone number.
+const PHONE_MASK: &str = "[phone]";
+
+/// Fewest and most digits in a phone number, from short local numbers to the longest
+/// international ones.
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 7..=15;
+/// Fewest digits a phone number written without spaces, dashes or a leading `+` must have, so
+/// that counts such as 1000000 aren't taken for one.
+const UNSEPARATED_PHONE_DIGITS: usize = 10;
+
+/// Masks email addresses and phone numbers in comment text. Detection goes by what they look like,
+/// so it misses numbers written out in words and, rarely, masks a number that only looks like one.
+pub struct PiiRedactor {
+    email: Regex,
+    phone: Regex,
+    /// Runs of digits that look like phone numbers but are far more often something else.
+    not_phone: Regex,
+}
+
+impl Default for PiiRedactor {
+    fn default() -> Self {
+        Self {
+            email: Regex::new(
+                r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b",
+            )
+            .unwrap(),
+            phone: Regex::new(r"\+?(?:\(\d{1,4}\)|\d)[\d ().-]{5,}\d").unwrap(),
+            not_phone: Regex::new(concat!(
+                // Dates.
+                r"^(?:\d{4}[-./]\d{1,2}[-./]\d{1,2}|\d{1,2}[-./]\d{1,2}[-./]\d{2,4}",
+                // Ranges and lists of years.
+                r"|(?:19|20)\d{2}(?: ?[- ] ?(?:19|20)\d{2})+",
+                // IP addresses.
+                r"|\d{1,3}(?:\.\d{1,3}){3})$",
+            ))
+            .unwrap(),
+        }
+    }
+}
+
+impl PiiRedactor {
+    /// Mask the email addresses and phone numbers in every comment and reply on the video, and in
+    /// their HTML text if there is any, counting how many were masked on each.
+    pub fn apply(&self, video: &mut Video) {
+        for comment in &mut video.comments {
+            comment.pii_redactions = Some(self.redact(&mut comment.text));
+            if let Some(html) = &mut comment.text_html {
+                self.redact(html);
+            }
+            for child in &mut comment.children {
+                child.pii_redactions = Some(self.redact(&mut child.text));
+                if let Some(html) = &mut child.text_html {
+                    self.redact(html);
+                }
+            }
+        }
+    }
+
+    /// Mask the text, returning how many addresses and numbers were masked.
+    fn redact(&self, text: &mut String) -> u32 {
+        let mut count = 0;
+        // Addresses go first, since they can hold digits that look like part of a number.
+        if self.email.is_match(text) {
+            count += self.email.find_iter(text).count() as u32;
+            *text = self.email.replace_all(text, EMAIL_MASK).into_owned();
+        }
+
+        let mut redacted = String::with_capacity(text.len());
+        let mut last = 0;
+        for found in self.phone.find_iter(text) {
+            if !self.is_phone(text, found.start(), found.end()) {
+                continue;
+            }
+            redacted.push_str(&text[last..found.start()]);
+            redacted.push_str(PHONE_MASK);
+            last = found.end();
+            count += 1;
+        }
+        if last > 0 {
+            redacted.push_str(&text[last..]);
+            *text = redacted;
+        }
+        count
+    }
+
+    /// Whether the digits found between `start` and `end` are a phone number rather than a date, a
+    /// count, or part of a link or word.
+    fn is_phone(&self, text: &str, start: usize, end: usize) -> bool {
+        let part_of_something =
+            |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || "/=&?#_@%+".contains(c));
+        if part_of_something(text[..start].chars().next_back())
+            || part_of_something(text[end..].chars().next())
+        {
+            return false;
+        }
+        let candidate = text[start..end].trim();
+        let digits = candidate.chars().filter(char::is_ascii_digit).count();
+        if !PHONE_DIGITS.contains(&digits) {
+            return false;
+        }
+        let separated = candidate.starts_with('+') || candidate.contains([' ', '-', '.', '(']);
+        if !separated && digits < UNSEPARATED_PHONE_DIGITS {
+            return false;
+        }
+        !self.not_phone.is_match(candidate)
+    }
+}
@@ -0,0 +1,172 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use regex::{Regex, RegexBuilder};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::Video;
+
+/// Words matched when no wordlist is given. Entries ending in `*` also match words starting with
+/// them, and the rest only match whole words, so that words like "class" aren't caught.
+const DEFAULT_WORDS: &[&str] = &[
+    "arse",
+    "arsehole*",
+    "asshole*",
+    "bastard*",
+    "bitch*",
+    "bollocks",
+    "bullshit*",
+    "cock",
+    "cocksucker*",
+    "crap",
+    "cunt*",
+    "damn",
+    "dick",
+    "dickhead*",
+    "douche*",
+    "dumbass*",
+    "fag",
+    "faggot*",
+    "fuck*",
+    "jackass*",
+    "motherfuck*",
+    "nigga*",
+    "nigger*",
+    "piss",
+    "pissed",
+    "prick",
+    "retard*",
+    "shit*",
+    "slut*",
+    "twat*",
+    "wanker*",
+    "whore*",
+];
+
+/// What to do with comments containing a word from the wordlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProfanityMode {
+    /// Remove the comment, along with its replies if it starts a thread.
+    Drop,
+    /// Replace every letter of the matched words with `*`.
+    Mask,
+    /// Only set `profane` on the comment.
+    Flag,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfanitySummary {
+    pub mode: ProfanityMode,
+    /// Comments and replies containing a word from the wordlist.
+    pub matched: usize,
+    /// Comments and replies removed, including replies to removed comments.
+    pub dropped: usize,
+}
+
+/// Finds words from a wordlist in comments, and drops, masks or flags the comments they're in.
+pub struct ProfanityFilter {
+    mode: ProfanityMode,
+    words: Regex,
+    matched: usize,
+    dropped: usize,
+}
+
+impl ProfanityFilter {
+    /// Use the words in `wordlist`, one per line, or the built in list if it isn't given. Blank
+    /// lines and lines starting with `#` are skipped.
+    pub fn new(mode: ProfanityMode, wordlist: Option<&str>) -> Result<Self> {
+        let words: Vec<String> = match wordlist {
+            Some(path) => fs::read_to_string(path)
+                .with_context(|| format!("Unable to read wordlist {path}"))?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string)
+                .collect(),
+            None => DEFAULT_WORDS.iter().map(|w| w.to_string()).collect(),
+        };
+        let alternatives: Vec<String> = words
+            .iter()
+            .map(|word| match word.strip_suffix('*') {
+                Some(stem) => format!(r"{}\w*", regex::escape(stem)),
+                None => regex::escape(word),
+            })
+            .collect();
+        // An empty wordlist matches nothing.
+        let pattern = if alternatives.is_empty() {
+            r"[^\s\S]".to_string()
+        } else {
+            format!(r"\b(?:{})\b", alternatives.join("|"))
+        };
+        let words = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .context("Unable to build a pattern from the wordlist")?;
+        Ok(Self {
+            mode,
+            words,
+            matched: 0,
+            dropped: 0,
+        })
+    }
+
+    /// Drop, mask or flag the comments and replies on the video that contain a word from the list.
+    pub fn apply(&mut self, video: &mut Video) {
+        for comment in &mut video.comments {
+            comment.profane = self.check(&mut comment.text, &mut comment.text_html);
+            for child in &mut comment.children {
+                child.profane = self.check(&mut child.text, &mut child.text_html);
+            }
+
+            if self.mode == ProfanityMode::Drop {
+                let before = comment.children.len();
+                comment.children.retain(|c| !c.profane);
+                self.dropped += before - comment.children.len();
+            }
+        }
+
+        if self.mode == ProfanityMode::Drop {
+            for comment in video.comments.iter().filter(|c| c.profane) {
+                self.dropped += 1 + comment.children.len();
+            }
+            video.comments.retain(|c| !c.profane);
+        }
+    }
+
+    /// Returns whether the text contains a word from the list, masking it first if masking.
+    fn check(&mut self, text: &mut String, text_html: &mut Option<String>) -> bool {
+        if !self.words.is_match(text) {
+            return false;
+        }
+        self.matched += 1;
+        if self.mode == ProfanityMode::Mask {
+            *text = self.mask(text);
+            if let Some(html) = text_html {
+                *html = self.mask(html);
+            }
+        }
+        true
+    }
+
+    fn mask(&self, text: &str) -> String {
+        self.words
+            .replace_all(text, |captures: &regex::Captures| {
+                captures[0]
+                    .chars()
+                    .map(|c| if c.is_alphanumeric() { '*' } else { c })
+                    .collect::<String>()
+            })
+            .into_owned()
+    }
+
+    pub fn summary(&self) -> ProfanitySummary {
+        ProfanitySummary {
+            mode: self.mode,
+            matched: self.matched,
+            dropped: self.dropped,
+        }
+    }
+}
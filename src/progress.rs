@@ -0,0 +1,351 @@
+use std::{
+    cell::{Cell, RefCell},
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use indicatif::{HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+
+use crate::{quota, status::StatusFile, Totals, Video};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProgressMode {
+    /// An interactive progress bar through the videos, with a line below it for the video being
+    /// fetched.
+    Bar,
+    /// Newline-delimited JSON events on stderr.
+    Json,
+    /// Nothing but a summary line once the download finishes.
+    #[value(skip)]
+    Quiet,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    Started {
+        videos: usize,
+        expected_pages: u64,
+    },
+    /// More videos were listed after the download started.
+    VideosListed {
+        videos: usize,
+        expected_pages: u64,
+    },
+    VideoStarted {
+        index: usize,
+        video_id: &'a str,
+        title: &'a str,
+    },
+    PageFetched {
+        video_id: &'a str,
+        pages: usize,
+        comments: usize,
+    },
+    CommentsFetched {
+        video_id: &'a str,
+        comments: usize,
+        replies: usize,
+        quota_used: u64,
+    },
+    Error {
+        video_id: Option<&'a str>,
+        message: String,
+        quota_used: u64,
+    },
+    Finished {
+        videos: usize,
+        quota_used: u64,
+    },
+    Summary {
+        videos: usize,
+        comments_disabled: usize,
+        failed: usize,
+        comments: usize,
+        replies: usize,
+        requests: u64,
+        quota_used: u64,
+        elapsed_secs: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        largest_video: Option<&'a str>,
+    },
+}
+
+/// Reports progress through the videos being downloaded, on the terminal and, if asked for, to a
+/// status file.
+pub struct Progress {
+    display: Display,
+    status: Option<RefCell<StatusFile>>,
+}
+
+enum Display {
+    Bar(Bars),
+    Json,
+    Quiet,
+}
+
+/// The overall bar advances by comment pages rather than videos, so its estimate of the time
+/// remaining accounts for a few videos holding most of the comments.
+pub struct Bars {
+    /// Kept so both bars are drawn together.
+    _multi: MultiProgress,
+    overall: ProgressBar,
+    current: ProgressBar,
+    /// Position of the overall bar at the start of each video, followed by its length.
+    starts: RefCell<Vec<u64>>,
+    index: Cell<usize>,
+}
+
+impl Bars {
+    fn videos(&self) -> usize {
+        self.starts.borrow().len() - 1
+    }
+
+    fn start(&self, index: usize) -> u64 {
+        self.starts.borrow()[index]
+    }
+
+    fn set_video(&self, index: usize, position: u64) {
+        self.index.set(index);
+        self.overall.set_position(position);
+        self.overall
+            .set_message(format!("{index}/{} videos", self.videos()));
+    }
+}
+
+impl Progress {
+    /// `expected_pages` holds the rough number of comment pages each video will take. The status
+    /// file at `status_path`, if given, is written straight away.
+    pub fn new(
+        mode: ProgressMode,
+        expected_pages: &[u64],
+        status_path: Option<&Path>,
+    ) -> Result<Self> {
+        let mut starts = vec![0];
+        for pages in expected_pages {
+            starts.push(starts[starts.len() - 1] + pages);
+        }
+        let total = starts[starts.len() - 1];
+
+        let display = match mode {
+            ProgressMode::Bar => {
+                let multi = MultiProgress::new();
+                let style = ProgressStyle::with_template(
+                    "[elapsed:{elapsed}] [remaining:{eta}] {bar:50} {msg}",
+                )?;
+                let overall = multi.add(ProgressBar::new(total).with_style(style));
+                let style = ProgressStyle::with_template("{spinner} {prefix} {wide_msg}")?;
+                let current = multi.add(ProgressBar::new_spinner().with_style(style));
+                current.enable_steady_tick(Duration::from_millis(100));
+                let bars = Bars {
+                    _multi: multi,
+                    overall,
+                    current,
+                    starts: RefCell::new(starts),
+                    index: Cell::new(0),
+                };
+                bars.set_video(0, 0);
+                Display::Bar(bars)
+            }
+            ProgressMode::Json => Display::Json,
+            ProgressMode::Quiet => Display::Quiet,
+        };
+        let status = status_path
+            .map(|path| StatusFile::create(path, expected_pages))
+            .transpose()?
+            .map(RefCell::new);
+        let progress = Self { display, status };
+        progress.emit(&Event::Started {
+            videos: expected_pages.len(),
+            expected_pages: total,
+        });
+        Ok(progress)
+    }
+
+    /// Reports nothing, for fetches that aren't part of a download's progress.
+    pub fn quiet() -> Self {
+        Self {
+            display: Display::Quiet,
+            status: None,
+        }
+    }
+
+    /// Add videos listed after the download started, when the channel is listed while comments
+    /// are being fetched.
+    pub fn videos_listed(&self, expected_pages: &[u64]) {
+        if let Some(status) = &self.status {
+            status.borrow_mut().videos_listed(expected_pages);
+        }
+        match &self.display {
+            Display::Bar(bars) => {
+                let mut starts = bars.starts.borrow_mut();
+                let mut total = starts[starts.len() - 1];
+                for pages in expected_pages {
+                    total += pages;
+                    starts.push(total);
+                }
+                drop(starts);
+                bars.overall.set_length(total);
+                bars.overall
+                    .set_message(format!("{}/{} videos", bars.index.get(), bars.videos()));
+            }
+            Display::Json => self.emit(&Event::VideosListed {
+                videos: expected_pages.len(),
+                expected_pages: expected_pages.iter().sum(),
+            }),
+            Display::Quiet => {}
+        }
+    }
+
+    pub fn video_started(&self, index: usize, video_id: &str, title: &str) {
+        if let Some(status) = &self.status {
+            status.borrow_mut().video_started(index, video_id);
+        }
+        if let Display::Bar(bars) = &self.display {
+            bars.set_video(index, bars.start(index));
+            bars.current.set_prefix(title.to_string());
+            bars.current.set_message("");
+        }
+        self.emit(&Event::VideoStarted {
+            index,
+            video_id,
+            title,
+        });
+    }
+
+    /// Called after each page of comment threads or replies, with the number of pages fetched
+    /// for the current video and the comments and replies collected from them so far.
+    pub fn page_fetched(&self, video_id: &str, pages: usize, comments: usize) {
+        if let Some(status) = &self.status {
+            status.borrow_mut().page_fetched(pages);
+        }
+        match &self.display {
+            Display::Bar(bars) => {
+                // Stay short of the next video, in case the estimate was low.
+                let index = bars.index.get();
+                let (start, end) = (bars.start(index), bars.start(index + 1));
+                bars.overall
+                    .set_position((start + pages as u64).min(end - 1));
+                bars.current
+                    .set_message(format!("{pages} pages, {comments} comments"));
+            }
+            Display::Json => self.emit(&Event::PageFetched {
+                video_id,
+                pages,
+                comments,
+            }),
+            Display::Quiet => {}
+        }
+    }
+
+    pub fn video_finished(&self, video: &Video) {
+        if let Some(status) = &self.status {
+            status
+                .borrow_mut()
+                .video_finished(video.comments.len(), replies(video));
+        }
+        match &self.display {
+            Display::Bar(bars) => {
+                let index = bars.index.get() + 1;
+                bars.set_video(index, bars.start(index));
+            }
+            Display::Json => self.emit(&Event::CommentsFetched {
+                video_id: &video.id,
+                comments: video.comments.len(),
+                replies: replies(video),
+                quota_used: quota::used(),
+            }),
+            Display::Quiet => {}
+        }
+    }
+
+    pub fn error(&self, video_id: Option<&str>, error: &anyhow::Error) {
+        if let Some(status) = &self.status {
+            status.borrow_mut().error();
+        }
+        self.emit(&Event::Error {
+            video_id,
+            message: format!("{error:#}"),
+            quota_used: quota::used(),
+        });
+    }
+
+    pub fn finish(&self, totals: &Totals) {
+        if let Some(status) = &self.status {
+            status.borrow_mut().finish();
+        }
+        match &self.display {
+            Display::Bar(bars) => {
+                bars.current.finish_and_clear();
+                bars.overall.finish();
+            }
+            Display::Json => self.emit(&Event::Finished {
+                videos: totals.videos,
+                quota_used: quota::used(),
+            }),
+            Display::Quiet => println!(
+                "Downloaded {} comments and {} replies on {} videos using {} quota units",
+                totals.comments,
+                totals.replies,
+                totals.videos,
+                quota::used()
+            ),
+        }
+    }
+
+    /// Report what the run did once the output and manifest have been written. Quiet mode already
+    /// printed its summary line when the download finished.
+    pub fn summary(&self, totals: &Totals, failed: usize, elapsed: Duration) {
+        match &self.display {
+            Display::Bar(_) => {
+                eprintln!(
+                    "Wrote {} videos with {} comments and {} replies",
+                    totals.videos, totals.comments, totals.replies
+                );
+                if totals.comments_disabled > 0 || failed > 0 {
+                    eprintln!(
+                        "Skipped {} videos with comments turned off and {failed} that couldn't be fetched",
+                        totals.comments_disabled
+                    );
+                }
+                eprintln!(
+                    "Made {} API requests using about {} quota units in {}",
+                    quota::requests(),
+                    quota::used(),
+                    HumanDuration(elapsed)
+                );
+                if let Some((title, count)) = &totals.largest {
+                    eprintln!("Most comments and replies: {title} ({count})");
+                }
+            }
+            Display::Json => self.emit(&Event::Summary {
+                videos: totals.videos,
+                comments_disabled: totals.comments_disabled,
+                failed,
+                comments: totals.comments,
+                replies: totals.replies,
+                requests: quota::requests(),
+                quota_used: quota::used(),
+                elapsed_secs: elapsed.as_secs_f64(),
+                largest_video: totals.largest.as_ref().map(|(title, _)| title.as_str()),
+            }),
+            Display::Quiet => {}
+        }
+    }
+
+    fn emit(&self, event: &Event) {
+        if let Display::Json = self.display {
+            if let Ok(line) = serde_json::to_string(event) {
+                eprintln!("{line}");
+            }
+        }
+    }
+}
+
+fn replies(video: &Video) -> usize {
+    video.comments.iter().map(|c| c.children.len()).sum()
+}
@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use google_youtube3::chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+
+/// Quota units spent by this run. List requests cost one unit each and writes cost [`WRITE_COST`].
+static QUOTA_USED: AtomicU64 = AtomicU64::new(0);
+
+/// API requests made by this run, not counting those answered from the response cache.
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// Quota units charged for each write request, rather than the single unit of a list request.
+pub const WRITE_COST: u64 = 50;
+
+/// Record that an API request was made, costing one unit.
+pub fn record_request() {
+    REQUESTS.fetch_add(1, Ordering::Relaxed);
+    record(1);
+}
+
+/// Record that `units` more quota units were spent, such as by a write request costing more than
+/// one.
+pub fn record(units: u64) {
+    QUOTA_USED.fetch_add(units, Ordering::Relaxed);
+}
+
+pub fn requests() -> u64 {
+    REQUESTS.load(Ordering::Relaxed)
+}
+
+pub fn used() -> u64 {
+    QUOTA_USED.load(Ordering::Relaxed)
+}
+
+/// When daily quota next resets, which is at midnight Pacific time, plus a minute in case the
+/// clocks disagree.
+pub fn next_reset(now: DateTime<Utc>) -> DateTime<Utc> {
+    let standard = (now - Duration::hours(8)).date_naive();
+    let today = if is_daylight_saving(standard) {
+        (now - Duration::hours(7)).date_naive()
+    } else {
+        standard
+    };
+    let tomorrow = today + Duration::days(1);
+    let offset = if is_daylight_saving(tomorrow) { 7 } else { 8 };
+    tomorrow.and_hms_opt(0, 0, 0).unwrap().and_utc()
+        + Duration::hours(offset)
+        + Duration::minutes(1)
+}
+
+/// Whether midnight Pacific time on the date falls in daylight saving time, which runs from the
+/// second Sunday in March to the first Sunday in November.
+fn is_daylight_saving(date: NaiveDate) -> bool {
+    let year = date.year();
+    let start = NaiveDate::from_weekday_of_month_opt(year, 3, Weekday::Sun, 2).unwrap();
+    let end = NaiveDate::from_weekday_of_month_opt(year, 11, Weekday::Sun, 1).unwrap();
+    date > start && date <= end
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use tokio::{sync::Mutex, time::Instant};
+
+/// A token bucket shared by every request made through the client. The bucket holds a single
+/// token, so requests are spaced at least `interval` apart however many are in flight at once.
+pub struct RateLimiter {
+    interval: Duration,
+    /// When the next token becomes available.
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn per_second(requests: f64) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / requests))
+    }
+
+    /// Wait until a token is available and take it.
+    pub async fn acquire(&self) {
+        // Holding the lock while sleeping makes concurrent callers queue up in order.
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            tokio::time::sleep_until(*next).await;
+        }
+        *next = Instant::now().max(*next) + self.interval;
+    }
+}
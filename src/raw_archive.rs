@@ -0,0 +1,120 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use anyhow::{Context, Result};
+use flate2::{write::GzEncoder, Compression};
+use google_youtube3::chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::info;
+
+/// Endpoints whose responses are archived, which between them hold everything the output is built
+/// from.
+const ARCHIVED_ENDPOINTS: [&str; 3] = ["commentThreads", "comments", "playlistItems"];
+
+/// A directory keeping every comment and playlist response exactly as YouTube sent it, so outputs
+/// can be rebuilt from them later without spending quota again. Each run gets its own
+/// subdirectory, named after when it started, holding one gzipped file per response, numbered in
+/// the order they arrived.
+pub struct RawArchive {
+    root: PathBuf,
+    /// The subdirectory being written to, and the bytes written to it.
+    current: Mutex<(PathBuf, u64)>,
+    /// Size at which a new subdirectory is started, named after when it was, as if a new run had
+    /// started.
+    max_dir_size: Option<u64>,
+    next: AtomicU64,
+}
+
+/// An archived response, along with the request it answered.
+#[derive(Serialize)]
+struct Record<'a> {
+    endpoint: &'a str,
+    params: &'a [(&'a str, &'a str)],
+    fetched_at: DateTime<Utc>,
+    response: &'a serde_json::Value,
+}
+
+impl RawArchive {
+    pub fn open(dir: &Path, started_at: DateTime<Utc>) -> Result<Self> {
+        let current = create_run_dir(dir, started_at)?;
+        Ok(Self {
+            root: dir.to_path_buf(),
+            current: Mutex::new((current, 0)),
+            max_dir_size: None,
+            next: AtomicU64::new(1),
+        })
+    }
+
+    /// Start a new subdirectory whenever the current one would grow past `max_size` bytes.
+    pub fn with_max_dir_size(mut self, max_size: Option<u64>) -> Self {
+        self.max_dir_size = max_size;
+        self
+    }
+
+    /// Archive a response, if it's from one of the archived endpoints.
+    pub fn put(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        response: &serde_json::Value,
+    ) -> Result<()> {
+        if !ARCHIVED_ENDPOINTS.contains(&endpoint) {
+            return Ok(());
+        }
+        let record = Record {
+            endpoint,
+            params,
+            fetched_at: Utc::now(),
+            response,
+        };
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        serde_json::to_writer(&mut encoder, &record)?;
+        let compressed = encoder.finish()?;
+
+        let dir = {
+            let mut current = self.current.lock().unwrap();
+            let (dir, size) = &mut *current;
+            let len = compressed.len() as u64;
+            if self
+                .max_dir_size
+                .is_some_and(|max| *size > 0 && *size + len > max)
+            {
+                *dir = create_run_dir(&self.root, Utc::now())?;
+                *size = 0;
+                info!(dir = %dir.display(), "Started a new raw archive directory");
+            }
+            *size += len;
+            dir.clone()
+        };
+        let number = self.next.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("{number:06}-{endpoint}.json.gz"));
+        let mut file =
+            File::create(&path).with_context(|| format!("Unable to create {}", path.display()))?;
+        file.write_all(&compressed)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Create the subdirectory of `root` for a run started at `started_at`, numbering it if another
+/// started in the same second. Numbers follow an underscore, so numbered directories sort after
+/// the one they follow, as reprocess relies on.
+fn create_run_dir(root: &Path, started_at: DateTime<Utc>) -> Result<PathBuf> {
+    let name = started_at.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut dir = root.join(&name);
+    let mut n = 1;
+    while dir.exists() && fs::read_dir(&dir)?.next().is_some() {
+        n += 1;
+        dir = root.join(format!("{name}_{n}"));
+    }
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Unable to create archive directory {}", dir.display()))?;
+    Ok(dir)
+}
@@ -0,0 +1,164 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueHint};
+use google_youtube3::api::{Comment, CommentListResponse, CommentSnippet};
+use tracing::{info, warn};
+
+use crate::{api, connect, moderate, quota, watch, AccessScope, Client, ClientArgs};
+
+#[derive(Args)]
+pub struct ReplyArgs {
+    /// Name of a file of comment ids, one per line, or of a JSON output file, in which case every comment and reply in it is replied to. Use - to read from stdin.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+
+    /// Text of the reply. {author} is replaced with the display name of the author of the comment being replied to.
+    #[arg(short, long)]
+    message: String,
+
+    /// Time to wait between replies, such as 30s or 2m, to avoid posting fast enough to be flagged as spam.
+    #[arg(long, value_parser = watch::parse_interval, default_value = "5s")]
+    delay: Duration,
+
+    /// Print each reply that would be posted without posting anything.
+    #[arg(long)]
+    dry_run: bool,
+
+    #[command(flatten)]
+    pub client: ClientArgs,
+}
+
+/// A comment to reply to.
+struct Target {
+    id: String,
+    /// Replies can't be nested, so replying to a reply adds to the thread of its top-level comment.
+    parent_id: String,
+    author_name: String,
+}
+
+/// Post the templated reply to every comment in the input. Replies can only be posted with OAuth,
+/// so an API key can't be used.
+pub async fn run(args: &ReplyArgs) -> Result<()> {
+    if args.client.uses_api_key() {
+        bail!("Replying to comments requires OAuth, so an API key can't be used");
+    }
+    if args.message.trim().is_empty() {
+        bail!("The reply message is empty");
+    }
+
+    let ids = moderate::read_comment_ids(&args.input_name)?;
+    if ids.is_empty() {
+        bail!("No comment ids found in {}", args.input_name);
+    }
+
+    let client = connect(&args.client, AccessScope::Full).await?;
+    let targets = look_up(&client, &ids).await?;
+    if targets.len() < ids.len() {
+        warn!(
+            missing = ids.len() - targets.len(),
+            "Some comments no longer exist and will be skipped"
+        );
+    }
+
+    if args.dry_run {
+        for target in &targets {
+            println!(
+                "Would reply to {} by {}: {}",
+                target.id,
+                target.author_name,
+                render(&args.message, target)
+            );
+        }
+        println!("{} replies would be posted", targets.len());
+        return Ok(());
+    }
+
+    let mut failed = vec![];
+    for (i, target) in targets.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(args.delay).await;
+        }
+
+        let comment = Comment {
+            snippet: Some(CommentSnippet {
+                parent_id: Some(target.parent_id.clone()),
+                text_original: Some(render(&args.message, target)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        client.before_request().await;
+        quota::record(quota::WRITE_COST - 1);
+        // Keep going after a failure, since stopping partway would leave it unclear which
+        // comments were already replied to.
+        match client
+            .credential()
+            .1
+            .youtube
+            .comments()
+            .insert(comment)
+            .doit()
+            .await
+        {
+            Ok(_) => info!(id = target.id, "Posted reply"),
+            Err(e) => {
+                warn!(id = target.id, "Unable to post reply: {e}");
+                failed.push(target.id.as_str());
+            }
+        }
+    }
+
+    println!(
+        "Replied to {} of {} comments, using {} quota units",
+        targets.len() - failed.len(),
+        targets.len(),
+        quota::used()
+    );
+    if !failed.is_empty() {
+        bail!("Unable to reply to {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+/// Fetch each comment to find its author and thread, which also drops comments that have been
+/// deleted since they were exported.
+async fn look_up(client: &Client, ids: &[String]) -> Result<Vec<Target>> {
+    let mut found = HashMap::new();
+    for batch in ids.chunks(50) {
+        client.before_request().await;
+        let response: CommentListResponse = api::list(
+            client,
+            "comments",
+            &[
+                ("part", "snippet"),
+                ("textFormat", "plainText"),
+                ("id", &batch.join(",")),
+            ],
+        )
+        .await
+        .context("Unable to look up comments")?;
+        for comment in response.items.unwrap_or_default() {
+            let (Some(id), Some(snippet)) = (comment.id, comment.snippet) else {
+                continue;
+            };
+            let parent_id = snippet.parent_id.unwrap_or_else(|| id.clone());
+            let author_name = snippet.author_display_name.unwrap_or_default();
+            found.insert(
+                id.clone(),
+                Target {
+                    id,
+                    parent_id,
+                    author_name,
+                },
+            );
+        }
+    }
+
+    // Keep the order of the input.
+    Ok(ids.iter().filter_map(|id| found.remove(id)).collect())
+}
+
+fn render(template: &str, target: &Target) -> String {
+    template.replace("{author}", &target.author_name)
+}
@@ -0,0 +1,408 @@
+use std::io::Write;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{sink::Sink, Totals, Video};
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; }
+section { border-top: 1px solid #ccc; margin-top: 2em; }
+ul.thread { list-style: none; padding-left: 0; }
+ul.replies { list-style: none; border-left: 3px solid #ddd; padding-left: 1em; }
+li { margin: 0.75em 0; }
+.author { font-weight: bold; }
+.avatar { width: 24px; height: 24px; border-radius: 50%; vertical-align: middle; margin-right: 0.5em; }
+.thumbnail { display: block; }
+.text { white-space: pre-wrap; }
+.counts { color: #666; }";
+
+/// Renders the videos as a single standalone HTML page. Since videos are written as they arrive,
+/// the table of contents goes at the end of the page, with a link to it at the top.
+pub struct HtmlSink<W: Write> {
+    w: W,
+    contents: Vec<ContentsEntry>,
+    totals: Totals,
+}
+
+impl<W: Write> HtmlSink<W> {
+    pub fn new(channel: &str, mut w: W) -> Result<Self> {
+        let channel = escape_html(channel);
+        writeln!(w, "<!DOCTYPE html>")?;
+        writeln!(w, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(w, "<title>Comments for {channel}</title>")?;
+        writeln!(w, "<style>\n{STYLE}\n</style>\n</head>\n<body>")?;
+        writeln!(w, "<h1>Comments for {channel}</h1>")?;
+        writeln!(w, "<p><a href=\"#contents\">Contents</a></p>")?;
+        Ok(Self {
+            w,
+            contents: vec![],
+            totals: Totals::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for HtmlSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let w = &mut self.w;
+        let id = escape_html(&video.id);
+        writeln!(w, "<section id=\"{id}\">")?;
+        writeln!(
+            w,
+            "<h2><a href=\"{}\">{}</a></h2>",
+            escape_html(&video_url(&video.id)),
+            escape_html(&video.title)
+        )?;
+        if let Some(url) = video.thumbnails.as_ref().and_then(|t| t.medium.as_deref()) {
+            writeln!(
+                w,
+                "<img class=\"thumbnail\" src=\"{}\" alt=\"\" loading=\"lazy\">",
+                escape_html(url)
+            )?;
+        }
+        writeln!(w, "<p class=\"counts\">{}</p>", video_counts(video))?;
+        writeln!(w, "<ul class=\"thread\">")?;
+        for comment in &video.comments {
+            writeln!(w, "<li>")?;
+            write_html_comment(
+                w,
+                &comment.author_name,
+                comment.author_channel_url.as_deref(),
+                comment.author_profile_image_url.as_deref(),
+                &comment.text,
+            )?;
+            if !comment.children.is_empty() {
+                writeln!(w, "<ul class=\"replies\">")?;
+                for child in &comment.children {
+                    writeln!(w, "<li>")?;
+                    write_html_comment(
+                        w,
+                        &child.author_name,
+                        child.author_channel_url.as_deref(),
+                        child.author_profile_image_url.as_deref(),
+                        &child.text,
+                    )?;
+                    writeln!(w, "</li>")?;
+                }
+                writeln!(w, "</ul>")?;
+            }
+            writeln!(w, "</li>")?;
+        }
+        writeln!(w, "</ul>\n</section>")?;
+        w.flush()?;
+
+        self.contents.push(ContentsEntry::new(video));
+        self.totals.add(video);
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        let w = &mut self.w;
+        writeln!(w, "<section id=\"contents\">\n<h2>Contents</h2>")?;
+        writeln!(w, "<p class=\"counts\">{}</p>", summary(&self.totals))?;
+        writeln!(w, "<nav>\n<ul>")?;
+        for entry in &self.contents {
+            writeln!(
+                w,
+                "<li><a href=\"#{}\">{}</a> <span class=\"counts\">({})</span></li>",
+                escape_html(&entry.id),
+                escape_html(&entry.title),
+                entry.counts
+            )?;
+        }
+        writeln!(w, "</ul>\n</nav>\n</section>")?;
+        writeln!(w, "</body>\n</html>")?;
+        w.flush()?;
+        Ok(())
+    }
+}
+
+fn write_html_comment(
+    w: &mut impl Write,
+    author_name: &str,
+    author_channel_url: Option<&str>,
+    author_profile_image_url: Option<&str>,
+    text: &str,
+) -> Result<()> {
+    if let Some(url) = author_profile_image_url {
+        writeln!(
+            w,
+            "<img class=\"avatar\" src=\"{}\" alt=\"\" loading=\"lazy\">",
+            escape_html(url)
+        )?;
+    }
+    match author_channel_url {
+        Some(url) => writeln!(
+            w,
+            "<a class=\"author\" href=\"{}\">{}</a>",
+            escape_html(url),
+            escape_html(author_name)
+        )?,
+        None => writeln!(
+            w,
+            "<span class=\"author\">{}</span>",
+            escape_html(author_name)
+        )?,
+    }
+    writeln!(w, "<div class=\"text\">{}</div>", escape_html(text))?;
+    Ok(())
+}
+
+/// Renders the videos as a Markdown document with one section per video, followed by a table of
+/// contents.
+pub struct MarkdownSink<W: Write> {
+    w: W,
+    contents: Vec<ContentsEntry>,
+    totals: Totals,
+}
+
+impl<W: Write> MarkdownSink<W> {
+    pub fn new(channel: &str, mut w: W) -> Result<Self> {
+        writeln!(w, "# Comments for {}\n", escape_markdown(channel))?;
+        writeln!(w, "[Contents](#contents)")?;
+        Ok(Self {
+            w,
+            contents: vec![],
+            totals: Totals::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for MarkdownSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let w = &mut self.w;
+        writeln!(w, "\n<a id=\"{}\"></a>", video.id)?;
+        writeln!(
+            w,
+            "## [{}]({})\n",
+            escape_markdown(&video.title),
+            link_target(&video_url(&video.id))
+        )?;
+        writeln!(w, "{}\n", video_counts(video))?;
+        for comment in &video.comments {
+            write_markdown_comment(
+                w,
+                0,
+                &comment.author_name,
+                comment.author_channel_url.as_deref(),
+                &comment.text,
+            )?;
+            for child in &comment.children {
+                write_markdown_comment(
+                    w,
+                    1,
+                    &child.author_name,
+                    child.author_channel_url.as_deref(),
+                    &child.text,
+                )?;
+            }
+        }
+        w.flush()?;
+
+        self.contents.push(ContentsEntry::new(video));
+        self.totals.add(video);
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        let w = &mut self.w;
+        writeln!(w, "\n<a id=\"contents\"></a>")?;
+        writeln!(w, "## Contents\n")?;
+        writeln!(w, "{}\n", summary(&self.totals))?;
+        for entry in &self.contents {
+            writeln!(
+                w,
+                "- [{}](#{}) ({})",
+                escape_markdown(&entry.title),
+                entry.id,
+                entry.counts
+            )?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
+
+fn write_markdown_comment(
+    w: &mut impl Write,
+    depth: usize,
+    author_name: &str,
+    author_channel_url: Option<&str>,
+    text: &str,
+) -> Result<()> {
+    let indent = "  ".repeat(depth);
+    let author = match author_channel_url {
+        Some(url) => format!("[{}]({})", escape_markdown(author_name), link_target(url)),
+        None => escape_markdown(author_name),
+    };
+
+    // Continuation lines must be indented to stay inside the list item.
+    let text = escape_markdown(text).replace('\n', &format!("  \n{indent}  "));
+    writeln!(w, "{indent}- **{author}**: {text}")?;
+    Ok(())
+}
+
+/// What's kept of each video for the table of contents once the video itself has been written.
+struct ContentsEntry {
+    id: String,
+    title: String,
+    counts: String,
+}
+
+impl ContentsEntry {
+    fn new(video: &Video) -> Self {
+        Self {
+            id: video.id.clone(),
+            title: video.title.clone(),
+            counts: video_counts(video),
+        }
+    }
+}
+
+fn summary(totals: &Totals) -> String {
+    format!(
+        "{} videos, {} comments, {} replies",
+        totals.videos, totals.comments, totals.replies
+    )
+}
+
+fn video_counts(video: &Video) -> String {
+    if video.comments_disabled {
+        return "Comments are disabled".to_string();
+    }
+    let replies: usize = video.comments.iter().map(|c| c.children.len()).sum();
+    format!("{} comments, {} replies", video.comments.len(), replies)
+}
+
+pub fn video_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={video_id}")
+}
+
+/// Link that opens the video with a comment highlighted, or just the video for comments from
+/// files written before ids were recorded.
+pub fn comment_url(video_id: &str, comment_id: &str) -> String {
+    if comment_id.is_empty() {
+        return video_url(video_id);
+    }
+    format!("{}&lc={comment_id}", video_url(video_id))
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A URL as the target of a Markdown link, in angle brackets so that parentheses and spaces in it
+/// don't end the link early. The characters that can't appear between the brackets, including a
+/// backslash that would escape the closing one, are percent-encoded.
+fn link_target(url: &str) -> String {
+    let mut target = String::with_capacity(url.len() + 2);
+    target.push('<');
+    for c in url.chars() {
+        match c {
+            '<' => target.push_str("%3C"),
+            '>' => target.push_str("%3E"),
+            '\\' => target.push_str("%5C"),
+            '\n' => target.push_str("%0A"),
+            '\r' => target.push_str("%0D"),
+            _ => target.push(c),
+        }
+    }
+    target.push('>');
+    target
+}
+
+fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether the target ends where CommonMark ends a link destination in angle brackets: at the
+    /// first `>` that isn't escaped, with no `<` or line ending before it.
+    fn is_one_target(target: &str) -> bool {
+        let Some(inner) = target.strip_prefix('<').and_then(|t| t.strip_suffix('>')) else {
+            return false;
+        };
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '<' | '>' | '\n' | '\r' => return false,
+                _ => {}
+            }
+        }
+        !inner.ends_with('\\')
+    }
+
+    #[test]
+    fn keeps_parentheses_and_spaces_inside_the_link() {
+        for url in [
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+            "https://example.com/a b)c(d",
+            "https://example.com/)",
+        ] {
+            let target = link_target(url);
+            assert_eq!(target, format!("<{url}>"));
+            assert!(is_one_target(&target), "{target}");
+        }
+    }
+
+    #[test]
+    fn encodes_what_would_end_the_link() {
+        assert_eq!(
+            link_target("https://example.com/<a>\\"),
+            "<https://example.com/%3Ca%3E%5C>"
+        );
+        assert_eq!(
+            link_target("https://example.com/a\r\nb"),
+            "<https://example.com/a%0D%0Ab>"
+        );
+        for url in ["https://example.com/\\", "https://example.com/>x<", "a\nb"] {
+            assert!(is_one_target(&link_target(url)), "{url}");
+        }
+    }
+
+    #[test]
+    fn writes_author_links_with_awkward_urls() {
+        let mut out = vec![];
+        write_markdown_comment(
+            &mut out,
+            0,
+            "Some [one]",
+            Some("https://www.youtube.com/channel/a b(c)"),
+            "Hi",
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.contains("[Some \\[one\\]](<https://www.youtube.com/channel/a b(c)>)"),
+            "{out}"
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use clap::{Args, ValueHint};
+use flate2::read::GzDecoder;
+use google_youtube3::api::{
+    CommentListResponse, CommentThreadListResponse, PlaylistItemListResponse,
+};
+use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{
+    analysis::{self, Analysis, Analyzer},
+    errors::VideoError,
+    export::format_for,
+    file_sink,
+    filter::{AuthorList, Filters},
+    get_comments, get_playlist_items,
+    output::{FilterSettings, Header},
+    progress::{Progress, ProgressMode},
+    report::video_url,
+    sink::Column,
+    source::CommentSource,
+    warnings::Warnings,
+    FetchOptions, FileLayout, Format, Order, TextFormat, Video,
+};
+
+#[derive(Args)]
+pub struct ReprocessArgs {
+    /// Directory of raw responses written by --archive-raw, either one run's subdirectory or the whole directory. Where runs fetched the same page, the latest copy is used.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    from: String,
+
+    /// Name of the file to write.
+    #[arg(value_hint = ValueHint::FilePath)]
+    output_name: String,
+
+    /// Format to write. Defaults to the one matching the extension of the output file.
+    #[arg(short, long, value_enum)]
+    format: Option<Format>,
+
+    /// Write JSON or NDJSON as a record for every comment and reply instead of videos with nested threads.
+    #[arg(long)]
+    flat: bool,
+
+    /// Write JSON without indentation or line breaks.
+    #[arg(long)]
+    compact: bool,
+
+    /// Comma-separated columns to write to CSV or Excel output, in that order. Defaults to all of them.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    columns: Vec<Column>,
+
+    /// Channel handle recorded in the header and shown in the title of HTML and Markdown output. Defaults to the name of the --from directory.
+    #[arg(long)]
+    channel_handle: Option<String>,
+
+    /// Only keep comments whose text matches this regular expression. Parents of matching replies are kept for context.
+    #[arg(long)]
+    comment_filter: Option<Regex>,
+
+    /// Only keep comments with at least this many likes. Parents of kept replies are kept for context.
+    #[arg(long)]
+    min_likes: Option<u32>,
+
+    /// Only keep comments detected to be in one of these languages, given as ISO 639-1 codes. Ex: en,es.
+    #[arg(long, value_delimiter = ',', value_parser = analysis::language::parse_language_code)]
+    language: Vec<String>,
+
+    /// File listing authors whose comments are dropped, one channel id or display name per line.
+    #[arg(long)]
+    exclude_authors: Option<String>,
+
+    /// File listing the only authors whose comments are kept, one channel id or display name per line.
+    #[arg(long)]
+    only_authors: Option<String>,
+}
+
+/// An archived response, as written by [`crate::raw_archive::RawArchive`].
+#[derive(Deserialize)]
+struct Record {
+    endpoint: String,
+    params: Vec<(String, String)>,
+    response: serde_json::Value,
+}
+
+/// The raw responses of one or more runs, looked up by the request they answered. Reading them
+/// goes through the same [`CommentSource`] as the live API, so the output is built exactly as a
+/// download builds it.
+struct ArchiveSource {
+    /// Responses keyed by endpoint, the id the request was for, and page token.
+    pages: HashMap<(String, String, String), serde_json::Value>,
+    playlist_ids: Vec<String>,
+    /// Whether comments were fetched as HTML, keeping their display text.
+    html: bool,
+    /// Whether threads were fetched without their replies.
+    no_replies: bool,
+}
+
+impl ArchiveSource {
+    fn read(dir: &Path) -> Result<Self> {
+        let mut paths = vec![];
+        find_records(dir, &mut paths)?;
+        // Runs are named after when they started and responses numbered in order, so later
+        // copies of a page replace earlier ones.
+        paths.sort();
+
+        let mut source = Self {
+            pages: HashMap::new(),
+            playlist_ids: vec![],
+            html: false,
+            no_replies: false,
+        };
+        for path in &paths {
+            let file =
+                File::open(path).with_context(|| format!("Unable to open {}", path.display()))?;
+            let record: Record = serde_json::from_reader(BufReader::new(GzDecoder::new(file)))
+                .with_context(|| format!("Unable to parse {}", path.display()))?;
+            let param = |name: &str| {
+                record
+                    .params
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, value)| value.clone())
+            };
+            let id = match record.endpoint.as_str() {
+                "playlistItems" => param("playlistId"),
+                "commentThreads" => {
+                    source.html |= param("textFormat").as_deref() == Some("html");
+                    source.no_replies |= param("part").as_deref() == Some("snippet");
+                    param("videoId")
+                }
+                "comments" => param("parentId"),
+                _ => None,
+            };
+            let Some(id) = id else {
+                continue;
+            };
+            if record.endpoint == "playlistItems" && !source.playlist_ids.contains(&id) {
+                source.playlist_ids.push(id.clone());
+            }
+            let page_token = param("pageToken").unwrap_or_default();
+            source
+                .pages
+                .insert((record.endpoint, id, page_token), record.response);
+        }
+        if paths.is_empty() {
+            bail!("No archived responses found in {}", dir.display());
+        }
+        if source.playlist_ids.is_empty() {
+            bail!(
+                "{} has no archived playlist pages to list the videos from",
+                dir.display()
+            );
+        }
+        Ok(source)
+    }
+
+    fn page<T: DeserializeOwned>(&self, endpoint: &str, id: &str, page_token: &str) -> Result<T> {
+        let key = (endpoint.to_string(), id.to_string(), page_token.to_string());
+        let response = self.pages.get(&key).ok_or_else(|| {
+            anyhow!("The {endpoint} page {page_token:?} for {id} isn't in the archive")
+        })?;
+        // Deserializing from a reference saves copying the whole page first.
+        Ok(T::deserialize(response)?)
+    }
+
+    fn has_comments(&self, video_id: &str) -> bool {
+        let key = (
+            "commentThreads".to_string(),
+            video_id.to_string(),
+            String::new(),
+        );
+        self.pages.contains_key(&key)
+    }
+}
+
+#[async_trait]
+impl CommentSource for ArchiveSource {
+    async fn playlist_items(
+        &self,
+        playlist_id: &str,
+        page_token: &str,
+    ) -> Result<PlaylistItemListResponse> {
+        self.page("playlistItems", playlist_id, page_token)
+    }
+
+    async fn comment_threads(
+        &self,
+        video_id: &str,
+        page_token: &str,
+        _api_text_format: &str,
+        _options: &FetchOptions,
+    ) -> Result<CommentThreadListResponse> {
+        self.page("commentThreads", video_id, page_token)
+    }
+
+    async fn replies(
+        &self,
+        parent_id: &str,
+        page_token: &str,
+        _api_text_format: &str,
+    ) -> Result<CommentListResponse> {
+        self.page("comments", parent_id, page_token)
+    }
+}
+
+/// Collect the archived responses in the directory and its subdirectories.
+fn find_records(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| format!("Unable to read {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_records(&path, paths)?;
+        } else if path.to_string_lossy().ends_with(".json.gz") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Build an output from the raw responses archived by earlier downloads, the same way a download
+/// would have, without making any requests.
+pub async fn run(args: &ReprocessArgs) -> Result<()> {
+    let output_path = Path::new(&args.output_name);
+    let format = match args.format {
+        Some(format) => format,
+        None => format_for(output_path)?,
+    };
+    if !args.columns.is_empty() && !matches!(format, Format::Csv | Format::Xlsx) {
+        bail!("--columns can only be used with CSV or Excel output");
+    }
+    if args.flat && !matches!(format, Format::Json | Format::Ndjson) {
+        bail!("--flat can only be used with JSON or NDJSON output");
+    }
+    if args.compact && format != Format::Json {
+        bail!("--compact can only be used with JSON output");
+    }
+
+    let from = Path::new(&args.from);
+    let source = ArchiveSource::read(from)?;
+    let channel_handle = match &args.channel_handle {
+        Some(handle) => handle.clone(),
+        None => from
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+    };
+    let header = Header {
+        filters: FilterSettings {
+            comment_filter: args.comment_filter.as_ref().map(|r| r.to_string()),
+            min_likes: args.min_likes,
+            languages: args.language.clone(),
+            exclude_authors: args.exclude_authors.clone(),
+            only_authors: args.only_authors.clone(),
+            no_replies: source.no_replies,
+            ..FilterSettings::default()
+        },
+        ..Header::new(&channel_handle)
+    };
+    let filters = Filters {
+        comment_pattern: args.comment_filter.clone(),
+        min_likes: args.min_likes,
+        languages: args.language.clone(),
+        exclude_authors: args
+            .exclude_authors
+            .as_deref()
+            .map(AuthorList::read)
+            .transpose()?,
+        only_authors: args
+            .only_authors
+            .as_deref()
+            .map(AuthorList::read)
+            .transpose()?,
+        exclude_owner: false,
+    };
+    let analyses = if args.language.is_empty() {
+        vec![]
+    } else {
+        vec![Analysis::Language]
+    };
+    let mut analyzer = Analyzer::new(&analyses, false, None);
+    let options = FetchOptions {
+        text_format: if source.html {
+            TextFormat::Html
+        } else {
+            TextFormat::Plain
+        },
+        order: Order::Time,
+        search_terms: None,
+        moderation_status: None,
+        live_chat: false,
+        captions: false,
+        no_replies: source.no_replies,
+        reply_concurrency: 1,
+    };
+
+    let mut warnings = Warnings::default();
+    let mut items = vec![];
+    let mut listed = HashSet::new();
+    for playlist_id in &source.playlist_ids {
+        for item in get_playlist_items(playlist_id, &source, &mut warnings).await? {
+            if listed.insert(item.video_id.clone()) {
+                items.push(item);
+            }
+        }
+    }
+
+    let layout = FileLayout {
+        format,
+        columns: args.columns.clone(),
+        flat: args.flat,
+        compact: args.compact,
+    };
+    let mut sink = file_sink(&layout, &header, output_path)?;
+    let progress = Progress::new(ProgressMode::Quiet, &[], None)?;
+    let (mut videos, mut unarchived) = (0, 0);
+    let mut errors = vec![];
+    for item in &items {
+        // Videos without comments, or whose comments were turned off, were never asked for.
+        if !source.has_comments(&item.video_id) {
+            unarchived += 1;
+            continue;
+        }
+        let comments = get_comments(
+            &item.video_id,
+            &source,
+            &options,
+            &progress,
+            &mut warnings,
+            None,
+            Default::default(),
+        )
+        .await;
+        let comments = match comments {
+            Ok(comments) => comments,
+            Err(e) => {
+                errors.push(VideoError::new(&item.video_id, &item.title, &e));
+                continue;
+            }
+        };
+        let mut video = Video {
+            title: item.title.clone(),
+            id: item.video_id.clone(),
+            url: video_url(&item.video_id),
+            published_at: item.published_at,
+            thumbnails: item.thumbnails.clone(),
+            sentiment: None,
+            keywords: None,
+            moments: None,
+            emoji: None,
+            comments_disabled: comments.is_none(),
+            comments: comments.unwrap_or_default(),
+            live_chat: None,
+            captions: None,
+        };
+        analyzer.tag_video(&mut video);
+        filters.apply(&mut video);
+        sink.write_video(&video).await?;
+        videos += 1;
+    }
+    sink.write_errors(&errors).await?;
+    sink.finish().await?;
+
+    eprintln!(
+        "Rebuilt {videos} videos from {}, leaving out {unarchived} without archived comments",
+        from.display()
+    );
+    if !warnings.is_empty() {
+        eprintln!(
+            "{} malformed comments or playlist items were skipped",
+            warnings.len()
+        );
+    }
+    if !errors.is_empty() {
+        bail!(
+            "{} videos had pages missing from the archive and were left out, see the errors in {}",
+            errors.len(),
+            output_path.display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use flate2::{write::GzEncoder, Compression};
+use google_youtube3::chrono::Utc;
+use serde::Deserialize;
+use tracing::{error, info};
+
+/// Parse a file size such as `500MB`, `2G` or `750k`, in multiples of 1024. A bare number is in
+/// bytes.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("{s} isn't a number followed by KB, MB or GB"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return Err(format!("Unknown unit {unit} in {s}, expected KB, MB or GB")),
+    };
+    match number.checked_mul(multiplier) {
+        Some(0) => Err("The size must be greater than zero".to_string()),
+        Some(size) => Ok(size),
+        None => Err(format!("{s} is too large")),
+    }
+}
+
+/// What's added to the name of a file when it's rotated out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RotateSuffix {
+    /// The next unused number, as in updates.1.ndjson.
+    Number,
+    /// When it was rotated out, as in updates.20240501T120000Z.ndjson.
+    Timestamp,
+}
+
+/// When and how files that grow for as long as watch mode runs are rotated.
+#[derive(Debug, Clone, Copy)]
+pub struct Rotation {
+    pub max_size: u64,
+    pub suffix: RotateSuffix,
+    /// Gzip rotated files, in the background so polling carries on.
+    pub compress: bool,
+}
+
+/// A file that lines are appended to, moved aside under another name for a new one to be started
+/// before a line would take it past the size limit. A single line larger than the limit still
+/// goes into a file of its own.
+pub struct RotatingFile {
+    path: PathBuf,
+    w: BufWriter<File>,
+    size: u64,
+    rotation: Option<Rotation>,
+}
+
+impl RotatingFile {
+    /// Open the file to append to, creating it if it doesn't exist yet.
+    pub fn open(path: &Path, rotation: Option<Rotation>) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Unable to open {}", path.display()))?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            w: BufWriter::new(file),
+            size,
+            rotation,
+        })
+    }
+
+    /// Append a line, rotating the file first if the line doesn't fit, and flush it.
+    pub fn write_line(&mut self, line: &[u8]) -> Result<()> {
+        let len = line.len() as u64 + 1;
+        if let Some(rotation) = self.rotation {
+            if self.size > 0 && self.size + len > rotation.max_size {
+                self.rotate(rotation)?;
+            }
+        }
+        self.w.write_all(line)?;
+        self.w.write_all(b"\n")?;
+        self.w.flush()?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self, rotation: Rotation) -> Result<()> {
+        self.w.flush()?;
+        let rotated = rotated_path(&self.path, rotation.suffix);
+        fs::rename(&self.path, &rotated).with_context(|| {
+            format!(
+                "Unable to rotate {} to {}",
+                self.path.display(),
+                rotated.display()
+            )
+        })?;
+        info!(path = %rotated.display(), "Rotated output file");
+        let file = File::create(&self.path)
+            .with_context(|| format!("Unable to create {}", self.path.display()))?;
+        self.w = BufWriter::new(file);
+        self.size = 0;
+
+        if rotation.compress {
+            std::thread::spawn(move || {
+                if let Err(e) = compress(&rotated) {
+                    error!("Unable to compress {}: {e:#}", rotated.display());
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The name the file at `path` is moved to: the suffix goes between its stem and extension, and
+/// numbers skip those already taken, compressed or not.
+fn rotated_path(path: &Path, suffix: RotateSuffix) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let name = |suffix: &str| path.with_file_name(format!("{stem}.{suffix}{extension}"));
+    let taken = |candidate: &Path| {
+        let mut compressed = candidate.as_os_str().to_os_string();
+        compressed.push(".gz");
+        candidate.exists() || Path::new(&compressed).exists()
+    };
+    let first = match suffix {
+        RotateSuffix::Number => 1.to_string(),
+        RotateSuffix::Timestamp => Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+    };
+    let mut candidate = name(&first);
+    let mut n = 1;
+    while taken(&candidate) {
+        n += 1;
+        candidate = match suffix {
+            RotateSuffix::Number => name(&n.to_string()),
+            RotateSuffix::Timestamp => name(&format!("{first}-{n}")),
+        };
+    }
+    candidate
+}
+
+/// Gzip the file next to it and delete the original once the copy is complete.
+fn compress(path: &Path) -> Result<()> {
+    let mut compressed_path = path.as_os_str().to_os_string();
+    compressed_path.push(".gz");
+    let compressed_path = PathBuf::from(compressed_path);
+    let mut input =
+        File::open(path).with_context(|| format!("Unable to open {}", path.display()))?;
+    let output = File::create(&compressed_path)
+        .with_context(|| format!("Unable to create {}", compressed_path.display()))?;
+    let mut encoder = GzEncoder::new(BufWriter::new(output), Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.flush()?;
+    fs::remove_file(path).with_context(|| format!("Unable to delete {}", path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use google_youtube3::chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike,
+};
+
+/// Names that months and days of the week can be given by instead of numbers.
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Furthest ahead the next matching time is looked for, past which a schedule such as one for the
+/// 30th of February is taken to never match.
+const MAX_YEARS_AHEAD: i32 = 8;
+
+/// When to poll in watch mode, as a cron expression in local time: the minute, hour, day of the
+/// month, month and day of the week, such as `0 3 * * *` for 3 AM every day.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Cron matches either day field when both are restricted, rather than both.
+    either_day: bool,
+}
+
+/// Parse a cron expression with five fields, each `*`, a number, a range such as `1-5`, or a list
+/// of them such as `1,15`, optionally with a step such as `*/15`. Months and days of the week can
+/// also be named, and Sunday is 0 or 7. `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`
+/// are also understood.
+pub fn parse_schedule(s: &str) -> Result<Schedule, String> {
+    let expression = match s.trim() {
+        "@hourly" => "0 * * * *",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@weekly" => "0 0 * * 0",
+        "@monthly" => "0 0 1 * *",
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        expression => expression,
+    };
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    let [minutes, hours, days, months, weekdays] = fields[..] else {
+        return Err(format!(
+            "{s} should have five fields: minute, hour, day of the month, month and day of the week"
+        ));
+    };
+    // Sunday can be 7 as well as 0.
+    let weekday_bits = parse_field(weekdays, 0, 7, WEEKDAYS, "day of the week")?;
+    let schedule = Schedule {
+        minutes: parse_field(minutes, 0, 59, &[], "minute")?,
+        hours: parse_field(hours, 0, 23, &[], "hour")?,
+        days: parse_field(days, 1, 31, &[], "day of the month")?,
+        months: parse_field(months, 1, 12, MONTHS, "month")?,
+        weekdays: (weekday_bits | weekday_bits >> 7) & 0x7f,
+        either_day: !days.starts_with('*') && !weekdays.starts_with('*'),
+    };
+    if schedule.next_after(Local::now()).is_none() {
+        return Err(format!("{s} never matches a date"));
+    }
+    Ok(schedule)
+}
+
+/// Parse one field into a bit set of the values it matches. Names, if given, stand for the values
+/// from `min` on.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], name: &str) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_lowercase();
+        if let Some(index) = names.iter().position(|n| *n == lower) {
+            return Ok(min + index as u32);
+        }
+        match s.parse::<u32>() {
+            Ok(n) if (min..=max).contains(&n) => Ok(n),
+            _ => Err(format!("{s} isn't a valid {name}, expected {min} to {max}")),
+        }
+    };
+
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("{step} isn't a valid step in the {name}")),
+            },
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // A single value with a step runs to the end, as in `5/15`.
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let n = value(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("{range} is backwards in the {name}"));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+impl Schedule {
+    /// The first minute strictly after `after` that matches, if there's one in the next years.
+    /// Times skipped by a daylight saving change are passed over.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut time = start;
+        let limit = start.year() + MAX_YEARS_AHEAD;
+        while time.year() <= limit {
+            if !has(self.months, time.month()) {
+                time = first_of_next_month(time)?;
+                continue;
+            }
+            if !self.matches_day(time.date()) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !has(self.hours, time.hour()) {
+                time = time.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !has(self.minutes, time.minute()) {
+                time += Duration::minutes(1);
+                continue;
+            }
+            if let Some(local) = Local.from_local_datetime(&time).earliest() {
+                return Some(local);
+            }
+            time += Duration::minutes(1);
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+fn has(bits: u64, n: u32) -> bool {
+    bits & (1 << n) != 0
+}
+
+fn first_of_next_month(time: NaiveDateTime) -> Option<NaiveDateTime> {
+    let (year, month) = match time.month() {
+        12 => (time.year() + 1, 1),
+        month => (time.year(), month + 1),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+}
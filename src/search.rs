@@ -0,0 +1,231 @@
+use anyhow::Result;
+use clap::{Args, ValueHint};
+use google_youtube3::chrono::{DateTime, NaiveDate, Utc};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{output, report::comment_url, video_id, Video};
+
+/// Longest parent text shown above a matching reply before it is truncated.
+const MAX_CONTEXT_WIDTH: usize = 80;
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Name of a JSON file previously written by this tool.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+
+    #[command(flatten)]
+    query: Query,
+
+    /// Print the matches as JSON instead of text.
+    #[arg(long)]
+    json: bool,
+}
+
+/// What to search for. Every part that's given has to match.
+#[derive(Args, Default)]
+pub struct Query {
+    /// Only show comments containing this text, ignoring case.
+    #[arg(value_name = "QUERY")]
+    pub text: Option<String>,
+
+    /// Only show comments whose text matches this regular expression.
+    #[arg(long)]
+    pub regex: Option<Regex>,
+
+    /// Only show comments by this author, given as part of their name, ignoring case, or their channel id.
+    #[arg(long)]
+    pub author: Option<String>,
+
+    /// Only show comments on this video, given as its id, a link to it, or part of its title, ignoring case.
+    #[arg(long, value_parser = video_id::parse_or_text)]
+    pub video: Option<String>,
+
+    /// Only show comments posted on or after this date, as YYYY-MM-DD or an RFC 3339 timestamp. Comments in files written before dates were recorded never match.
+    #[arg(long, value_parser = parse_date)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only show comments posted before this date, as YYYY-MM-DD or an RFC 3339 timestamp.
+    #[arg(long, value_parser = parse_date)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// A matching comment or reply, with the video and thread it belongs to.
+#[derive(Debug, Clone, Serialize)]
+pub struct Match<'a> {
+    video_id: &'a str,
+    video_title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_author_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_text: Option<&'a str>,
+    id: &'a str,
+    author_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    published_at: Option<DateTime<Utc>>,
+    like_count: u32,
+    text: &'a str,
+    url: String,
+}
+
+/// The fields of a comment or reply the predicates look at.
+struct Candidate<'a> {
+    text: &'a str,
+    author_name: &'a str,
+    author_channel_id: Option<&'a str>,
+    published_at: Option<DateTime<Utc>>,
+}
+
+/// Parse a date given as YYYY-MM-DD, meaning the start of that day in UTC, or as an RFC 3339
+/// timestamp.
+pub fn parse_date(s: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    DateTime::parse_from_rfc3339(s)
+        .map(|d| d.with_timezone(&Utc))
+        .map_err(|_| format!("{s} isn't a date as YYYY-MM-DD or an RFC 3339 timestamp"))
+}
+
+pub fn run(args: &SearchArgs) -> Result<()> {
+    let videos = output::read(&args.input_name)?.videos;
+
+    let matches = search(&args.query, &videos);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+    } else {
+        print_matches(&matches);
+        eprintln!("{} matches", matches.len());
+    }
+
+    Ok(())
+}
+
+pub fn search<'a>(query: &Query, videos: &'a [Video]) -> Vec<Match<'a>> {
+    let text = query.text.as_ref().map(|t| t.to_lowercase());
+    let author = query.author.as_ref().map(|a| a.to_lowercase());
+    let video = query.video.as_ref().map(|v| v.to_lowercase());
+
+    let is_match = |c: &Candidate| {
+        text.as_ref()
+            .is_none_or(|t| c.text.to_lowercase().contains(t))
+            && query.regex.as_ref().is_none_or(|r| r.is_match(c.text))
+            && author.as_ref().is_none_or(|a| {
+                c.author_name.to_lowercase().contains(a)
+                    || c.author_channel_id
+                        .is_some_and(|id| id.to_lowercase() == *a)
+            })
+            && query
+                .since
+                .is_none_or(|since| c.published_at.is_some_and(|p| p >= since))
+            && query
+                .until
+                .is_none_or(|until| c.published_at.is_some_and(|p| p < until))
+    };
+
+    let mut matches = vec![];
+    for v in videos {
+        if video
+            .as_ref()
+            .is_some_and(|q| v.id.to_lowercase() != *q && !v.title.to_lowercase().contains(q))
+        {
+            continue;
+        }
+
+        for thread in &v.comments {
+            let candidate = Candidate {
+                text: &thread.text,
+                author_name: &thread.author_name,
+                author_channel_id: thread.author_channel_id.as_deref(),
+                published_at: thread.published_at,
+            };
+            if is_match(&candidate) {
+                matches.push(Match {
+                    video_id: &v.id,
+                    video_title: &v.title,
+                    parent_id: None,
+                    parent_author_name: None,
+                    parent_text: None,
+                    id: &thread.id,
+                    author_name: &thread.author_name,
+                    published_at: thread.published_at,
+                    like_count: thread.like_count,
+                    text: &thread.text,
+                    url: comment_url(&v.id, &thread.id),
+                });
+            }
+
+            for reply in &thread.children {
+                let candidate = Candidate {
+                    text: &reply.text,
+                    author_name: &reply.author_name,
+                    author_channel_id: reply.author_channel_id.as_deref(),
+                    published_at: reply.published_at,
+                };
+                if is_match(&candidate) {
+                    matches.push(Match {
+                        video_id: &v.id,
+                        video_title: &v.title,
+                        parent_id: Some(&thread.id),
+                        parent_author_name: Some(&thread.author_name),
+                        parent_text: Some(&thread.text),
+                        id: &reply.id,
+                        author_name: &reply.author_name,
+                        published_at: reply.published_at,
+                        like_count: reply.like_count,
+                        text: &reply.text,
+                        url: comment_url(&v.id, &reply.id),
+                    });
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+/// Print each match under its video's title, with the start of the comment it replies to, if
+/// any.
+fn print_matches(matches: &[Match]) {
+    let mut current_video = None;
+    for m in matches {
+        if current_video != Some(m.video_id) {
+            if current_video.is_some() {
+                println!();
+            }
+            println!("{} ({})", m.video_title, m.video_id);
+            current_video = Some(m.video_id);
+        }
+
+        println!();
+        let mut indent = "  ";
+        if let (Some(author), Some(text)) = (m.parent_author_name, m.parent_text) {
+            println!("  {author}: {}", truncate(text));
+            indent = "    ";
+        }
+        let date = m
+            .published_at
+            .map(|d| format!(", {}", d.format("%Y-%m-%d")))
+            .unwrap_or_default();
+        println!(
+            "{indent}{} ({} likes{date}) {}",
+            m.author_name, m.like_count, m.url
+        );
+        for line in m.text.lines() {
+            println!("{indent}  {line}");
+        }
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > MAX_CONTEXT_WIDTH || text.lines().nth(1).is_some() {
+        let shortened: String = line.chars().take(MAX_CONTEXT_WIDTH - 3).collect();
+        format!("{shortened}...")
+    } else {
+        line.to_string()
+    }
+}
@@ -0,0 +1,109 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use clap::ValueEnum;
+use google_youtube3::oauth2::storage::{TokenInfo, TokenStorage};
+use keyring::Entry;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+/// Service name the keyring entries are stored under.
+const SERVICE: &str = "youtube-comments";
+
+/// Where client secrets and OAuth tokens are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretStore {
+    /// The client secret file and token cache file.
+    File,
+    /// The system keychain: Keychain on macOS, Credential Manager on Windows and the Secret
+    /// Service on Linux.
+    Keyring,
+}
+
+fn entry(kind: &str, name: &str) -> Result<Entry> {
+    Entry::new(SERVICE, &format!("{kind} {name}")).context("Unable to open the keyring")
+}
+
+/// Read a client secret given as JSON or a file name. With the keyring, a secret that's passed is
+/// saved to it, so the file can be deleted afterwards, and one that isn't is read from it.
+pub fn client_secret(name: &str, store: SecretStore) -> Result<String> {
+    let json = if name.trim_start().starts_with('{') {
+        Some(name.to_string())
+    } else if store == SecretStore::File || Path::new(name).is_file() {
+        Some(
+            fs::read_to_string(name)
+                .with_context(|| format!("Unable to read client secret file {name}"))?,
+        )
+    } else {
+        None
+    };
+
+    if store == SecretStore::File {
+        return Ok(json.unwrap_or_default());
+    }
+    let entry = entry("client-secret", name)?;
+    match json {
+        Some(json) => {
+            entry
+                .set_password(&json)
+                .context("Unable to save the client secret to the keyring")?;
+            Ok(json)
+        }
+        None => entry.get_password().with_context(|| {
+            format!("No client secret in {name} or the keyring, pass it once to save it")
+        }),
+    }
+}
+
+/// Delete the tokens cached in the keyring under a token cache name, if there are any.
+pub fn delete_tokens(token_cache_name: &str) -> Result<()> {
+    match entry("tokens", token_cache_name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => {
+            Err(e).with_context(|| format!("Unable to delete {token_cache_name} from the keyring"))
+        }
+    }
+}
+
+/// Keeps OAuth tokens in the keyring, as JSON under the token cache name.
+pub struct KeyringTokenCache {
+    entry: Entry,
+    /// Tokens keyed by their space-separated scopes.
+    tokens: Mutex<HashMap<String, TokenInfo>>,
+}
+
+impl KeyringTokenCache {
+    pub fn open(token_cache_name: &str) -> Result<Self> {
+        let entry = entry("tokens", token_cache_name)?;
+        let tokens = match entry.get_password() {
+            Ok(json) => serde_json::from_str(&json)
+                .with_context(|| format!("Unable to parse {token_cache_name} from the keyring"))?,
+            Err(keyring::Error::NoEntry) => HashMap::new(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Unable to read {token_cache_name} from the keyring"))
+            }
+        };
+        Ok(Self {
+            entry,
+            tokens: Mutex::new(tokens),
+        })
+    }
+}
+
+#[async_trait]
+impl TokenStorage for KeyringTokenCache {
+    async fn set(&self, scopes: &[&str], token: TokenInfo) -> Result<()> {
+        let mut tokens = self.tokens.lock().await;
+        tokens.insert(scopes.join(" "), token);
+        self.entry
+            .set_password(&serde_json::to_string(&*tokens)?)
+            .context("Unable to save the token to the keyring")
+    }
+
+    async fn get(&self, scopes: &[&str]) -> Option<TokenInfo> {
+        self.tokens.lock().await.get(&scopes.join(" ")).cloned()
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, ErrorKind, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+
+use crate::Video;
+
+/// A file listing the id of every comment and reply written by earlier runs, one per line, so that
+/// overlapping runs only write what's new, whatever files and formats they write it to. Ids are
+/// appended as soon as their video is written, so the file stays usable if a run is killed, and
+/// comments that never made it into the output are fetched again by the next run.
+pub struct SeenStore {
+    ids: HashSet<String>,
+    w: BufWriter<File>,
+    /// Comments and replies dropped this run for having been seen before.
+    skipped: usize,
+}
+
+impl SeenStore {
+    /// Open the store, creating it if it doesn't exist yet.
+    pub fn open(path: &Path) -> Result<Self> {
+        let ids = match fs::read_to_string(path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(e) if e.kind() == ErrorKind::NotFound => HashSet::new(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Unable to read seen store {}", path.display()))
+            }
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Unable to open seen store {}", path.display()))?;
+        Ok(Self {
+            ids,
+            w: BufWriter::new(file),
+            skipped: 0,
+        })
+    }
+
+    /// Remove every thread and reply from the video that's in the store. Threads that gained
+    /// replies are kept with only their new replies.
+    pub fn take_new(&mut self, video: &mut Video) {
+        let ids = &self.ids;
+        let mut skipped = 0;
+        video.comments.retain_mut(|comment| {
+            let before = comment.children.len();
+            comment.children.retain(|child| !ids.contains(&child.id));
+            skipped += before - comment.children.len();
+
+            if !ids.contains(&comment.id) {
+                true
+            } else if comment.children.is_empty() {
+                skipped += 1;
+                false
+            } else {
+                true
+            }
+        });
+        self.skipped += skipped;
+    }
+
+    /// Add the threads and replies of a video that has been written to the store.
+    pub fn record(&mut self, video: &Video) -> Result<()> {
+        for comment in &video.comments {
+            let ids = std::iter::once(&comment.id).chain(comment.children.iter().map(|c| &c.id));
+            for id in ids {
+                if self.ids.insert(id.clone()) {
+                    writeln!(self.w, "{id}")?;
+                }
+            }
+        }
+        self.w.flush()?;
+        Ok(())
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+}
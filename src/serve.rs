@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use google_youtube3::{
+    chrono::{DateTime, Utc},
+    hyper::{
+        self,
+        service::{make_service_fn, service_fn},
+        Body, Method, Request, Response, StatusCode,
+    },
+};
+use regex::Regex;
+use serde::Serialize;
+use tracing::info;
+
+use crate::{
+    output::{self, Output},
+    search::{parse_date, search, Query},
+    shutdown, video_id,
+};
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Name of a JSON file previously written by this tool.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+
+    /// Port to listen on.
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+
+    /// Address to listen on. Defaults to only accepting connections from this machine; pass 0.0.0.0 to serve the whole network.
+    #[arg(long, default_value = "127.0.0.1")]
+    host: IpAddr,
+}
+
+/// A video as listed by `/videos`, without its comments.
+#[derive(Debug, Serialize)]
+struct VideoSummary<'a> {
+    id: &'a str,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    published_at: Option<DateTime<Utc>>,
+    comments_disabled: bool,
+    comments: usize,
+    replies: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// The archive being served, with its videos indexed by id.
+struct Archive {
+    output: Output,
+    indices: HashMap<String, usize>,
+}
+
+/// Serve read-only JSON endpoints over an output file until interrupted:
+/// - `/videos` lists every video with its comment counts.
+/// - `/videos/{id}/comments` returns a video's threads with their replies.
+/// - `/search` returns the comments matching the query parameters `q`, `regex`, `author`,
+///   `video`, `since` and `until`, which mean the same as the options of `search`.
+pub async fn run(args: &ServeArgs) -> Result<()> {
+    let output = output::read(&args.input_name)?;
+    let indices = output
+        .videos
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (v.id.clone(), i))
+        .collect();
+    let archive = Arc::new(Archive { output, indices });
+
+    let addr = SocketAddr::new(args.host, args.port);
+    let server = hyper::Server::try_bind(&addr)
+        .with_context(|| format!("Unable to listen on {addr}"))?
+        .serve(make_service_fn(move |_| {
+            let archive = archive.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let archive = archive.clone();
+                    async move { Ok::<_, Infallible>(handle(&archive, &request)) }
+                }))
+            }
+        }));
+    info!(%addr, "Serving {}", args.input_name);
+    eprintln!("Serving {} on http://{addr}", args.input_name);
+    server.with_graceful_shutdown(shutdown::signal()).await?;
+    Ok(())
+}
+
+fn handle(archive: &Archive, request: &Request<Body>) -> Response<Body> {
+    if request.method() != Method::GET {
+        return error(StatusCode::METHOD_NOT_ALLOWED, "Only GET is supported");
+    }
+
+    let segments: Vec<&str> = request
+        .uri()
+        .path()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let videos = &archive.output.videos;
+    match segments.as_slice() {
+        ["videos"] => {
+            let summaries: Vec<_> = videos
+                .iter()
+                .map(|v| VideoSummary {
+                    id: &v.id,
+                    title: &v.title,
+                    published_at: v.published_at,
+                    comments_disabled: v.comments_disabled,
+                    comments: v.comments.len(),
+                    replies: v.comments.iter().map(|c| c.children.len()).sum(),
+                })
+                .collect();
+            json(&summaries)
+        }
+        ["videos", id, "comments"] => match archive.indices.get(*id) {
+            Some(&i) => json(&videos[i].comments),
+            None => error(StatusCode::NOT_FOUND, &format!("No video {id}")),
+        },
+        ["search"] => match parse_query(request.uri().query().unwrap_or_default()) {
+            Ok(query) => json(&search(&query, videos)),
+            Err(message) => error(StatusCode::BAD_REQUEST, &message),
+        },
+        _ => error(StatusCode::NOT_FOUND, "Not found"),
+    }
+}
+
+fn parse_query(query_string: &str) -> Result<Query, String> {
+    let mut query = Query::default();
+    for (key, value) in url::form_urlencoded::parse(query_string.as_bytes()) {
+        let value = value.into_owned();
+        match key.as_ref() {
+            "q" => query.text = Some(value),
+            "regex" => {
+                let regex = Regex::new(&value).map_err(|e| format!("Invalid regex: {e}"))?;
+                query.regex = Some(regex);
+            }
+            "author" => query.author = Some(value),
+            "video" => query.video = Some(video_id::parse_or_text(&value)?),
+            "since" => query.since = Some(parse_date(&value)?),
+            "until" => query.until = Some(parse_date(&value)?),
+            _ => return Err(format!("Unknown parameter {key}")),
+        }
+    }
+    Ok(query)
+}
+
+fn json(body: &impl Serialize) -> Response<Body> {
+    match serde_json::to_vec(body) {
+        Ok(body) => Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .expect("the response is valid"),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    let body = serde_json::to_vec(&ErrorBody {
+        error: message.to_string(),
+    })
+    .unwrap_or_default();
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .expect("the response is valid")
+}
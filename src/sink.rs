@@ -0,0 +1,504 @@
+use std::{borrow::Cow, io::Write};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use google_youtube3::chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{errors::VideoError, output::Header, Video};
+
+pub mod elasticsearch;
+pub mod kafka;
+pub mod per_video;
+pub mod postgres;
+pub mod redis;
+pub mod sheets;
+pub mod split;
+pub mod tee;
+
+/// Where fetched comments are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SinkKind {
+    /// The output file, in the format given by --format.
+    File,
+    /// Bulk-index each comment and reply into an Elasticsearch or OpenSearch index.
+    Elasticsearch,
+    /// Upsert videos and comments into tables in a PostgreSQL database.
+    Postgres,
+    /// Publish one message per comment and reply to a Kafka topic.
+    Kafka,
+    /// Add one entry per comment and reply to a Redis stream.
+    Redis,
+    /// Write a row per comment and reply to a tab of a Google Sheet.
+    Sheets,
+}
+
+/// Somewhere videos are written to one at a time, as soon as their comments are fetched, so a
+/// whole channel never has to be held in memory.
+#[async_trait]
+pub trait Sink: Send {
+    async fn write_video(&mut self, video: &Video) -> Result<()>;
+
+    /// Record the videos that couldn't be fetched, after the last video has been written. Only
+    /// JSON has a place for them, the other sinks leave them to the manifest.
+    async fn write_errors(&mut self, _errors: &[VideoError]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Complete the output after the last video has been written.
+    async fn finish(self: Box<Self>) -> Result<()>;
+}
+
+/// Writes the header and then the videos as JSON, pretty-printed unless it's compact.
+pub struct JsonSink<W: Write> {
+    w: W,
+    compact: bool,
+    empty: bool,
+    errors: Vec<VideoError>,
+}
+
+impl<W: Write> JsonSink<W> {
+    pub fn new(mut w: W, header: &Header, compact: bool) -> Result<Self> {
+        // The header's closing brace is left off so the videos can follow it one at a time.
+        if compact {
+            let header = serde_json::to_string(header)?;
+            let header = header.strip_suffix('}').unwrap_or(&header);
+            write!(w, "{header},\"videos\":[")?;
+        } else {
+            let header = serde_json::to_string_pretty(header)?;
+            let header = header.strip_suffix('}').unwrap_or(&header).trim_end();
+            write!(w, "{header},\n  \"videos\": [")?;
+        }
+        Ok(Self {
+            w,
+            compact,
+            empty: true,
+            errors: vec![],
+        })
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for JsonSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        if !self.empty {
+            write!(self.w, ",")?;
+        }
+        self.empty = false;
+        if self.compact {
+            serde_json::to_writer(&mut self.w, video)?;
+            self.w.flush()?;
+            return Ok(());
+        }
+        writeln!(self.w)?;
+        // Strings can't contain raw newlines, so this only indents the JSON itself.
+        let video = serde_json::to_string_pretty(video)?;
+        for (i, line) in video.lines().enumerate() {
+            if i > 0 {
+                writeln!(self.w)?;
+            }
+            write!(self.w, "    {line}")?;
+        }
+        self.w.flush()?;
+        Ok(())
+    }
+
+    async fn write_errors(&mut self, errors: &[VideoError]) -> Result<()> {
+        self.errors = errors.to_vec();
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        if self.compact {
+            write!(self.w, "]")?;
+            if !self.errors.is_empty() {
+                write!(self.w, ",\"errors\":")?;
+                serde_json::to_writer(&mut self.w, &self.errors)?;
+            }
+            write!(self.w, "}}")?;
+            self.w.flush()?;
+            return Ok(());
+        }
+        if !self.empty {
+            write!(self.w, "\n  ")?;
+        }
+        write!(self.w, "]")?;
+        if !self.errors.is_empty() {
+            let errors = serde_json::to_string_pretty(&self.errors)?;
+            write!(self.w, ",\n  \"errors\": {}", errors.replace('\n', "\n  "))?;
+        }
+        write!(self.w, "\n}}")?;
+        self.w.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes a single JSON array of every comment and reply, in place of the nested videos, for
+/// `--flat`.
+pub struct FlatJsonSink<W: Write> {
+    w: W,
+    compact: bool,
+    empty: bool,
+}
+
+impl<W: Write> FlatJsonSink<W> {
+    pub fn new(mut w: W, compact: bool) -> Result<Self> {
+        write!(w, "[")?;
+        Ok(Self {
+            w,
+            compact,
+            empty: true,
+        })
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for FlatJsonSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        for record in comment_records(video) {
+            if !self.empty {
+                write!(self.w, ",")?;
+            }
+            self.empty = false;
+            if self.compact {
+                serde_json::to_writer(&mut self.w, &record)?;
+                continue;
+            }
+            let record = serde_json::to_string_pretty(&record)?;
+            write!(self.w, "\n  {}", record.replace('\n', "\n  "))?;
+        }
+        self.w.flush()?;
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        if !self.empty && !self.compact {
+            writeln!(self.w)?;
+        }
+        write!(self.w, "]")?;
+        self.w.flush()?;
+        Ok(())
+    }
+}
+
+/// Holds every video until the end, then writes them to another sink sorted by publish time and
+/// then id, with their threads and replies sorted the same way, so the output doesn't depend on
+/// the order videos were fetched in or comments were returned in.
+pub struct SortedSink {
+    inner: Box<dyn Sink>,
+    videos: Vec<Video>,
+    errors: Vec<VideoError>,
+}
+
+impl SortedSink {
+    pub fn new(inner: Box<dyn Sink>) -> Self {
+        Self {
+            inner,
+            videos: vec![],
+            errors: vec![],
+        }
+    }
+}
+
+/// Order the threads of the video, and the replies in each thread, oldest first.
+pub fn sort_comments(video: &mut Video) {
+    video
+        .comments
+        .sort_by(|a, b| (a.published_at, &a.id).cmp(&(b.published_at, &b.id)));
+    for comment in &mut video.comments {
+        comment
+            .children
+            .sort_by(|a, b| (a.published_at, &a.id).cmp(&(b.published_at, &b.id)));
+    }
+}
+
+#[async_trait]
+impl Sink for SortedSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let mut video = video.clone();
+        sort_comments(&mut video);
+        self.videos.push(video);
+        Ok(())
+    }
+
+    async fn write_errors(&mut self, errors: &[VideoError]) -> Result<()> {
+        self.errors = errors.to_vec();
+        self.errors.sort_by(|a, b| a.video_id.cmp(&b.video_id));
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.videos
+            .sort_by(|a, b| (a.published_at, &a.id).cmp(&(b.published_at, &b.id)));
+        for video in &self.videos {
+            self.inner.write_video(video).await?;
+        }
+        self.inner.write_errors(&self.errors).await?;
+        self.inner.finish().await
+    }
+}
+
+/// Writes a line of JSON for every video, or with `--flat`, for every comment and reply.
+pub struct NdjsonSink<W: Write> {
+    w: W,
+    flat: bool,
+}
+
+impl<W: Write> NdjsonSink<W> {
+    pub fn new(flat: bool, w: W) -> Self {
+        Self { w, flat }
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for NdjsonSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        if self.flat {
+            for record in comment_records(video) {
+                serde_json::to_writer(&mut self.w, &record)?;
+                writeln!(self.w)?;
+            }
+        } else {
+            serde_json::to_writer(&mut self.w, video)?;
+            writeln!(self.w)?;
+        }
+        self.w.flush()?;
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.w.flush()?;
+        Ok(())
+    }
+}
+
+/// One comment or reply, flattened together with its video, for sinks that store comments
+/// individually.
+#[derive(Debug, Serialize)]
+pub struct CommentRecord<'a> {
+    pub video_id: &'a str,
+    pub video_title: &'a str,
+    pub comment_id: &'a str,
+    pub url: &'a str,
+    /// Set for replies, to the id of the comment being replied to, and null otherwise.
+    pub parent_id: Option<&'a str>,
+    /// 0 for top-level comments and 1 for replies.
+    pub depth: u32,
+    pub author_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_channel_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_channel_url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_profile_image_url: Option<&'a str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_owner: bool,
+    pub text: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<DateTime<Utc>>,
+    pub like_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spam_score: Option<f64>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    pub mentions: &'a [String],
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    pub links: &'a [String],
+    #[serde(skip_serializing_if = "<[u32]>::is_empty")]
+    pub timestamps: &'a [u32],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_translated: Option<&'a str>,
+}
+
+/// Flatten every comment and reply on the video, each reply following its parent.
+pub fn comment_records(video: &Video) -> Vec<CommentRecord<'_>> {
+    let mut records = vec![];
+    for comment in &video.comments {
+        records.push(CommentRecord {
+            video_id: &video.id,
+            video_title: &video.title,
+            comment_id: &comment.id,
+            url: &comment.url,
+            parent_id: None,
+            depth: 0,
+            author_name: &comment.author_name,
+            author_channel_id: comment.author_channel_id.as_deref(),
+            author_channel_url: comment.author_channel_url.as_deref(),
+            author_profile_image_url: comment.author_profile_image_url.as_deref(),
+            is_owner: comment.is_owner,
+            text: &comment.text,
+            published_at: comment.published_at,
+            like_count: comment.like_count,
+            sentiment: comment.sentiment.as_ref().map(|s| s.compound),
+            language: comment.language.as_deref(),
+            duplicate_of: comment.duplicate_of.as_deref(),
+            spam_score: comment.spam_score,
+            mentions: &comment.mentions,
+            links: &comment.links,
+            timestamps: &comment.timestamps,
+            text_translated: comment.text_translated.as_deref(),
+        });
+        for child in &comment.children {
+            records.push(CommentRecord {
+                video_id: &video.id,
+                video_title: &video.title,
+                comment_id: &child.id,
+                url: &child.url,
+                parent_id: Some(&comment.id),
+                depth: 1,
+                author_name: &child.author_name,
+                author_channel_id: child.author_channel_id.as_deref(),
+                author_channel_url: child.author_channel_url.as_deref(),
+                author_profile_image_url: child.author_profile_image_url.as_deref(),
+                is_owner: child.is_owner,
+                text: &child.text,
+                published_at: child.published_at,
+                like_count: child.like_count,
+                sentiment: child.sentiment.as_ref().map(|s| s.compound),
+                language: child.language.as_deref(),
+                duplicate_of: child.duplicate_of.as_deref(),
+                spam_score: child.spam_score,
+                mentions: &child.mentions,
+                links: &child.links,
+                timestamps: &child.timestamps,
+                text_translated: child.text_translated.as_deref(),
+            });
+        }
+    }
+    records
+}
+
+/// A column of the formats with a row for every comment and reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[value(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    VideoId,
+    VideoTitle,
+    CommentId,
+    /// Link that opens the video with the comment highlighted.
+    Url,
+    /// Id of the comment being replied to, empty for top-level comments.
+    ParentId,
+    Author,
+    AuthorChannelId,
+    AuthorChannelUrl,
+    /// Address of the author's avatar.
+    AuthorProfileImageUrl,
+    /// true for comments the channel wrote itself.
+    IsOwner,
+    Text,
+    PublishedAt,
+    Likes,
+    Sentiment,
+    Language,
+    DuplicateOf,
+    SpamScore,
+    /// The @handles mentioned in the text, separated by spaces.
+    Mentions,
+    /// The links in the text, separated by spaces.
+    Links,
+    /// Offsets in seconds of the timestamps in the text, separated by spaces.
+    Timestamps,
+    /// The translation from --translate-to.
+    TextTranslated,
+}
+
+/// The value of a column for one comment.
+pub enum Field<'a> {
+    Text(Cow<'a, str>),
+    Number(f64),
+    Empty,
+}
+
+impl Column {
+    /// Every column, in the order they're written when `--columns` isn't given.
+    pub const ALL: [Column; 21] = [
+        Column::VideoId,
+        Column::VideoTitle,
+        Column::CommentId,
+        Column::Url,
+        Column::ParentId,
+        Column::Author,
+        Column::AuthorChannelId,
+        Column::AuthorChannelUrl,
+        Column::AuthorProfileImageUrl,
+        Column::IsOwner,
+        Column::Text,
+        Column::PublishedAt,
+        Column::Likes,
+        Column::Sentiment,
+        Column::Language,
+        Column::DuplicateOf,
+        Column::SpamScore,
+        Column::Mentions,
+        Column::Links,
+        Column::Timestamps,
+        Column::TextTranslated,
+    ];
+
+    /// The columns picked, or all of them if none were.
+    pub fn or_all(columns: &[Column]) -> Vec<Column> {
+        if columns.is_empty() {
+            Column::ALL.to_vec()
+        } else {
+            columns.to_vec()
+        }
+    }
+
+    /// Name of the column as passed to `--columns`.
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .expect("no columns are skipped")
+            .get_name()
+            .to_string()
+    }
+
+    pub fn value<'a>(self, record: &CommentRecord<'a>) -> Field<'a> {
+        let text = |value: Option<&'a str>| value.map_or(Field::Empty, |v| Field::Text(v.into()));
+        match self {
+            Column::VideoId => text(Some(record.video_id)),
+            Column::VideoTitle => text(Some(record.video_title)),
+            Column::CommentId => text(Some(record.comment_id)),
+            Column::Url => text(Some(record.url)),
+            Column::ParentId => text(record.parent_id),
+            Column::Author => text(Some(record.author_name)),
+            Column::AuthorChannelId => text(record.author_channel_id),
+            Column::AuthorChannelUrl => text(record.author_channel_url),
+            Column::AuthorProfileImageUrl => text(record.author_profile_image_url),
+            Column::IsOwner => Field::Text(record.is_owner.to_string().into()),
+            Column::Text => text(Some(record.text)),
+            Column::PublishedAt => record.published_at.map_or(Field::Empty, |t| {
+                Field::Text(t.to_rfc3339_opts(SecondsFormat::Secs, true).into())
+            }),
+            Column::Likes => Field::Number(record.like_count.into()),
+            Column::Sentiment => record.sentiment.map_or(Field::Empty, Field::Number),
+            Column::Language => text(record.language),
+            Column::DuplicateOf => text(record.duplicate_of),
+            Column::SpamScore => record.spam_score.map_or(Field::Empty, Field::Number),
+            Column::Mentions if record.mentions.is_empty() => Field::Empty,
+            Column::Mentions => Field::Text(record.mentions.join(" ").into()),
+            Column::Links if record.links.is_empty() => Field::Empty,
+            Column::Links => Field::Text(record.links.join(" ").into()),
+            Column::Timestamps if record.timestamps.is_empty() => Field::Empty,
+            Column::Timestamps => Field::Text(
+                record
+                    .timestamps
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .into(),
+            ),
+            Column::TextTranslated => text(record.text_translated),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+use super::{comment_records, CommentRecord, Sink};
+use crate::Video;
+
+/// Maximum number of documents sent in one bulk request.
+const BULK_SIZE: usize = 500;
+
+/// Bulk-indexes every comment and reply into an Elasticsearch or OpenSearch index, keyed by
+/// comment id so that rerunning a download updates documents instead of duplicating them.
+pub struct ElasticsearchSink {
+    http_client: reqwest::Client,
+    url: String,
+    index: String,
+}
+
+impl ElasticsearchSink {
+    /// Connect to the cluster at `url`, creating the index with a mapping if it doesn't exist.
+    pub async fn new(http_client: reqwest::Client, url: &str, index: &str) -> Result<Self> {
+        let sink = Self {
+            http_client,
+            url: url.trim_end_matches('/').to_string(),
+            index: index.to_string(),
+        };
+
+        let index_url = format!("{}/{}", sink.url, sink.index);
+        let response = sink
+            .http_client
+            .head(&index_url)
+            .send()
+            .await
+            .with_context(|| format!("Unable to reach Elasticsearch at {}", sink.url))?;
+        match response.status() {
+            StatusCode::OK => {}
+            StatusCode::NOT_FOUND => {
+                info!(index, "Creating Elasticsearch index");
+                sink.http_client
+                    .put(&index_url)
+                    .json(&mapping())
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .with_context(|| format!("Unable to create index {index}"))?;
+            }
+            status => bail!("Unexpected status {status} checking for index {index}"),
+        }
+
+        Ok(sink)
+    }
+
+    async fn bulk(&self, documents: &[CommentRecord<'_>]) -> Result<()> {
+        let mut body = String::new();
+        for document in documents {
+            let action = json!({ "index": { "_index": self.index, "_id": document.comment_id } });
+            body.push_str(&action.to_string());
+            body.push('\n');
+            body.push_str(&serde_json::to_string(document)?);
+            body.push('\n');
+        }
+
+        let response: Value = self
+            .http_client
+            .post(format!("{}/_bulk", self.url))
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context("Bulk indexing request failed")?
+            .json()
+            .await?;
+        debug!(documents = documents.len(), "Bulk indexed comments");
+
+        // A bulk request succeeds as a whole even when individual documents fail.
+        if response["errors"].as_bool() == Some(true) {
+            let reason = response["items"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find_map(|item| item["index"]["error"]["reason"].as_str())
+                .unwrap_or("unknown error");
+            bail!("Elasticsearch rejected some comments: {reason}");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for ElasticsearchSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let documents = comment_records(video);
+        for chunk in documents.chunks(BULK_SIZE) {
+            self.bulk(chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<()> {
+        // Make everything indexed searchable straight away rather than at the next refresh.
+        self.http_client
+            .post(format!("{}/{}/_refresh", self.url, self.index))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Unable to refresh index {}", self.index))?;
+        Ok(())
+    }
+}
+
+/// Comment text and video titles are analyzed for full-text search, while ids, authors and
+/// languages are keywords for exact matching and aggregations.
+fn mapping() -> Value {
+    json!({
+        "mappings": {
+            "properties": {
+                "video_id": { "type": "keyword" },
+                "video_title": { "type": "text" },
+                "comment_id": { "type": "keyword" },
+                "parent_id": { "type": "keyword" },
+                "author_name": { "type": "keyword" },
+                "author_channel_id": { "type": "keyword" },
+                "author_channel_url": { "type": "keyword", "index": false },
+                "text": { "type": "text" },
+                "like_count": { "type": "integer" },
+                "sentiment": { "type": "float" },
+                "language": { "type": "keyword" },
+                "duplicate_of": { "type": "keyword" }
+            }
+        }
+    })
+}
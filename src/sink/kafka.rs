@@ -0,0 +1,93 @@
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use google_youtube3::chrono::Utc;
+use rskafka::{
+    client::{
+        partition::{Compression, PartitionClient, UnknownTopicHandling},
+        ClientBuilder,
+    },
+    record::Record,
+};
+use tracing::debug;
+
+use super::{comment_records, Sink};
+use crate::Video;
+
+/// Maximum number of messages produced in one request, to stay under the broker's message size
+/// limit.
+const BATCH_SIZE: usize = 500;
+
+/// Publishes every comment and reply to a Kafka topic as a JSON message keyed by comment id. All
+/// the comments on a video go to the same partition, so they're consumed in order.
+pub struct KafkaSink {
+    partitions: Vec<PartitionClient>,
+}
+
+impl KafkaSink {
+    /// Connect to the cluster through the `brokers`. The topic must already exist.
+    pub async fn new(brokers: &[String], topic: &str) -> Result<Self> {
+        let client = ClientBuilder::new(brokers.to_vec())
+            .build()
+            .await
+            .context("Unable to connect to Kafka")?;
+        let topics = client.list_topics().await?;
+        let partition_ids = topics
+            .into_iter()
+            .find(|t| t.name == topic)
+            .with_context(|| format!("No Kafka topic named {topic}"))?
+            .partitions;
+
+        let mut partitions = vec![];
+        for id in partition_ids {
+            partitions.push(
+                client
+                    .partition_client(topic, id, UnknownTopicHandling::Retry)
+                    .await?,
+            );
+        }
+        Ok(Self { partitions })
+    }
+}
+
+#[async_trait]
+impl Sink for KafkaSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        video.id.hash(&mut hasher);
+        let partition = &self.partitions[hasher.finish() as usize % self.partitions.len()];
+
+        let timestamp = Utc::now();
+        let records = comment_records(video)
+            .iter()
+            .map(|record| {
+                Ok(Record {
+                    key: Some(record.comment_id.as_bytes().to_vec()),
+                    value: Some(serde_json::to_vec(record)?),
+                    headers: BTreeMap::new(),
+                    timestamp,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for batch in records.chunks(BATCH_SIZE) {
+            partition
+                .produce(batch.to_vec(), Compression::NoCompression)
+                .await
+                .with_context(|| format!("Unable to publish comments on video {}", video.id))?;
+        }
+        debug!(
+            video_id = video.id,
+            messages = records.len(),
+            "Published to Kafka"
+        );
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
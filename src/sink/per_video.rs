@@ -0,0 +1,133 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use super::Sink;
+use crate::{
+    create_parent_dir, file_sink, output::Header, output_path, template_sink, FileLayout, Video,
+};
+
+/// Writes every video to the file its output name names, after filling in the video placeholders,
+/// such as `{video_id}.json`. Videos whose placeholders fill in the same, such as those published
+/// on the same day with `{published}`, share a file. Videos whose names only come out the same once
+/// made safe for file names, or differ only in case, which Windows and macOS don't tell apart, get
+/// ` (2)`, ` (3)` and so on added in the order they're written. Errors are only recorded in the
+/// manifest.
+///
+/// No file is held open between videos, so channels with thousands of uploads don't run out of
+/// file descriptors. When each video has a file of its own, it's finished as soon as the video is
+/// written. Otherwise a file's videos are appended to an NDJSON file next to it as they come, and
+/// the file is written from them once the run finishes.
+pub struct PerVideoSink {
+    name: String,
+    layout: FileLayout,
+    template: Option<String>,
+    header: Header,
+    /// Whether the output name contains `{video_id}`, so no two videos share a file.
+    each_video: bool,
+    /// The file each key from [`output_path::video_key`] is written to.
+    paths: HashMap<String, PathBuf>,
+    /// Every path handed out so far, in lowercase.
+    taken: HashSet<String>,
+}
+
+impl PerVideoSink {
+    pub fn new(name: &str, layout: &FileLayout, template: Option<&str>, header: &Header) -> Self {
+        Self {
+            name: name.to_string(),
+            layout: layout.clone(),
+            template: template.map(str::to_string),
+            header: header.clone(),
+            each_video: output_path::names_each_video(name),
+            paths: HashMap::new(),
+            taken: HashSet::new(),
+        }
+    }
+
+    /// The file the video goes to, numbering it if another video already has its name, and
+    /// whether it's the first video to go there.
+    fn path(&mut self, video: &Video) -> (PathBuf, bool) {
+        let key = output_path::video_key(&self.name, video);
+        if let Some(path) = self.paths.get(&key) {
+            return (path.clone(), false);
+        }
+        let base = PathBuf::from(output_path::expand_video(&self.name, video));
+        let mut path = base.clone();
+        let mut n = 1;
+        while !self.taken.insert(path.to_string_lossy().to_lowercase()) {
+            n += 1;
+            path = output_path::numbered(&base, n);
+        }
+        self.paths.insert(key, path.clone());
+        (path, true)
+    }
+
+    fn open(&self, path: &Path) -> Result<Box<dyn Sink>> {
+        match &self.template {
+            Some(template) => template_sink(template, &self.header, path),
+            None => file_sink(&self.layout, &self.header, path),
+        }
+    }
+}
+
+/// Where the videos of a shared file are kept until the run finishes.
+fn pending_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".pending");
+    path.with_file_name(name)
+}
+
+#[async_trait]
+impl Sink for PerVideoSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let (path, first) = self.path(video);
+        if self.each_video {
+            let mut sink = self.open(&path)?;
+            sink.write_video(video).await?;
+            return sink.finish().await;
+        }
+
+        // Left over from an earlier run that didn't finish, if it's already there the first time.
+        let pending = pending_path(&path);
+        create_parent_dir(&pending)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(!first)
+            .write(true)
+            .truncate(first)
+            .open(&pending)
+            .with_context(|| format!("Unable to open {}", pending.display()))?;
+        let mut w = BufWriter::new(file);
+        serde_json::to_writer(&mut w, video)?;
+        w.write_all(b"\n")?;
+        w.flush()?;
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<()> {
+        if self.each_video {
+            return Ok(());
+        }
+        for path in self.paths.values() {
+            let pending = pending_path(path);
+            let file = File::open(&pending)
+                .with_context(|| format!("Unable to open {}", pending.display()))?;
+            let mut sink = self.open(path)?;
+            for line in BufReader::new(file).lines() {
+                let video: Video = serde_json::from_str(&line?)
+                    .with_context(|| format!("Unable to parse {}", pending.display()))?;
+                sink.write_video(&video).await?;
+            }
+            sink.finish().await?;
+            fs::remove_file(&pending)
+                .with_context(|| format!("Unable to delete {}", pending.display()))?;
+        }
+        Ok(())
+    }
+}
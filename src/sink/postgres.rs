@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio_postgres::{Client, NoTls, Statement};
+use tracing::{debug, error};
+
+use super::Sink;
+use crate::Video;
+
+/// Tables are only created if they don't exist, so the schema of an existing database is left
+/// as it is.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS videos (
+    id TEXT PRIMARY KEY,
+    channel_handle TEXT NOT NULL,
+    title TEXT NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE TABLE IF NOT EXISTS comments (
+    id TEXT PRIMARY KEY,
+    video_id TEXT NOT NULL REFERENCES videos (id),
+    parent_id TEXT,
+    author_name TEXT NOT NULL,
+    author_channel_id TEXT,
+    author_channel_url TEXT,
+    text TEXT NOT NULL,
+    like_count INTEGER NOT NULL,
+    sentiment DOUBLE PRECISION,
+    language TEXT,
+    duplicate_of TEXT,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS comments_video_id ON comments (video_id);
+CREATE INDEX IF NOT EXISTS comments_parent_id ON comments (parent_id);
+";
+
+const UPSERT_VIDEO: &str = "
+INSERT INTO videos (id, channel_handle, title) VALUES ($1, $2, $3)
+ON CONFLICT (id) DO UPDATE SET
+    channel_handle = excluded.channel_handle,
+    title = excluded.title,
+    fetched_at = now()
+";
+
+const UPSERT_COMMENT: &str = "
+INSERT INTO comments (
+    id, video_id, parent_id, author_name, author_channel_id, author_channel_url, text,
+    like_count, sentiment, language, duplicate_of
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+ON CONFLICT (id) DO UPDATE SET
+    video_id = excluded.video_id,
+    parent_id = excluded.parent_id,
+    author_name = excluded.author_name,
+    author_channel_id = excluded.author_channel_id,
+    author_channel_url = excluded.author_channel_url,
+    text = excluded.text,
+    like_count = excluded.like_count,
+    sentiment = excluded.sentiment,
+    language = excluded.language,
+    duplicate_of = excluded.duplicate_of,
+    fetched_at = now()
+";
+
+/// Upserts each video and its comments into `videos` and `comments` tables, one transaction per
+/// video, creating the tables on first use.
+pub struct PostgresSink {
+    client: Client,
+    channel_handle: String,
+    upsert_video: Statement,
+    upsert_comment: Statement,
+}
+
+impl PostgresSink {
+    /// Connect to the database described by `dsn`. TLS isn't supported, so the connection must be
+    /// allowed without it.
+    pub async fn new(dsn: &str, channel_handle: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(dsn, NoTls)
+            .await
+            .context("Unable to connect to Postgres")?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                error!("Postgres connection failed: {e}");
+            }
+        });
+
+        client
+            .batch_execute(SCHEMA)
+            .await
+            .context("Unable to create tables")?;
+        let upsert_video = client.prepare(UPSERT_VIDEO).await?;
+        let upsert_comment = client.prepare(UPSERT_COMMENT).await?;
+
+        Ok(Self {
+            client,
+            channel_handle: channel_handle.to_string(),
+            upsert_video,
+            upsert_comment,
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for PostgresSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let transaction = self.client.transaction().await?;
+        transaction
+            .execute(
+                &self.upsert_video,
+                &[&video.id, &self.channel_handle, &video.title],
+            )
+            .await?;
+
+        for comment in &video.comments {
+            transaction
+                .execute(
+                    &self.upsert_comment,
+                    &[
+                        &comment.id,
+                        &video.id,
+                        &None::<&str>,
+                        &comment.author_name,
+                        &comment.author_channel_id,
+                        &comment.author_channel_url,
+                        &comment.text,
+                        &(comment.like_count as i32),
+                        &comment.sentiment.as_ref().map(|s| s.compound),
+                        &comment.language,
+                        &comment.duplicate_of,
+                    ],
+                )
+                .await?;
+            for child in &comment.children {
+                transaction
+                    .execute(
+                        &self.upsert_comment,
+                        &[
+                            &child.id,
+                            &video.id,
+                            &Some(&comment.id),
+                            &child.author_name,
+                            &child.author_channel_id,
+                            &child.author_channel_url,
+                            &child.text,
+                            &(child.like_count as i32),
+                            &child.sentiment.as_ref().map(|s| s.compound),
+                            &child.language,
+                            &child.duplicate_of,
+                        ],
+                    )
+                    .await?;
+            }
+        }
+
+        transaction
+            .commit()
+            .await
+            .with_context(|| format!("Unable to save comments on video {}", video.id))?;
+        debug!(video_id = video.id, "Saved video to Postgres");
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
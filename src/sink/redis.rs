@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use redis::aio::MultiplexedConnection;
+use tracing::debug;
+
+use super::{comment_records, Sink};
+use crate::Video;
+
+/// Adds every comment and reply to a Redis stream, as an entry with the comment id in an `id`
+/// field and the comment as JSON in a `comment` field.
+pub struct RedisSink {
+    connection: MultiplexedConnection,
+    stream: String,
+}
+
+impl RedisSink {
+    pub async fn new(url: &str, stream: &str) -> Result<Self> {
+        let connection = redis::Client::open(url)
+            .context("Invalid Redis URL")?
+            .get_multiplexed_tokio_connection()
+            .await
+            .context("Unable to connect to Redis")?;
+        Ok(Self {
+            connection,
+            stream: stream.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for RedisSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let records = comment_records(video);
+        let mut pipe = redis::pipe();
+        for record in &records {
+            pipe.xadd(
+                &self.stream,
+                "*",
+                &[
+                    ("id", record.comment_id.to_string()),
+                    ("comment", serde_json::to_string(record)?),
+                ],
+            )
+            .ignore();
+        }
+        pipe.query_async::<_, ()>(&mut self.connection)
+            .await
+            .with_context(|| format!("Unable to add comments on video {} to Redis", video.id))?;
+        debug!(
+            video_id = video.id,
+            entries = records.len(),
+            "Added to Redis stream"
+        );
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use google_youtube3::client::GetToken;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{debug, info};
+use url::Url;
+
+use super::{comment_records, Column, Field, Sink};
+use crate::Video;
+
+const BASE_URL: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+
+/// Lets the tool edit the user's spreadsheets. Asked for on top of the YouTube scope the first time
+/// a download writes to a sheet.
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+/// Rows collected before they're appended to the sheet, since Sheets only allows a few dozen
+/// writes a minute.
+const BATCH_ROWS: usize = 2000;
+
+/// Writes every comment and reply as a row of a tab in a Google Sheet, after a row of column
+/// names, replacing whatever the tab held before.
+pub struct SheetsSink {
+    http: reqwest::Client,
+    auth: Box<dyn GetToken>,
+    spreadsheet_id: String,
+    /// The tab's range in A1 notation, which is its quoted title.
+    range: String,
+    columns: Vec<Column>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl SheetsSink {
+    /// Open the tab called `sheet` in the spreadsheet, adding it if it doesn't exist yet and
+    /// clearing it if it does.
+    pub async fn new(
+        http: reqwest::Client,
+        auth: Box<dyn GetToken>,
+        spreadsheet_id: &str,
+        sheet: &str,
+        columns: &[Column],
+    ) -> Result<Self> {
+        let columns = Column::or_all(columns);
+        let mut sink = Self {
+            http,
+            auth,
+            spreadsheet_id: spreadsheet_id.to_string(),
+            range: format!("'{}'", sheet.replace('\'', "''")),
+            columns,
+            rows: vec![],
+        };
+
+        #[derive(Deserialize)]
+        struct Spreadsheet {
+            #[serde(default)]
+            sheets: Vec<Sheet>,
+        }
+        #[derive(Deserialize)]
+        struct Sheet {
+            properties: Properties,
+        }
+        #[derive(Deserialize)]
+        struct Properties {
+            title: String,
+        }
+
+        let spreadsheet: Spreadsheet = sink
+            .request(
+                reqwest::Method::GET,
+                &[spreadsheet_id],
+                &[("fields", "sheets.properties.title")],
+            )
+            .await?
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Unable to open spreadsheet {spreadsheet_id}"))?
+            .json()
+            .await?;
+        if spreadsheet
+            .sheets
+            .iter()
+            .any(|s| s.properties.title == sheet)
+        {
+            let path = format!("{}:clear", sink.range);
+            sink.request(
+                reqwest::Method::POST,
+                &[spreadsheet_id, "values", &path],
+                &[],
+            )
+            .await?
+            .json(&json!({}))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Unable to clear sheet {sheet}"))?;
+        } else {
+            info!(sheet, "Adding sheet to spreadsheet");
+            let body =
+                json!({ "requests": [{ "addSheet": { "properties": { "title": sheet } } }] });
+            let path = format!("{spreadsheet_id}:batchUpdate");
+            sink.request(reqwest::Method::POST, &[&path], &[])
+                .await?
+                .json(&body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .with_context(|| format!("Unable to add sheet {sheet}"))?;
+        }
+
+        let names = sink.columns.iter().map(|c| Value::from(c.name())).collect();
+        sink.rows.push(names);
+        Ok(sink)
+    }
+
+    /// A request to the path under the spreadsheets URL, authorized with a token for the Sheets
+    /// scope. The first segment is the spreadsheet id, with any method called on it.
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &[&str],
+        query: &[(&str, &str)],
+    ) -> Result<reqwest::RequestBuilder> {
+        let mut url = Url::parse(BASE_URL)?;
+        url.path_segments_mut()
+            .expect("the base URL has a path")
+            .extend(path);
+        let token = self
+            .auth
+            .get_token(&[SHEETS_SCOPE])
+            .await
+            .map_err(|e| anyhow!("Unable to get an OAuth token for Google Sheets: {e}"))?
+            .context("No OAuth token was returned for Google Sheets")?;
+        Ok(self
+            .http
+            .request(method, url)
+            .query(query)
+            .bearer_auth(token))
+    }
+
+    /// Append the rows collected so far to the sheet.
+    async fn flush(&mut self) -> Result<()> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let path = format!("{}:append", self.range);
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                &[&self.spreadsheet_id, "values", &path],
+                &[
+                    ("valueInputOption", "RAW"),
+                    ("insertDataOption", "INSERT_ROWS"),
+                ],
+            )
+            .await?
+            .json(&json!({ "values": self.rows }))
+            .send()
+            .await
+            .context("Unable to reach Google Sheets")?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("Appending rows to the sheet failed with {status}: {body}");
+        }
+        debug!(rows = self.rows.len(), "Appended rows to sheet");
+        self.rows.clear();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for SheetsSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        for record in comment_records(video) {
+            let row = self
+                .columns
+                .iter()
+                .map(|column| match column.value(&record) {
+                    Field::Text(text) => Value::from(text.into_owned()),
+                    Field::Number(number) => Value::from(number),
+                    Field::Empty => Value::from(""),
+                })
+                .collect();
+            self.rows.push(row);
+        }
+        if self.rows.len() >= BATCH_ROWS {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.flush().await
+    }
+}
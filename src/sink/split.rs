@@ -0,0 +1,113 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use super::Sink;
+use crate::{
+    file_sink, output::Header, output_path::PERIOD_PLACEHOLDER, FileLayout, ParentComment, Video,
+};
+
+/// How long a stretch of time each file of split output covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SplitPeriod {
+    Month,
+    Year,
+}
+
+impl SplitPeriod {
+    fn key(self, comment: &ParentComment) -> String {
+        let format = match self {
+            SplitPeriod::Month => "%Y-%m",
+            SplitPeriod::Year => "%Y",
+        };
+        match comment.published_at {
+            Some(published_at) => published_at.format(format).to_string(),
+            None => "undated".to_string(),
+        }
+    }
+}
+
+/// Writes output files that each hold the threads started in one month or year, named after the
+/// output file with the period added, such as `comments-2023-07.json`, or put in place of a
+/// `{period}` placeholder. Replies stay in their
+/// thread's file. Threads from files written before publish dates were recorded, and videos
+/// without any threads, go to `comments-undated.json`. Errors are only recorded in the manifest.
+pub struct SplitSink {
+    period: SplitPeriod,
+    layout: FileLayout,
+    header: Header,
+    path: PathBuf,
+    parts: BTreeMap<String, Box<dyn Sink>>,
+}
+
+impl SplitSink {
+    pub fn new(period: SplitPeriod, layout: &FileLayout, header: &Header, path: &Path) -> Self {
+        Self {
+            period,
+            layout: layout.clone(),
+            header: header.clone(),
+            path: path.to_path_buf(),
+            parts: BTreeMap::new(),
+        }
+    }
+
+    fn part_path(&self, key: &str) -> PathBuf {
+        let path = self.path.to_string_lossy();
+        if path.contains(PERIOD_PLACEHOLDER) {
+            return PathBuf::from(path.replace(PERIOD_PLACEHOLDER, key));
+        }
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        let name = match self.path.extension() {
+            Some(extension) => format!("{stem}-{key}.{}", extension.to_string_lossy()),
+            None => format!("{stem}-{key}"),
+        };
+        self.path.with_file_name(name)
+    }
+
+    async fn write_part(&mut self, key: String, video: &Video) -> Result<()> {
+        if !self.parts.contains_key(&key) {
+            let sink = file_sink(&self.layout, &self.header, &self.part_path(&key))?;
+            self.parts.insert(key.clone(), sink);
+        }
+        self.parts.get_mut(&key).unwrap().write_video(video).await
+    }
+}
+
+#[async_trait]
+impl Sink for SplitSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        if video.comments.is_empty() {
+            return self.write_part("undated".to_string(), video).await;
+        }
+
+        let mut threads: BTreeMap<String, Vec<ParentComment>> = BTreeMap::new();
+        for comment in &video.comments {
+            threads
+                .entry(self.period.key(comment))
+                .or_default()
+                .push(comment.clone());
+        }
+        for (key, comments) in threads {
+            let part = Video {
+                comments,
+                ..video.clone()
+            };
+            self.write_part(key, &part).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<()> {
+        for sink in self.parts.into_values() {
+            sink.finish().await?;
+        }
+        Ok(())
+    }
+}
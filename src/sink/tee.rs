@@ -0,0 +1,41 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::Sink;
+use crate::{errors::VideoError, Video};
+
+/// Passes every video on to several sinks, such as one for each format of a run writing more than
+/// one, so a single download feeds them all.
+pub struct TeeSink {
+    sinks: Vec<Box<dyn Sink>>,
+}
+
+impl TeeSink {
+    pub fn new(sinks: Vec<Box<dyn Sink>>) -> Self {
+        Self { sinks }
+    }
+}
+
+#[async_trait]
+impl Sink for TeeSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        for sink in &mut self.sinks {
+            sink.write_video(video).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_errors(&mut self, errors: &[VideoError]) -> Result<()> {
+        for sink in &mut self.sinks {
+            sink.write_errors(errors).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<()> {
+        for sink in self.sinks {
+            sink.finish().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use google_youtube3::chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{output::Header, report::video_url, ParentComment, Video};
+
+/// Front matter of a video's page. Both Hugo and Jekyll read YAML between `---` lines.
+#[derive(Serialize)]
+struct FrontMatter<'a> {
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<DateTime<Utc>>,
+    video_id: &'a str,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail: Option<&'a str>,
+    comments: usize,
+    replies: usize,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    comments_disabled: bool,
+}
+
+/// The data file holding a video's threads, looked up by the page through its `video_id`.
+#[derive(Serialize)]
+struct CommentData<'a> {
+    video_id: &'a str,
+    title: &'a str,
+    comments: &'a [ParentComment],
+}
+
+/// Write the videos as the source of a static site: a Markdown page for each video under
+/// `content/videos`, its threads under `data/comments`, and the header as `data/channel.yaml`.
+pub fn write(header: &Header, videos: &[Video], dir: &Path) -> Result<()> {
+    let pages = dir.join("content").join("videos");
+    let data = dir.join("data").join("comments");
+    for path in [&pages, &data] {
+        fs::create_dir_all(path).with_context(|| format!("Unable to create {}", path.display()))?;
+    }
+    write_yaml(&dir.join("data").join("channel.yaml"), header)?;
+
+    for video in videos {
+        let front_matter = FrontMatter {
+            title: &video.title,
+            date: video.published_at,
+            video_id: &video.id,
+            url: video_url(&video.id),
+            thumbnail: video.thumbnails.as_ref().and_then(|t| t.medium.as_deref()),
+            comments: video.comments.len(),
+            replies: video.comments.iter().map(|c| c.children.len()).sum(),
+            comments_disabled: video.comments_disabled,
+        };
+        let path = pages.join(format!("{}.md", video.id));
+        let file =
+            File::create(&path).with_context(|| format!("Unable to create {}", path.display()))?;
+        let mut w = BufWriter::new(file);
+        // serde_yaml starts documents without the leading `---` that front matter needs.
+        writeln!(w, "---")?;
+        serde_yaml::to_writer(&mut w, &front_matter)?;
+        writeln!(w, "---")?;
+        w.flush()?;
+
+        let comments = CommentData {
+            video_id: &video.id,
+            title: &video.title,
+            comments: &video.comments,
+        };
+        write_yaml(&data.join(format!("{}.yaml", video.id)), &comments)?;
+    }
+    Ok(())
+}
+
+fn write_yaml(path: &Path, value: &impl Serialize) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Unable to create {}", path.display()))?;
+    let mut w = BufWriter::new(file);
+    serde_yaml::to_writer(&mut w, value)?;
+    w.flush()?;
+    Ok(())
+}
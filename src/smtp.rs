@@ -0,0 +1,211 @@
+use std::{env, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use google_youtube3::chrono::Utc;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_native_tls::{native_tls, TlsConnector};
+use url::Url;
+
+/// Longest wait for the server to connect or answer a command.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+/// An SMTP server to send mail through, set up from the environment.
+pub struct Smtp {
+    host: String,
+    port: u16,
+    /// Whether the connection is TLS from the start, rather than upgraded with STARTTLS.
+    implicit_tls: bool,
+    credentials: Option<(String, String)>,
+    from: String,
+}
+
+impl Smtp {
+    /// Read `YT_SMTP_URL`, such as smtp://mail.example.com:587 or smtps://mail.example.com,
+    /// along with `YT_SMTP_USERNAME`, `YT_SMTP_PASSWORD` and `YT_SMTP_FROM`.
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let url = var("YT_SMTP_URL").context("YT_SMTP_URL must be set to send email")?;
+        let parsed = Url::parse(&url).with_context(|| format!("Invalid SMTP URL {url}"))?;
+        let (implicit_tls, default_port) = match parsed.scheme() {
+            "smtp" => (false, 587),
+            "smtps" => (true, 465),
+            scheme => bail!("Unsupported SMTP scheme {scheme}, expected smtp or smtps"),
+        };
+        let host = parsed
+            .host_str()
+            .with_context(|| format!("SMTP URL {url} has no host"))?
+            .to_string();
+        let credentials = var("YT_SMTP_USERNAME")
+            .map(|username| (username, var("YT_SMTP_PASSWORD").unwrap_or_default()));
+        let from = var("YT_SMTP_FROM")
+            .or_else(|| credentials.as_ref().map(|(username, _)| username.clone()))
+            .context("YT_SMTP_FROM must be set to send email without YT_SMTP_USERNAME")?;
+        Ok(Self {
+            port: parsed.port().unwrap_or(default_port),
+            host,
+            implicit_tls,
+            credentials,
+            from,
+        })
+    }
+
+    /// Send a plain text email to one recipient.
+    pub async fn send(&self, to: &str, subject: &str, body: &str) -> Result<()> {
+        tokio::time::timeout(TIMEOUT * 4, self.deliver(to, subject, body))
+            .await
+            .context("Timed out sending email")?
+            .with_context(|| format!("Unable to send email through {}", self.host))
+    }
+
+    async fn deliver(&self, to: &str, subject: &str, body: &str) -> Result<()> {
+        let tcp =
+            tokio::time::timeout(TIMEOUT, TcpStream::connect((self.host.as_str(), self.port)))
+                .await
+                .context("Timed out connecting")??;
+        let stream: Box<dyn Stream> = if self.implicit_tls {
+            Box::new(self.tls(tcp).await?)
+        } else {
+            Box::new(tcp)
+        };
+        let mut connection = Connection {
+            stream: BufReader::new(stream),
+        };
+        connection.reply(&[220]).await?;
+        let mut extensions = connection.command("EHLO localhost", &[250]).await?;
+
+        if !self.implicit_tls {
+            if extensions
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case("STARTTLS"))
+            {
+                connection.command("STARTTLS", &[220]).await?;
+                let tls = self.tls(connection.stream.into_inner()).await?;
+                connection = Connection {
+                    stream: BufReader::new(Box::new(tls)),
+                };
+                extensions = connection.command("EHLO localhost", &[250]).await?;
+            } else if self.credentials.is_some() {
+                bail!("The server doesn't offer STARTTLS, so the password would be sent in the clear. Use an smtps:// URL if it takes TLS on another port");
+            }
+        }
+        if let Some((username, password)) = &self.credentials {
+            let offers_plain = extensions.iter().any(|e| {
+                let e = e.to_uppercase();
+                e.starts_with("AUTH") && e.split_whitespace().any(|m| m == "PLAIN")
+            });
+            if !offers_plain {
+                bail!("The server doesn't accept AUTH PLAIN");
+            }
+            let token = base64::engine::general_purpose::STANDARD
+                .encode(format!("\0{username}\0{password}"));
+            connection
+                .command(&format!("AUTH PLAIN {token}"), &[235])
+                .await
+                .context("Signing in to the SMTP server failed")?;
+        }
+
+        connection
+            .command(&format!("MAIL FROM:<{}>", self.from), &[250])
+            .await?;
+        connection
+            .command(&format!("RCPT TO:<{to}>"), &[250, 251])
+            .await?;
+        connection.command("DATA", &[354]).await?;
+        let message = format!(
+            "From: {}\r\nTo: {to}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n{}\r\n.",
+            self.from,
+            encode_header(subject),
+            Utc::now().to_rfc2822(),
+            dot_stuff(body),
+        );
+        connection.command(&message, &[250]).await?;
+        connection.command("QUIT", &[221]).await?;
+        Ok(())
+    }
+
+    async fn tls<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        stream: S,
+    ) -> Result<tokio_native_tls::TlsStream<S>> {
+        let connector = TlsConnector::from(native_tls::TlsConnector::new()?);
+        Ok(connector.connect(&self.host, stream).await?)
+    }
+}
+
+struct Connection {
+    stream: BufReader<Box<dyn Stream>>,
+}
+
+impl Connection {
+    /// Send a line and read the reply, returning its lines after the first.
+    async fn command(&mut self, line: &str, expected: &[u16]) -> Result<Vec<String>> {
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{line}\r\n").as_bytes()).await?;
+        stream.flush().await?;
+        self.reply(expected).await
+    }
+
+    /// Read a possibly multiline reply, failing unless its code is one of those expected.
+    async fn reply(&mut self, expected: &[u16]) -> Result<Vec<String>> {
+        let mut lines = vec![];
+        loop {
+            let mut line = String::new();
+            let read = tokio::time::timeout(TIMEOUT, self.stream.read_line(&mut line))
+                .await
+                .context("Timed out waiting for the SMTP server")??;
+            if read == 0 {
+                bail!("The SMTP server closed the connection");
+            }
+            let line = line.trim_end();
+            let code: u16 = line
+                .get(..3)
+                .and_then(|code| code.parse().ok())
+                .with_context(|| format!("Unexpected reply from the SMTP server: {line}"))?;
+            if !expected.contains(&code) {
+                bail!("The SMTP server replied {line}");
+            }
+            lines.push(line.get(4..).unwrap_or_default().to_string());
+            // The last line of a reply has a space after the code, the others a hyphen.
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+        }
+        lines.remove(0);
+        Ok(lines)
+    }
+}
+
+/// Encode a header value as UTF-8 if it isn't plain ASCII.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!(
+            "=?UTF-8?B?{}?=",
+            base64::engine::general_purpose::STANDARD.encode(value)
+        )
+    }
+}
+
+/// End lines with CRLF and double the dot at the start of any line, so that none ends the message
+/// early.
+fn dot_stuff(body: &str) -> String {
+    body.lines()
+        .map(|line| {
+            if line.starts_with('.') {
+                format!(".{line}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
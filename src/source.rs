@@ -0,0 +1,231 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use google_youtube3::api::{
+    CommentListResponse, CommentThreadListResponse, PlaylistItemListResponse,
+};
+
+use crate::{api, Client, FetchOptions, ModerationStatus};
+
+/// The pages of API responses that videos and their comments are read from. Pagination and reply
+/// fetching only go through this trait, so they can run against something other than the live
+/// API.
+#[async_trait]
+pub trait CommentSource: Sync {
+    /// One page of the items in a playlist.
+    async fn playlist_items(
+        &self,
+        playlist_id: &str,
+        page_token: &str,
+    ) -> Result<PlaylistItemListResponse>;
+
+    /// One page of the comment threads of a video.
+    async fn comment_threads(
+        &self,
+        video_id: &str,
+        page_token: &str,
+        api_text_format: &str,
+        options: &FetchOptions,
+    ) -> Result<CommentThreadListResponse>;
+
+    /// One page of the replies to a top-level comment.
+    async fn replies(
+        &self,
+        parent_id: &str,
+        page_token: &str,
+        api_text_format: &str,
+    ) -> Result<CommentListResponse>;
+}
+
+#[async_trait]
+impl CommentSource for Client {
+    async fn playlist_items(
+        &self,
+        playlist_id: &str,
+        page_token: &str,
+    ) -> Result<PlaylistItemListResponse> {
+        self.before_request().await;
+        api::list(
+            self,
+            "playlistItems",
+            &[
+                ("part", "snippet,contentDetails"),
+                ("maxResults", &self.page_size(50)),
+                ("playlistId", playlist_id),
+                ("pageToken", page_token),
+            ],
+        )
+        .await
+    }
+
+    async fn comment_threads(
+        &self,
+        video_id: &str,
+        page_token: &str,
+        api_text_format: &str,
+        options: &FetchOptions,
+    ) -> Result<CommentThreadListResponse> {
+        // Pages after the first, such as of a video being resumed, were never fetched together.
+        if let Some(threads) = &self.channel_threads {
+            if let Some(page) = threads.take(video_id).filter(|_| page_token.is_empty()) {
+                return Ok(page);
+            }
+        }
+        self.before_request().await;
+        api::list(
+            self,
+            "commentThreads",
+            &[
+                (
+                    "part",
+                    if options.no_replies {
+                        "snippet"
+                    } else {
+                        "snippet,replies"
+                    },
+                ),
+                ("textFormat", api_text_format),
+                ("order", options.order.api_value()),
+                ("videoId", video_id),
+                ("maxResults", &self.page_size(100)),
+                ("pageToken", page_token),
+                (
+                    "searchTerms",
+                    options.search_terms.as_deref().unwrap_or_default(),
+                ),
+                (
+                    "moderationStatus",
+                    options
+                        .moderation_status
+                        .map_or("", ModerationStatus::api_value),
+                ),
+            ],
+        )
+        .await
+    }
+
+    async fn replies(
+        &self,
+        parent_id: &str,
+        page_token: &str,
+        api_text_format: &str,
+    ) -> Result<CommentListResponse> {
+        self.before_request().await;
+        api::list(
+            self,
+            "comments",
+            &[
+                ("part", "snippet"),
+                ("textFormat", api_text_format),
+                ("parentId", parent_id),
+                ("maxResults", &self.page_size(100)),
+                ("pageToken", page_token),
+            ],
+        )
+        .await
+    }
+}
+
+/// A source serving canned pages from memory, for testing pagination without the API.
+#[cfg(test)]
+pub mod fake {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use anyhow::{Context, Result};
+    use async_trait::async_trait;
+    use google_youtube3::api::{
+        CommentListResponse, CommentThreadListResponse, PlaylistItemListResponse,
+    };
+    use serde::de::DeserializeOwned;
+    use serde_json::Value;
+
+    use super::CommentSource;
+    use crate::FetchOptions;
+
+    /// Pages keyed by the playlist, video or thread they belong to and their page token, which is
+    /// empty for the first page. Asking for a page that wasn't added fails.
+    #[derive(Default)]
+    pub struct FakeSource {
+        playlist_items: HashMap<(String, String), Value>,
+        comment_threads: HashMap<(String, String), Value>,
+        replies: HashMap<(String, String), Value>,
+        /// Every page asked for, as the endpoint, id and page token, in order.
+        pub requests: Mutex<Vec<(&'static str, String, String)>>,
+    }
+
+    impl FakeSource {
+        pub fn playlist_page(mut self, playlist_id: &str, page_token: &str, page: Value) -> Self {
+            self.playlist_items
+                .insert((playlist_id.to_string(), page_token.to_string()), page);
+            self
+        }
+
+        pub fn thread_page(mut self, video_id: &str, page_token: &str, page: Value) -> Self {
+            self.comment_threads
+                .insert((video_id.to_string(), page_token.to_string()), page);
+            self
+        }
+
+        pub fn reply_page(mut self, parent_id: &str, page_token: &str, page: Value) -> Self {
+            self.replies
+                .insert((parent_id.to_string(), page_token.to_string()), page);
+            self
+        }
+
+        fn serve<T: DeserializeOwned>(
+            &self,
+            endpoint: &'static str,
+            pages: &HashMap<(String, String), Value>,
+            id: &str,
+            page_token: &str,
+        ) -> Result<T> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((endpoint, id.to_string(), page_token.to_string()));
+            let page = pages
+                .get(&(id.to_string(), page_token.to_string()))
+                .with_context(|| format!("No {endpoint} page {page_token:?} for {id}"))?;
+            Ok(serde_json::from_value(page.clone())?)
+        }
+    }
+
+    #[async_trait]
+    impl CommentSource for FakeSource {
+        async fn playlist_items(
+            &self,
+            playlist_id: &str,
+            page_token: &str,
+        ) -> Result<PlaylistItemListResponse> {
+            self.serve(
+                "playlistItems",
+                &self.playlist_items,
+                playlist_id,
+                page_token,
+            )
+        }
+
+        async fn comment_threads(
+            &self,
+            video_id: &str,
+            page_token: &str,
+            _api_text_format: &str,
+            _options: &FetchOptions,
+        ) -> Result<CommentThreadListResponse> {
+            self.serve(
+                "commentThreads",
+                &self.comment_threads,
+                video_id,
+                page_token,
+            )
+        }
+
+        async fn replies(
+            &self,
+            parent_id: &str,
+            page_token: &str,
+            _api_text_format: &str,
+        ) -> Result<CommentListResponse> {
+            self.serve("comments", &self.replies, parent_id, page_token)
+        }
+    }
+}
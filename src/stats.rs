@@ -0,0 +1,335 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use anyhow::Result;
+use clap::{Args, ValueHint};
+use serde::Serialize;
+
+use crate::{graph::replied_to, output, ParentComment, Video};
+
+/// Longest thread text shown in the tables before it is truncated.
+const MAX_TEXT_WIDTH: usize = 60;
+
+/// Upper bounds, in characters, of the comment length buckets. Longer comments go in a last bucket.
+const LENGTH_BOUNDS: &[usize] = &[20, 50, 100, 200, 500, 1000];
+
+/// Upper bounds of the replies per thread buckets. Threads with more go in a last bucket.
+const REPLY_BOUNDS: &[usize] = &[0, 1, 5, 10, 50, 100];
+
+#[derive(Args)]
+pub struct StatsArgs {
+    /// Name of a JSON file previously written by this tool.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+
+    /// Number of entries to show in the ranked lists.
+    #[arg(short, long, default_value_t = 10)]
+    top: usize,
+
+    /// Print the statistics as JSON instead of tables.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Stats {
+    total_videos: usize,
+    total_comments: usize,
+    total_replies: usize,
+    /// Average over every thread of how deep its replies go. See [`VideoStats::average_depth`].
+    average_depth: f64,
+    /// How many comments and replies are of each length.
+    comment_lengths: Vec<LengthBucket>,
+    /// How many threads have each number of replies.
+    replies_per_thread: Vec<ReplyBucket>,
+    top_commenters: Vec<CommenterStats>,
+    top_threads: Vec<ThreadStats>,
+    videos: Vec<VideoStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LengthBucket {
+    min: usize,
+    /// Missing for the last bucket.
+    max: Option<usize>,
+    comments: usize,
+    replies: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ReplyBucket {
+    min: usize,
+    /// Missing for the last bucket.
+    max: Option<usize>,
+    threads: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct CommenterStats {
+    author_name: String,
+    author_channel_url: Option<String>,
+    comments: usize,
+    replies: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ThreadStats {
+    video_id: String,
+    video_title: String,
+    author_name: String,
+    text: String,
+    replies: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct VideoStats {
+    id: String,
+    title: String,
+    comments: usize,
+    replies: usize,
+    /// Average over the video's threads of how many replies deep they go. A thread without
+    /// replies is 0 deep and one with replies 1, plus one for every reply in a chain of replies
+    /// that each start with an @mention of the one before.
+    average_depth: f64,
+    max_depth: usize,
+}
+
+pub fn run(args: &StatsArgs) -> Result<()> {
+    let videos = output::read(&args.input_name)?.videos;
+
+    let stats = compute(&videos, args.top);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print_tables(&stats);
+    }
+
+    Ok(())
+}
+
+fn compute(videos: &[Video], top: usize) -> Stats {
+    // Authors are keyed by channel id where available since display names aren't unique.
+    let mut commenters: HashMap<&str, CommenterStats> = HashMap::new();
+    let mut threads = vec![];
+    let mut video_stats = vec![];
+    let mut comment_lengths = buckets(LENGTH_BOUNDS, |min, max| LengthBucket {
+        min,
+        max,
+        comments: 0,
+        replies: 0,
+    });
+    let mut replies_per_thread = buckets(REPLY_BOUNDS, |min, max| ReplyBucket {
+        min,
+        max,
+        threads: 0,
+    });
+    let mut total_depth = 0;
+
+    for video in videos {
+        let mut replies = 0;
+        let (mut video_depth, mut max_depth) = (0, 0);
+        for comment in &video.comments {
+            comment_lengths[bucket(LENGTH_BOUNDS, comment.text.chars().count())].comments += 1;
+            // Files written with --no-replies have the count without the replies themselves.
+            let reply_count = comment.children.len().max(comment.reply_count as usize);
+            replies_per_thread[bucket(REPLY_BOUNDS, reply_count)].threads += 1;
+            let depth = thread_depth(comment);
+            video_depth += depth;
+            max_depth = max_depth.max(depth);
+
+            let key = comment
+                .author_channel_id
+                .as_deref()
+                .unwrap_or(&comment.author_name);
+            let commenter = commenters.entry(key).or_insert_with(|| CommenterStats {
+                author_name: comment.author_name.clone(),
+                author_channel_url: comment.author_channel_url.clone(),
+                ..Default::default()
+            });
+            commenter.comments += 1;
+
+            for child in &comment.children {
+                let key = child
+                    .author_channel_id
+                    .as_deref()
+                    .unwrap_or(&child.author_name);
+                let commenter = commenters.entry(key).or_insert_with(|| CommenterStats {
+                    author_name: child.author_name.clone(),
+                    author_channel_url: child.author_channel_url.clone(),
+                    ..Default::default()
+                });
+                commenter.replies += 1;
+                comment_lengths[bucket(LENGTH_BOUNDS, child.text.chars().count())].replies += 1;
+            }
+
+            replies += comment.children.len();
+            threads.push(ThreadStats {
+                video_id: video.id.clone(),
+                video_title: video.title.clone(),
+                author_name: comment.author_name.clone(),
+                text: comment.text.clone(),
+                replies: comment.children.len(),
+            });
+        }
+
+        video_stats.push(VideoStats {
+            id: video.id.clone(),
+            title: video.title.clone(),
+            comments: video.comments.len(),
+            replies,
+            average_depth: average(video_depth, video.comments.len()),
+            max_depth,
+        });
+        total_depth += video_depth;
+    }
+
+    let mut top_commenters: Vec<CommenterStats> = commenters.into_values().collect();
+    top_commenters.sort_by(|a, b| {
+        (b.comments + b.replies)
+            .cmp(&(a.comments + a.replies))
+            .then_with(|| a.author_name.cmp(&b.author_name))
+    });
+    top_commenters.truncate(top);
+
+    threads.sort_by_key(|t| Reverse(t.replies));
+    threads.truncate(top);
+
+    let total_comments = video_stats.iter().map(|v| v.comments).sum();
+    Stats {
+        total_videos: videos.len(),
+        total_comments,
+        total_replies: video_stats.iter().map(|v| v.replies).sum(),
+        average_depth: average(total_depth, total_comments),
+        comment_lengths,
+        replies_per_thread,
+        top_commenters,
+        top_threads: threads,
+        videos: video_stats,
+    }
+}
+
+/// How deep the replies in a thread go, following the @mentions replies start with to the posts
+/// they answer.
+fn thread_depth(comment: &ParentComment) -> usize {
+    let mut names = vec![comment.author_name.as_str()];
+    // The depth of each post in the thread, the top-level comment first.
+    let mut depths = vec![0];
+    for child in &comment.children {
+        let depth = replied_to(&child.text, &names).map_or(1, |index| depths[index] + 1);
+        names.push(child.author_name.as_str());
+        depths.push(depth);
+    }
+    depths.into_iter().max().unwrap_or_default()
+}
+
+/// A bucket for every upper bound, and one for everything above the last.
+fn buckets<T>(bounds: &[usize], new: impl Fn(usize, Option<usize>) -> T) -> Vec<T> {
+    let mut min = 0;
+    let mut buckets = vec![];
+    for &max in bounds {
+        buckets.push(new(min, Some(max)));
+        min = max + 1;
+    }
+    buckets.push(new(min, None));
+    buckets
+}
+
+/// Index of the bucket the value falls in.
+fn bucket(bounds: &[usize], value: usize) -> usize {
+    bounds
+        .iter()
+        .position(|&max| value <= max)
+        .unwrap_or(bounds.len())
+}
+
+fn average(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+fn range_label(min: usize, max: Option<usize>) -> String {
+    match max {
+        Some(max) if max == min => min.to_string(),
+        Some(max) => format!("{min}-{max}"),
+        None => format!("{min}+"),
+    }
+}
+
+fn print_tables(stats: &Stats) {
+    println!("Videos:   {}", stats.total_videos);
+    println!("Comments: {}", stats.total_comments);
+    println!("Replies:  {}", stats.total_replies);
+    println!("Average thread depth: {:.2}", stats.average_depth);
+
+    println!("\nComment length in characters");
+    println!("{:>10} {:>8} {:>8}", "Length", "Comments", "Replies");
+    for bucket in &stats.comment_lengths {
+        println!(
+            "{:>10} {:>8} {:>8}",
+            range_label(bucket.min, bucket.max),
+            bucket.comments,
+            bucket.replies
+        );
+    }
+
+    println!("\nReplies per thread");
+    println!("{:>10} {:>8}", "Replies", "Threads");
+    for bucket in &stats.replies_per_thread {
+        println!(
+            "{:>10} {:>8}",
+            range_label(bucket.min, bucket.max),
+            bucket.threads
+        );
+    }
+
+    println!("\nMost active commenters");
+    println!("{:>8} {:>8}  Author", "Comments", "Replies");
+    for commenter in &stats.top_commenters {
+        println!(
+            "{:>8} {:>8}  {}",
+            commenter.comments, commenter.replies, commenter.author_name
+        );
+    }
+
+    println!("\nThreads with the most replies");
+    println!("{:>8}  {:<11}  Comment", "Replies", "Video");
+    for thread in &stats.top_threads {
+        println!(
+            "{:>8}  {:<11}  {}: {}",
+            thread.replies,
+            thread.video_id,
+            thread.author_name,
+            truncate(&thread.text)
+        );
+    }
+
+    println!("\nComments per video");
+    println!(
+        "{:>8} {:>8} {:>6} {:>6}  {:<11}  Title",
+        "Comments", "Replies", "Depth", "Max", "Video"
+    );
+    for video in &stats.videos {
+        println!(
+            "{:>8} {:>8} {:>6.2} {:>6}  {:<11}  {}",
+            video.comments,
+            video.replies,
+            video.average_depth,
+            video.max_depth,
+            video.id,
+            video.title
+        );
+    }
+}
+
+/// Shorten text to a single line suitable for a table cell.
+fn truncate(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > MAX_TEXT_WIDTH || text.lines().nth(1).is_some() {
+        let shortened: String = line.chars().take(MAX_TEXT_WIDTH - 3).collect();
+        format!("{shortened}...")
+    } else {
+        line.to_string()
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use google_youtube3::chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::quota;
+
+/// Shortest time between rewrites of the status file while comments are being fetched. Starting
+/// or finishing a video, or the download, always rewrites it.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum State {
+    Running,
+    Finished,
+    /// The download stopped without finishing, such as on an error.
+    Failed,
+}
+
+/// The contents of the status file.
+#[derive(Debug, Serialize)]
+struct Status {
+    state: State,
+    started_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    videos: usize,
+    /// Index of the video being fetched, counting from 0.
+    video_index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    video_id: Option<String>,
+    videos_done: usize,
+    comments: usize,
+    replies: usize,
+    errors: usize,
+    requests: u64,
+    quota_used: u64,
+    /// Estimated seconds left, from how long the comment pages fetched so far took compared to
+    /// how many the videos are expected to take. Missing until the first page is fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eta_secs: Option<u64>,
+}
+
+/// A JSON file describing how far a download has got, rewritten as it goes, so that dashboards
+/// and scripts wrapping a long run can follow it without reading the terminal.
+pub struct StatusFile {
+    path: PathBuf,
+    /// Comment pages each video is expected to start at, followed by the expected total.
+    starts: Vec<u64>,
+    started: Instant,
+    written: Option<Instant>,
+    status: Status,
+}
+
+impl StatusFile {
+    /// `expected_pages` holds the rough number of comment pages each video will take.
+    pub fn create(path: &Path, expected_pages: &[u64]) -> Result<Self> {
+        let mut starts = vec![0];
+        for pages in expected_pages {
+            starts.push(starts[starts.len() - 1] + pages);
+        }
+        let now = Utc::now();
+        let mut file = Self {
+            path: path.to_path_buf(),
+            starts,
+            started: Instant::now(),
+            written: None,
+            status: Status {
+                state: State::Running,
+                started_at: now,
+                updated_at: now,
+                videos: expected_pages.len(),
+                video_index: 0,
+                video_id: None,
+                videos_done: 0,
+                comments: 0,
+                replies: 0,
+                errors: 0,
+                requests: 0,
+                quota_used: 0,
+                eta_secs: None,
+            },
+        };
+        file.write()
+            .with_context(|| format!("Unable to write status file {}", path.display()))?;
+        Ok(file)
+    }
+
+    /// Add videos listed after the download started.
+    pub fn videos_listed(&mut self, expected_pages: &[u64]) {
+        for pages in expected_pages {
+            self.starts.push(self.starts[self.starts.len() - 1] + pages);
+        }
+        self.status.videos += expected_pages.len();
+        self.write_or_warn();
+    }
+
+    pub fn video_started(&mut self, index: usize, video_id: &str) {
+        self.status.video_index = index;
+        self.status.video_id = Some(video_id.to_string());
+        self.pages_done(self.starts[index]);
+        self.write_or_warn();
+    }
+
+    /// Called after each page of the current video, with how many it has taken so far.
+    pub fn page_fetched(&mut self, pages: usize) {
+        let index = self.status.video_index;
+        let (start, end) = (self.starts[index], self.starts[index + 1]);
+        // Stay short of the next video, in case the estimate was low.
+        self.pages_done((start + pages as u64).min(end.saturating_sub(1).max(start)));
+        if self.written.is_none_or(|at| at.elapsed() >= MIN_INTERVAL) {
+            self.write_or_warn();
+        }
+    }
+
+    pub fn video_finished(&mut self, comments: usize, replies: usize) {
+        self.status.videos_done += 1;
+        self.status.comments += comments;
+        self.status.replies += replies;
+        let next = (self.status.video_index + 1).min(self.starts.len() - 1);
+        self.pages_done(self.starts[next]);
+        self.write_or_warn();
+    }
+
+    pub fn error(&mut self) {
+        self.status.errors += 1;
+        self.write_or_warn();
+    }
+
+    pub fn finish(&mut self) {
+        self.status.state = State::Finished;
+        self.status.video_id = None;
+        self.status.eta_secs = Some(0);
+        self.write_or_warn();
+    }
+
+    /// Estimate the time left from the pages fetched so far.
+    fn pages_done(&mut self, done: u64) {
+        let total = self.starts[self.starts.len() - 1];
+        self.status.eta_secs = (done > 0).then(|| {
+            let elapsed = self.started.elapsed().as_secs_f64();
+            (elapsed * total.saturating_sub(done) as f64 / done as f64).round() as u64
+        });
+    }
+
+    fn write_or_warn(&mut self) {
+        if let Err(e) = self.write() {
+            warn!(path = %self.path.display(), "Unable to write status file: {e:#}");
+        }
+    }
+
+    /// Replace the file in one step, so readers never see it half written.
+    fn write(&mut self) -> Result<()> {
+        self.status.updated_at = Utc::now();
+        self.status.requests = quota::requests();
+        self.status.quota_used = quota::used();
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_vec_pretty(&self.status)?)?;
+        fs::rename(&temp, &self.path)?;
+        self.written = Some(Instant::now());
+        Ok(())
+    }
+}
+
+impl Drop for StatusFile {
+    fn drop(&mut self) {
+        if self.status.state == State::Running {
+            self.status.state = State::Failed;
+            self.write_or_warn();
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tracing::{debug, warn};
+
+/// Tell systemd that the service has started, for units with `Type=notify`. Does nothing when
+/// not run by systemd.
+pub fn ready() {
+    notify("READY=1");
+}
+
+/// Show what the service is doing in `systemctl status`.
+pub fn status(status: &str) {
+    notify(&format!("STATUS={status}"));
+}
+
+/// Tell systemd that the service is shutting down.
+pub fn stopping() {
+    notify("STOPPING=1");
+}
+
+/// When the work last showed it was moving, such as by getting a response from the API.
+static HEARTBEAT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Set while the process is meant to be idle, such as between polls, when no heartbeats arrive.
+static WAITING: AtomicBool = AtomicBool::new(false);
+
+/// Record that the work is making progress, for the watchdog.
+pub fn heartbeat() {
+    *HEARTBEAT.lock().unwrap() = Some(Instant::now());
+}
+
+/// Mark the start or end of a deliberate wait, during which the watchdog is pinged without
+/// heartbeats.
+pub fn waiting(waiting: bool) {
+    WAITING.store(waiting, Ordering::Relaxed);
+    heartbeat();
+}
+
+/// Ping systemd's watchdog at half the interval the unit's `WatchdogSec=` asks for, but only while
+/// heartbeats keep arriving within that interval or the process is deliberately waiting, so one
+/// whose work has hung stops pinging and is restarted. Does nothing when the watchdog isn't on.
+pub fn start_watchdog() {
+    let Some(interval) = watchdog_interval() else {
+        return;
+    };
+    debug!(?interval, "Pinging the systemd watchdog");
+    heartbeat();
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval / 2);
+        let mut stalled = false;
+        loop {
+            ticks.tick().await;
+            let alive = WAITING.load(Ordering::Relaxed)
+                || HEARTBEAT
+                    .lock()
+                    .unwrap()
+                    .is_some_and(|at| at.elapsed() < interval);
+            if alive {
+                notify("WATCHDOG=1");
+            } else if !stalled {
+                warn!(
+                    ?interval,
+                    "No progress within the watchdog interval, no longer pinging it"
+                );
+            }
+            stalled = !alive;
+        }
+    });
+}
+
+/// The watchdog interval systemd set for this process, if any.
+fn watchdog_interval() -> Option<Duration> {
+    // Set for another process, such as the one that started this one.
+    if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+        if pid.parse() != Ok(std::process::id()) {
+            return None;
+        }
+    }
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    (usec > 0).then(|| Duration::from_micros(usec))
+}
+
+/// Send a state change to the socket systemd passes in `NOTIFY_SOCKET`, if there is one.
+fn notify(state: &str) {
+    if let Err(e) = send(state) {
+        warn!("Unable to notify systemd: {e}");
+    }
+}
+
+#[cfg(unix)]
+fn send(state: &str) -> std::io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(());
+    };
+    let socket = UnixDatagram::unbound()?;
+    // Names starting with @ are in the abstract namespace, which only Linux has.
+    match path.as_encoded_bytes().strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+            socket.send_to_addr(state.as_bytes(), &SocketAddr::from_abstract_name(name)?)?;
+        }
+        #[cfg(not(target_os = "linux"))]
+        Some(_) => return Ok(()),
+        None => {
+            socket.send_to(state.as_bytes(), &path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send(_state: &str) -> std::io::Result<()> {
+    Ok(())
+}
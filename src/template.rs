@@ -0,0 +1,65 @@
+use std::{fs, io::Write, path::Path};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use handlebars::Handlebars;
+use serde_json::{json, Value};
+
+use crate::{output::Header, sink::Sink, Video};
+
+const TEMPLATE_NAME: &str = "video";
+
+/// Renders every video through a Handlebars template and writes the results one after another.
+/// The template sees the fields of the video as in the JSON output, plus `header`, and `first`,
+/// which is only true for the first video so a heading can be written once. Output is only
+/// HTML-escaped for templates whose name ends in `.html` or `.html.hbs`.
+pub struct TemplateSink<W: Write> {
+    w: W,
+    registry: Handlebars<'static>,
+    header: Value,
+    first: bool,
+}
+
+impl<W: Write> TemplateSink<W> {
+    pub fn new(template_name: &str, header: &Header, w: W) -> Result<Self> {
+        let template = fs::read_to_string(template_name)
+            .with_context(|| format!("Unable to open template {template_name}"))?;
+        let mut registry = Handlebars::new();
+        let name = Path::new(template_name)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        if !(name.ends_with(".html") || name.ends_with(".html.hbs")) {
+            registry.register_escape_fn(handlebars::no_escape);
+        }
+        registry
+            .register_template_string(TEMPLATE_NAME, template)
+            .with_context(|| format!("Invalid template {template_name}"))?;
+        Ok(Self {
+            w,
+            registry,
+            header: serde_json::to_value(header)?,
+            first: true,
+        })
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for TemplateSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let mut data = serde_json::to_value(video)?;
+        data["header"] = self.header.clone();
+        data["first"] = json!(self.first);
+        self.registry
+            .render_to_write(TEMPLATE_NAME, &data, &mut self.w)
+            .with_context(|| format!("Unable to render the template for video {}", video.id))?;
+        self.w.flush()?;
+        self.first = false;
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.w.flush()?;
+        Ok(())
+    }
+}
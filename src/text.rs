@@ -0,0 +1,95 @@
+use emojis::{Emoji, SkinTone};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::Video;
+
+/// What to do with emoji in comment text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmojiHandling {
+    /// Remove them.
+    Strip,
+    /// Replace them with their shortcodes, such as `:thumbsup:`.
+    Shortcode,
+}
+
+/// Changes made to the text of every comment and reply as soon as it's fetched, so that the
+/// filters, analyses and every output format see the same text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextCleanup {
+    /// Normalize text to Unicode NFC, so that accented letters are always a single code point.
+    pub nfc: bool,
+    pub emoji: Option<EmojiHandling>,
+}
+
+impl TextCleanup {
+    pub fn is_empty(&self) -> bool {
+        !self.nfc && self.emoji.is_none()
+    }
+
+    /// Clean the text, and HTML text if there is any, of every comment and reply on the video.
+    pub fn apply(&self, video: &mut Video) {
+        for comment in &mut video.comments {
+            self.clean(&mut comment.text);
+            if let Some(html) = &mut comment.text_html {
+                self.clean(html);
+            }
+            for child in &mut comment.children {
+                self.clean(&mut child.text);
+                if let Some(html) = &mut child.text_html {
+                    self.clean(html);
+                }
+            }
+        }
+    }
+
+    fn clean(&self, text: &mut String) {
+        if self.nfc {
+            *text = text.nfc().collect();
+        }
+        if let Some(handling) = self.emoji {
+            *text = replace_emoji(text, handling);
+        }
+    }
+}
+
+/// Strip the emoji from the text or replace them with shortcodes. Each emoji is a whole grapheme,
+/// so sequences such as flags, families and skin tones are replaced at once rather than piece by
+/// piece.
+fn replace_emoji(text: &str, handling: EmojiHandling) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    for grapheme in text.graphemes(true) {
+        match (as_emoji(grapheme), handling) {
+            (None, _) => cleaned.push_str(grapheme),
+            (Some(_), EmojiHandling::Strip) => {}
+            (Some(emoji), EmojiHandling::Shortcode) => {
+                cleaned.push(':');
+                cleaned.push_str(&shortcode(emoji));
+                cleaned.push(':');
+            }
+        }
+    }
+    cleaned
+}
+
+/// The emoji a grapheme of text is, if it's one.
+pub fn as_emoji(grapheme: &str) -> Option<&'static Emoji> {
+    // © and ® are emoji too, but are far more often written as text.
+    emojis::get(grapheme).filter(|_| grapheme.chars().any(|c| c > '\u{ff}'))
+}
+
+/// The emoji's GitHub shortcode. Emoji with a skin tone other than the default share the
+/// shortcode of the default, and the few without any are named after their Unicode name.
+fn shortcode(emoji: &Emoji) -> String {
+    let base = emoji.with_skin_tone(SkinTone::Default).unwrap_or(emoji);
+    match base.shortcode() {
+        Some(code) => code.to_string(),
+        None => base
+            .name()
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("_"),
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::Mutex;
+
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+/// Caps the number of requests in flight at once. The cap is halved whenever the API says requests
+/// are coming too fast, and raised by one again after as many successes in a row as the cap, up to
+/// the configured maximum, so a high concurrency backs off on its own when it's too much.
+pub struct Throttle {
+    max: usize,
+    state: Mutex<State>,
+    released: Notify,
+}
+
+struct State {
+    limit: usize,
+    in_flight: usize,
+    /// Successful requests since the limit last changed.
+    successes: usize,
+}
+
+/// A slot for one request, given back when dropped.
+pub struct Permit<'a> {
+    throttle: &'a Throttle,
+}
+
+impl Throttle {
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            state: Mutex::new(State {
+                limit: max,
+                in_flight: 0,
+                successes: 0,
+            }),
+            released: Notify::new(),
+        }
+    }
+
+    /// Wait until fewer requests than the limit are in flight and take a slot.
+    pub async fn acquire(&self) -> Permit<'_> {
+        loop {
+            let released = self.released.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.in_flight < state.limit {
+                    state.in_flight += 1;
+                    return Permit { throttle: self };
+                }
+            }
+            released.await;
+        }
+    }
+
+    /// Record that a request was rejected for coming too fast.
+    pub fn throttled(&self) {
+        let mut state = self.state.lock().unwrap();
+        let limit = (state.limit / 2).max(1);
+        if limit < state.limit {
+            warn!(
+                from = state.limit,
+                to = limit,
+                "Requests are being rate limited, making fewer at once"
+            );
+        }
+        state.limit = limit;
+        state.successes = 0;
+    }
+
+    /// Record that a request succeeded.
+    pub fn succeeded(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.successes += 1;
+        if state.limit < self.max && state.successes >= state.limit {
+            state.limit += 1;
+            state.successes = 0;
+            debug!(
+                limit = state.limit,
+                "Raising the number of requests made at once"
+            );
+            self.released.notify_one();
+        }
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.throttle.state.lock().unwrap().in_flight -= 1;
+        self.throttle.released.notify_one();
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use clap::{Args, ValueEnum, ValueHint};
+use google_youtube3::chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::{csv::quote, output, Video};
+
+#[derive(Args)]
+pub struct TimelineArgs {
+    /// Name of a JSON file previously written by this tool.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+
+    /// Length of each bucket. Weeks start on Monday.
+    #[arg(long, value_enum, default_value_t = Bucket::Day)]
+    bucket: Bucket,
+
+    /// Print the buckets as JSON instead of CSV.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Bucket {
+    Day,
+    Week,
+}
+
+impl Bucket {
+    fn start(self, time: DateTime<Utc>) -> NaiveDate {
+        let date = time.date_naive();
+        match self {
+            Bucket::Day => date,
+            Bucket::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+        }
+    }
+}
+
+/// The comments and replies posted on one video in one day or week.
+#[derive(Debug, Clone, Serialize)]
+struct Row<'a> {
+    video_id: &'a str,
+    video_title: &'a str,
+    /// First day of the bucket.
+    start: NaiveDate,
+    /// Days from the video's publish date to the start of the bucket, 0 for the week it was
+    /// published in, or missing for files written before publish dates were recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    days_since_upload: Option<i64>,
+    comments: usize,
+    replies: usize,
+}
+
+/// Print how many comments and replies were posted on each video in each day or week, oldest
+/// first, to see how discussion develops after upload and spot late spikes. Only buckets with
+/// activity are listed. Comments from files written before publish dates were recorded are
+/// skipped.
+pub fn run(args: &TimelineArgs) -> Result<()> {
+    let videos = output::read(&args.input_name)?.videos;
+    let rows: Vec<Row> = videos
+        .iter()
+        .flat_map(|video| video_rows(video, args.bucket))
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+    println!("video_id,video_title,start,days_since_upload,comments,replies");
+    for row in &rows {
+        println!(
+            "{},{},{},{},{},{}",
+            quote(row.video_id),
+            quote(row.video_title),
+            row.start,
+            row.days_since_upload
+                .map(|days| days.to_string())
+                .unwrap_or_default(),
+            row.comments,
+            row.replies
+        );
+    }
+    Ok(())
+}
+
+fn video_rows(video: &Video, bucket: Bucket) -> Vec<Row<'_>> {
+    // Counts of comments and replies, keyed by the start of their bucket.
+    let mut buckets: BTreeMap<NaiveDate, (usize, usize)> = BTreeMap::new();
+    for comment in &video.comments {
+        if let Some(published_at) = comment.published_at {
+            buckets.entry(bucket.start(published_at)).or_default().0 += 1;
+        }
+        for child in &comment.children {
+            if let Some(published_at) = child.published_at {
+                buckets.entry(bucket.start(published_at)).or_default().1 += 1;
+            }
+        }
+    }
+
+    let uploaded = video.published_at.map(|p| p.date_naive());
+    buckets
+        .into_iter()
+        .map(|(start, (comments, replies))| Row {
+            video_id: &video.id,
+            video_title: &video.title,
+            start,
+            days_since_upload: uploaded.map(|uploaded| (start - uploaded).num_days().max(0)),
+            comments,
+            replies,
+        })
+        .collect()
+}
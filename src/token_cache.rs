@@ -0,0 +1,121 @@
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use google_youtube3::oauth2::storage::{TokenInfo, TokenStorage};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::Mutex;
+
+/// Environment variable the passphrase for an encrypted token cache is read from before prompting.
+pub const PASSPHRASE_VAR: &str = "YT_TOKEN_PASSPHRASE";
+
+/// PBKDF2 rounds turning the passphrase into a key, as recommended by OWASP for HMAC-SHA256.
+const KDF_ROUNDS: u32 = 600_000;
+
+/// Read the passphrase from [`PASSPHRASE_VAR`], or prompt for it on the terminal.
+pub fn passphrase() -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+    let passphrase = rpassword::prompt_password("Token cache passphrase: ")
+        .context("Unable to read the token cache passphrase")?;
+    if passphrase.is_empty() {
+        bail!("The token cache passphrase can't be empty");
+    }
+    Ok(passphrase)
+}
+
+/// The token cache file as written to disk. The tokens are encrypted with ChaCha20-Poly1305 under
+/// a key derived from the passphrase and salt.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Keeps OAuth tokens in a file encrypted with a passphrase, decrypting them only in memory. A new
+/// salt and nonce are used every time the file is written.
+pub struct EncryptedTokenCache {
+    path: PathBuf,
+    passphrase: String,
+    /// Tokens keyed by their space-separated scopes.
+    tokens: Mutex<HashMap<String, TokenInfo>>,
+}
+
+impl EncryptedTokenCache {
+    pub fn open(path: &str, passphrase: &str) -> Result<Self> {
+        let tokens = match fs::read_to_string(path) {
+            Ok(contents) => decrypt(&contents, passphrase)
+                .with_context(|| format!("Unable to read encrypted token cache {path}"))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e).with_context(|| format!("Unable to open {path}")),
+        };
+        Ok(Self {
+            path: PathBuf::from(path),
+            passphrase: passphrase.to_string(),
+            tokens: Mutex::new(tokens),
+        })
+    }
+}
+
+#[async_trait]
+impl TokenStorage for EncryptedTokenCache {
+    async fn set(&self, scopes: &[&str], token: TokenInfo) -> Result<()> {
+        let mut tokens = self.tokens.lock().await;
+        tokens.insert(scopes.join(" "), token);
+        let contents = encrypt(&tokens, &self.passphrase)?;
+        fs::write(&self.path, contents)
+            .with_context(|| format!("Unable to write {}", self.path.display()))
+    }
+
+    async fn get(&self, scopes: &[&str]) -> Option<TokenInfo> {
+        self.tokens.lock().await.get(&scopes.join(" ")).cloned()
+    }
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> ChaCha20Poly1305 {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, KDF_ROUNDS, &mut key);
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+fn encrypt(tokens: &HashMap<String, TokenInfo>, passphrase: &str) -> Result<String> {
+    let mut salt = [0; 16];
+    let mut nonce = [0; 12];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let plaintext = serde_json::to_vec(tokens)?;
+    let ciphertext = cipher(passphrase, &salt)
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| anyhow::anyhow!("Unable to encrypt the token cache"))?;
+    let envelope = Envelope {
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    };
+    Ok(serde_json::to_string_pretty(&envelope)?)
+}
+
+fn decrypt(contents: &str, passphrase: &str) -> Result<HashMap<String, TokenInfo>> {
+    let envelope: Envelope = serde_json::from_str(contents).context(
+        "It isn't encrypted, delete it or run auth --force to sign in again and encrypt it",
+    )?;
+    let salt = STANDARD.decode(envelope.salt)?;
+    let nonce = STANDARD.decode(envelope.nonce)?;
+    let ciphertext = STANDARD.decode(envelope.ciphertext)?;
+    if nonce.len() != 12 {
+        bail!("The nonce is the wrong length");
+    }
+    let plaintext = cipher(passphrase, &salt)
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase, or the file is corrupt"))?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
@@ -0,0 +1,199 @@
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::language,
+    warnings::{WarningCode, Warnings},
+    Video,
+};
+
+/// Number of texts sent in each translation request.
+const BATCH_SIZE: usize = 100;
+
+const GOOGLE_URL: &str = "https://translation.googleapis.com/language/translate/v2";
+
+/// Service that translates comments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// The Google Cloud Translation API, with the API key used for YouTube. The Cloud Translation
+    /// API has to be enabled in the key's project, and is billed separately.
+    Google,
+    /// A LibreTranslate server, such as one running locally, at --translate-url.
+    Libretranslate,
+}
+
+#[derive(Debug, Serialize)]
+struct LibreRequest<'a> {
+    q: &'a [&'a str],
+    source: &'static str,
+    target: &'a str,
+    format: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LibreResponse {
+    translated_text: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleResponse {
+    data: GoogleData,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleData {
+    translations: Vec<GoogleTranslation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleTranslation {
+    translated_text: String,
+    detected_source_language: Option<String>,
+}
+
+/// Translates comments that aren't already in the target language, keeping the original text.
+pub struct Translator {
+    http_client: reqwest::Client,
+    backend: Backend,
+    target: String,
+    url: String,
+    key: Option<String>,
+}
+
+impl Translator {
+    /// `key` is the API key for Google, and the optional one for LibreTranslate.
+    pub fn new(
+        http_client: reqwest::Client,
+        backend: Backend,
+        target: &str,
+        url: &str,
+        key: Option<String>,
+    ) -> Result<Self> {
+        if backend == Backend::Google && key.is_none() {
+            bail!("--translator google needs an API key, passed with --api-key or --credentials");
+        }
+        Ok(Self {
+            http_client,
+            backend,
+            target: target.to_string(),
+            url: url.trim_end_matches('/').to_string(),
+            key,
+        })
+    }
+
+    /// Set `text_translated` on every comment and reply on the video that isn't detected to be in
+    /// the target language already. If the backend fails, the video is left untranslated and a
+    /// warning is recorded.
+    pub async fn translate_video(&self, video: &mut Video, warnings: &mut Warnings) {
+        let texts: Vec<&str> = video
+            .comments
+            .iter()
+            .flat_map(|c| std::iter::once(&c.text).chain(c.children.iter().map(|c| &c.text)))
+            .map(String::as_str)
+            .collect();
+        let needed: Vec<usize> = texts
+            .iter()
+            .enumerate()
+            .filter(|(_, text)| {
+                !text.trim().is_empty()
+                    && language::detect(text).as_deref() != Some(self.target.as_str())
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut translated = vec![None; texts.len()];
+        for batch in needed.chunks(BATCH_SIZE) {
+            let batch_texts: Vec<&str> = batch.iter().map(|&i| texts[i]).collect();
+            match self.translate(&batch_texts).await {
+                Ok(results) => {
+                    for (&i, result) in batch.iter().zip(results) {
+                        translated[i] = result;
+                    }
+                }
+                Err(e) => {
+                    warnings.push(
+                        WarningCode::TranslationUnavailable,
+                        Some(&video.id),
+                        None,
+                        format!("Unable to translate comments: {e:#}"),
+                    );
+                    return;
+                }
+            }
+        }
+
+        let mut translated = translated.into_iter();
+        for comment in &mut video.comments {
+            comment.text_translated = translated.next().flatten();
+            for child in &mut comment.children {
+                child.text_translated = translated.next().flatten();
+            }
+        }
+    }
+
+    /// Translate the texts, returning `None` for those the backend found to be in the target
+    /// language already.
+    async fn translate(&self, texts: &[&str]) -> Result<Vec<Option<String>>> {
+        let results = match self.backend {
+            Backend::Google => {
+                let mut form: Vec<(&str, &str)> = texts.iter().map(|text| ("q", *text)).collect();
+                form.push(("target", &self.target));
+                form.push(("format", "text"));
+                let response: GoogleResponse = self
+                    .http_client
+                    .post(GOOGLE_URL)
+                    .query(&[("key", self.key.as_deref().unwrap_or_default())])
+                    .form(&form)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await
+                    .context("Unable to parse the Cloud Translation response")?;
+                response
+                    .data
+                    .translations
+                    .into_iter()
+                    .map(|t| {
+                        let same = t.detected_source_language.as_deref() == Some(&self.target);
+                        (!same).then_some(t.translated_text)
+                    })
+                    .collect::<Vec<_>>()
+            }
+            Backend::Libretranslate => {
+                let request = LibreRequest {
+                    q: texts,
+                    source: "auto",
+                    target: &self.target,
+                    format: "text",
+                    api_key: self.key.as_deref(),
+                };
+                let response: LibreResponse = self
+                    .http_client
+                    .post(format!("{}/translate", self.url))
+                    .json(&request)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await
+                    .context("Unable to parse the LibreTranslate response")?;
+                response.translated_text.into_iter().map(Some).collect()
+            }
+        };
+        if results.len() != texts.len() {
+            bail!(
+                "Sent {} texts to translate but got {} back",
+                texts.len(),
+                results.len()
+            );
+        }
+        Ok(results)
+    }
+}
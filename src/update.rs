@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use google_youtube3::chrono::{DateTime, Utc};
+
+use crate::{
+    diff::{child_key, parent_key},
+    edits::record_edits,
+    Video,
+};
+
+/// The videos of an earlier download being updated, to notice which of their comments have been
+/// deleted since.
+pub struct Archive {
+    videos: HashMap<String, Video>,
+    order: Vec<String>,
+}
+
+impl Archive {
+    pub fn new(videos: Vec<Video>) -> Self {
+        let order = videos.iter().map(|v| v.id.clone()).collect();
+        let videos = videos.into_iter().map(|v| (v.id.clone(), v)).collect();
+        Self { videos, order }
+    }
+
+    /// Take the archived copy of a video to compare with the one just fetched.
+    pub fn take(&mut self, video_id: &str) -> Option<Video> {
+        self.videos.remove(video_id)
+    }
+
+    /// The archived videos that weren't fetched again, such as ones no longer on the channel or
+    /// that failed, in the order they were archived.
+    pub fn into_remaining(mut self) -> Vec<Video> {
+        self.order
+            .iter()
+            .filter_map(|id| self.videos.remove(id))
+            .collect()
+    }
+}
+
+/// The comments YouTube returned for a video, noted before filters drop any, so that comments
+/// left out by a filter aren't mistaken for deleted ones.
+pub struct Fetched {
+    threads: HashSet<String>,
+    replies: HashSet<String>,
+    /// Threads whose replies weren't all fetched, whose missing replies may not be deleted.
+    incomplete: HashSet<String>,
+    comments_disabled: bool,
+}
+
+impl Fetched {
+    pub fn new(video: &Video) -> Self {
+        Self {
+            threads: video.comments.iter().map(parent_key).collect(),
+            replies: video
+                .comments
+                .iter()
+                .flat_map(|c| c.children.iter().map(child_key))
+                .collect(),
+            incomplete: video
+                .comments
+                .iter()
+                .filter(|c| c.missing_replies > 0)
+                .map(parent_key)
+                .collect(),
+            comments_disabled: video.comments_disabled,
+        }
+    }
+}
+
+/// Add the threads and replies of the archived copy of a video that YouTube no longer returned,
+/// marked as deleted at `now` unless they already were. Nothing is marked on a video whose
+/// comments have been turned off, since they're only hidden, or in a thread whose replies weren't
+/// all fetched. Those fetched again keep their archived edit history, along with the archived
+/// text if it has been edited since.
+pub fn merge_archived(video: &mut Video, archived: Video, fetched: &Fetched, now: DateTime<Utc>) {
+    let threads: HashMap<String, usize> = video
+        .comments
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (parent_key(c), i))
+        .collect();
+    for mut thread in archived.comments {
+        let key = parent_key(&thread);
+        if !fetched.threads.contains(&key) {
+            if !fetched.comments_disabled {
+                thread.deleted_detected_at.get_or_insert(now);
+            }
+            video.comments.push(thread);
+            continue;
+        }
+        // Threads fetched but dropped by a filter stay dropped, along with their replies.
+        let Some(&i) = threads.get(&key) else {
+            continue;
+        };
+        let comment = &mut video.comments[i];
+        record_edits(
+            &comment.text,
+            &mut comment.edit_history,
+            thread.text,
+            thread.updated_at,
+            thread.edit_history,
+            Some(now),
+        );
+        let replies: HashMap<String, usize> = comment
+            .children
+            .iter()
+            .enumerate()
+            .map(|(j, c)| (child_key(c), j))
+            .collect();
+        let incomplete = fetched.incomplete.contains(&key);
+        for mut reply in thread.children {
+            let key = child_key(&reply);
+            if let Some(&j) = replies.get(&key) {
+                let child = &mut video.comments[i].children[j];
+                record_edits(
+                    &child.text,
+                    &mut child.edit_history,
+                    reply.text,
+                    reply.updated_at,
+                    reply.edit_history,
+                    Some(now),
+                );
+                continue;
+            }
+            if fetched.replies.contains(&key) {
+                continue;
+            }
+            if !incomplete {
+                reply.deleted_detected_at.get_or_insert(now);
+            }
+            video.comments[i].children.push(reply);
+        }
+    }
+}
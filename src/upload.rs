@@ -0,0 +1,327 @@
+use std::{env, path::Path};
+
+use anyhow::{bail, Context, Result};
+use google_youtube3::chrono::Utc;
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use reqwest::Method;
+use sha2::{Digest, Sha256};
+use tokio::{fs::File, io::AsyncReadExt};
+use tracing::{info, warn};
+use url::Url;
+
+/// Size of each part of a multipart upload. Files no larger than this are uploaded in one request.
+const PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// A bucket and key prefix that finished output files are uploaded to, as given to `--upload`.
+#[derive(Debug, Clone)]
+pub struct Destination {
+    store: Store,
+    bucket: String,
+    /// Prepended to the file names to make their keys, ending in / unless empty.
+    prefix: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Store {
+    S3,
+    /// Google Cloud Storage, through its S3 compatible XML API.
+    Gcs,
+}
+
+impl Destination {
+    /// Parse an `s3://bucket/prefix/` or `gs://bucket/prefix/` URL. The prefix is taken as a
+    /// directory whether or not it ends in a slash.
+    pub fn parse(url: &str) -> Result<Self> {
+        let (store, rest) = if let Some(rest) = url.strip_prefix("s3://") {
+            (Store::S3, rest)
+        } else if let Some(rest) = url.strip_prefix("gs://") {
+            (Store::Gcs, rest)
+        } else {
+            bail!("{url} isn't an s3:// or gs:// URL");
+        };
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            bail!("{url} has no bucket");
+        }
+        let prefix = prefix.trim_matches('/');
+        Ok(Self {
+            store,
+            bucket: bucket.to_string(),
+            prefix: if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("{prefix}/")
+            },
+        })
+    }
+}
+
+/// Uploads files to a bucket, signing requests with AWS Signature Version 4, which Cloud Storage
+/// accepts too with HMAC keys.
+pub struct Uploader {
+    http: reqwest::Client,
+    destination: Destination,
+    /// URL of the bucket, ending in a slash, that keys are appended to.
+    bucket_url: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+}
+
+impl Uploader {
+    /// Read the credentials for the destination from the environment, so that missing ones are
+    /// noticed before the download rather than after it.
+    pub fn new(http: reqwest::Client, destination: Destination) -> Result<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let required = |name: &str| {
+            var(name).with_context(|| {
+                format!("{name} must be set to upload to {:?}", destination.bucket)
+            })
+        };
+        let uploader = match destination.store {
+            Store::S3 => {
+                let region = var("AWS_REGION")
+                    .or_else(|| var("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|| "us-east-1".to_string());
+                // S3 compatible stores are addressed by path, AWS by virtual host.
+                let bucket_url = match var("AWS_ENDPOINT_URL") {
+                    Some(endpoint) => {
+                        format!("{}/{}/", endpoint.trim_end_matches('/'), destination.bucket)
+                    }
+                    None => format!("https://{}.s3.{region}.amazonaws.com/", destination.bucket),
+                };
+                Self {
+                    bucket_url,
+                    region,
+                    access_key: required("AWS_ACCESS_KEY_ID")?,
+                    secret_key: required("AWS_SECRET_ACCESS_KEY")?,
+                    session_token: var("AWS_SESSION_TOKEN"),
+                    http,
+                    destination,
+                }
+            }
+            Store::Gcs => Self {
+                bucket_url: format!("https://storage.googleapis.com/{}/", destination.bucket),
+                region: "auto".to_string(),
+                access_key: required("YT_GCS_ACCESS_ID")?,
+                secret_key: required("YT_GCS_SECRET")?,
+                session_token: None,
+                http,
+                destination,
+            },
+        };
+        Ok(uploader)
+    }
+
+    /// Upload each file under the destination prefix, keyed by its file name. Files larger than a
+    /// part are uploaded in parts, so they're never held in memory whole.
+    pub async fn upload(&self, paths: &[&Path]) -> Result<()> {
+        for path in paths {
+            let name = path
+                .file_name()
+                .with_context(|| format!("{} has no file name", path.display()))?;
+            let key = format!("{}{}", self.destination.prefix, name.to_string_lossy());
+            let mut file = File::open(path)
+                .await
+                .with_context(|| format!("Unable to open {}", path.display()))?;
+            let size = file.metadata().await?.len();
+            if size as usize <= PART_SIZE {
+                let mut body = Vec::with_capacity(size as usize);
+                file.read_to_end(&mut body).await?;
+                self.send(Method::PUT, &key, &[], body).await?;
+            } else {
+                self.upload_parts(&key, &mut file).await?;
+            }
+            info!(bucket = self.destination.bucket, key, size, "Uploaded");
+        }
+        Ok(())
+    }
+
+    async fn upload_parts(&self, key: &str, file: &mut File) -> Result<()> {
+        let response = self
+            .send(Method::POST, key, &[("uploads", "")], vec![])
+            .await?;
+        let body = response.text().await?;
+        let upload_id = Regex::new(r"<UploadId>([^<]+)</UploadId>")
+            .unwrap()
+            .captures(&body)
+            .map(|captures| captures[1].to_string())
+            .context("The multipart upload wasn't given an id")?;
+
+        let result = async {
+            let mut etags = vec![];
+            loop {
+                let part = read_part(file).await?;
+                if part.is_empty() {
+                    break;
+                }
+                let number = (etags.len() + 1).to_string();
+                let query = [("partNumber", number.as_str()), ("uploadId", &upload_id)];
+                let response = self.send(Method::PUT, key, &query, part).await?;
+                let etag = response
+                    .headers()
+                    .get(reqwest::header::ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .context("An uploaded part wasn't given an ETag")?
+                    .to_string();
+                etags.push(etag);
+            }
+            let parts: String = etags
+                .iter()
+                .enumerate()
+                .map(|(i, etag)| {
+                    format!(
+                        "<Part><PartNumber>{}</PartNumber><ETag>{etag}</ETag></Part>",
+                        i + 1
+                    )
+                })
+                .collect();
+            let body = format!("<CompleteMultipartUpload>{parts}</CompleteMultipartUpload>");
+            let query = [("uploadId", upload_id.as_str())];
+            let response = self
+                .send(Method::POST, key, &query, body.into_bytes())
+                .await?;
+            // Completing can fail after the response has started, in which case the error is in
+            // the body.
+            let body = response.text().await?;
+            if body.contains("<Error>") {
+                bail!("Completing the upload of {key} failed: {body}");
+            }
+            Ok(())
+        }
+        .await;
+
+        if result.is_err() {
+            let query = [("uploadId", upload_id.as_str())];
+            if let Err(e) = self.send(Method::DELETE, key, &query, vec![]).await {
+                warn!(key, error = %e, "Unable to abort the multipart upload");
+            }
+        }
+        result
+    }
+
+    /// Make a signed request for the key, failing on any error status.
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response> {
+        let key: Vec<_> = key.split('/').map(uri_encode).collect();
+        let mut url = Url::parse(&format!("{}{}", self.bucket_url, key.join("/")))?;
+        let mut query: Vec<_> = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", uri_encode(name), uri_encode(value)))
+            .collect();
+        query.sort();
+        let query = query.join("&");
+        url.set_query((!query.is_empty()).then_some(query.as_str()));
+
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => bail!("{url} has no host"),
+        };
+        let now = Utc::now();
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex(&Sha256::digest(&body));
+
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", timestamp.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers: Vec<_> = headers.iter().map(|(name, _)| *name).collect();
+        let signed_headers = signed_headers.join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let canonical_request = format!(
+            "{method}\n{}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+            url.path()
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{timestamp}\n{scope}\n{}",
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut key = hmac(
+            format!("AWS4{}", self.secret_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            key = hmac(&key, part.as_bytes());
+        }
+        let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.access_key
+        );
+
+        let mut request = self
+            .http
+            .request(method, url.clone())
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .body(body);
+        // Host is set by the client from the URL.
+        for (name, value) in headers.into_iter().skip(1) {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Unable to reach {}", url.host_str().unwrap_or_default()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("Upload request failed with {status}: {body}");
+        }
+        Ok(response)
+    }
+}
+
+/// Read up to a part's worth of the file, less only at its end.
+async fn read_part(file: &mut File) -> Result<Vec<u8>> {
+    let mut part = vec![0; PART_SIZE];
+    let mut filled = 0;
+    while filled < PART_SIZE {
+        let read = file.read(&mut part[filled..]).await?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    part.truncate(filled);
+    Ok(part)
+}
+
+/// Percent-encode everything but the unreserved characters, as signing requires.
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+use clap::{Args, ValueHint};
+use serde::Serialize;
+
+use crate::{manifest::Integrity, output, Video};
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Name of a JSON file previously written by this tool.
+    #[arg(value_hint = ValueHint::FilePath)]
+    input_name: String,
+
+    /// Print the report as JSON instead of text.
+    #[arg(long)]
+    json: bool,
+}
+
+/// What was found checking an archive.
+#[derive(Debug, Default, Serialize)]
+struct Report {
+    /// Missing for files written before the header was added.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_version: Option<u32>,
+    videos: usize,
+    comments: usize,
+    replies: usize,
+    /// Videos listed in the errors, whose comments aren't in the file.
+    failed_videos: usize,
+    /// Comments and replies without an id, from files written before ids were recorded.
+    missing_ids: usize,
+    /// Comments and replies marked as deleted by --update.
+    deleted: usize,
+    integrity: Integrity,
+    problems: Vec<Problem>,
+}
+
+/// Something wrong with the archive, on the video and comment it was found on.
+#[derive(Debug, Serialize)]
+struct Problem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    video_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment_id: Option<String>,
+    message: String,
+}
+
+impl Report {
+    fn problem(&mut self, video_id: Option<&str>, comment_id: Option<&str>, message: String) {
+        self.problems.push(Problem {
+            video_id: video_id.map(str::to_string),
+            comment_id: comment_id.map(str::to_string),
+            message,
+        });
+    }
+
+    fn check_video(&mut self, video: &Video, comment_ids: &mut HashMap<String, String>) {
+        if video.comments_disabled && !video.comments.is_empty() {
+            self.problem(
+                Some(&video.id),
+                None,
+                "Video has comments turned off but has comments".to_string(),
+            );
+        }
+        for comment in &video.comments {
+            self.comments += 1;
+            self.check_comment(
+                video,
+                &comment.id,
+                comment.deleted_detected_at.is_some(),
+                comment_ids,
+            );
+            for child in &comment.children {
+                self.replies += 1;
+                self.check_comment(
+                    video,
+                    &child.id,
+                    child.deleted_detected_at.is_some(),
+                    comment_ids,
+                );
+                // Reply ids are the id of their thread, a period, and an id of their own.
+                let parent = child.id.split_once('.').map(|(parent, _)| parent);
+                if !comment.id.is_empty() && parent.is_some_and(|parent| parent != comment.id) {
+                    self.problem(
+                        Some(&video.id),
+                        Some(&child.id),
+                        format!("Reply is in thread {} but belongs to another", comment.id),
+                    );
+                }
+            }
+        }
+        self.integrity.add(video);
+    }
+
+    fn check_comment(
+        &mut self,
+        video: &Video,
+        id: &str,
+        deleted: bool,
+        comment_ids: &mut HashMap<String, String>,
+    ) {
+        if deleted {
+            self.deleted += 1;
+        }
+        if id.is_empty() {
+            self.missing_ids += 1;
+            return;
+        }
+        if let Some(first) = comment_ids.insert(id.to_string(), video.id.clone()) {
+            self.problem(
+                Some(&video.id),
+                Some(id),
+                format!("Comment id is repeated, first on video {first}"),
+            );
+        }
+    }
+}
+
+/// Check that a JSON output file can be trusted as an archive: that it parses as the current
+/// layout, that videos and comments have ids and aren't repeated, and that replies are in the
+/// threads they belong to. Also reports how complete the threads are. Fails if any problem is
+/// found.
+pub fn run(args: &ValidateArgs) -> Result<()> {
+    // Reading checks the file against the same types the schema is generated from.
+    let output = output::read(&args.input_name)?;
+
+    let mut report = Report {
+        schema_version: output.header.as_ref().map(|h| h.schema_version),
+        videos: output.videos.len(),
+        ..Report::default()
+    };
+    let mut video_ids = HashSet::new();
+    let mut comment_ids = HashMap::new();
+    for video in &output.videos {
+        if video.id.is_empty() {
+            report.problem(None, None, format!("Video {:?} has no id", video.title));
+        } else if !video_ids.insert(video.id.as_str()) {
+            report.problem(Some(&video.id), None, "Video is repeated".to_string());
+        }
+        report.check_video(video, &mut comment_ids);
+    }
+    let failed: HashSet<&str> = output.errors.iter().map(|e| e.video_id.as_str()).collect();
+    report.failed_videos = failed.len();
+    for video_id in failed {
+        if video_ids.contains(video_id) {
+            report.problem(
+                Some(video_id),
+                None,
+                "Video is listed as failed but is also in the file".to_string(),
+            );
+        }
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    if !report.problems.is_empty() {
+        bail!(
+            "Found {} problems in {}",
+            report.problems.len(),
+            args.input_name
+        );
+    }
+    Ok(())
+}
+
+fn print_report(report: &Report) {
+    match report.schema_version {
+        Some(version) => println!("Schema version {version}"),
+        None => println!("No header, written before schema versions were recorded"),
+    }
+    println!(
+        "{} videos, {} with comments turned off, {} failed",
+        report.videos, report.integrity.comments_disabled, report.failed_videos
+    );
+    println!(
+        "{} comments and {} replies, {} of them deleted",
+        report.comments, report.replies, report.deleted
+    );
+    println!(
+        "{} of {} threads incomplete, missing {} replies",
+        report.integrity.incomplete_threads,
+        report.integrity.threads,
+        report.integrity.missing_replies
+    );
+    if report.missing_ids > 0 {
+        println!(
+            "{} comments and replies have no id, so repeats can't be checked for",
+            report.missing_ids
+        );
+    }
+
+    if report.problems.is_empty() {
+        println!("No problems found");
+        return;
+    }
+    println!("\nProblems:");
+    for problem in &report.problems {
+        let location = match (&problem.video_id, &problem.comment_id) {
+            (Some(video), Some(comment)) => format!("video {video}, comment {comment}: "),
+            (Some(video), None) => format!("video {video}: "),
+            (None, _) => String::new(),
+        };
+        println!("  {location}{}", problem.message);
+    }
+}
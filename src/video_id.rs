@@ -0,0 +1,70 @@
+use url::Url;
+
+/// Hosts that serve YouTube videos, after any leading `www.` or `m.`.
+const HOSTS: [&str; 3] = ["youtube.com", "music.youtube.com", "youtube-nocookie.com"];
+
+/// Parse a video id, or a link to a video as copied from the browser: watch pages, youtu.be short
+/// links, Shorts, live streams and embeds.
+pub fn parse(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    if is_video_id(input) {
+        return Ok(input.to_string());
+    }
+    from_url(input).ok_or_else(|| format!("{input} isn't a YouTube video id or link to a video"))
+}
+
+/// Parse a value that's either a link to a video, turned into its id, or anything else, such as an
+/// id or part of a title, kept as it is. Values that look like YouTube links but don't point to a
+/// video are an error rather than matching nothing.
+pub fn parse_or_text(input: &str) -> Result<String, String> {
+    if looks_like_url(input) {
+        parse(input)
+    } else {
+        Ok(input.to_string())
+    }
+}
+
+fn looks_like_url(input: &str) -> bool {
+    let input = input.trim().to_lowercase();
+    input.contains("://")
+        || ["youtu.be/", "youtube.com/", "www.", "m.youtube.com/"]
+            .iter()
+            .any(|prefix| input.starts_with(prefix))
+}
+
+fn from_url(input: &str) -> Option<String> {
+    let url = if input.contains("://") {
+        Url::parse(input)
+    } else {
+        Url::parse(&format!("https://{input}"))
+    }
+    .ok()?;
+    let host = url.host_str()?.to_lowercase();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(&host);
+    let mut segments = url.path_segments()?.filter(|s| !s.is_empty());
+    let id = if host == "youtu.be" {
+        segments.next()?.to_string()
+    } else if HOSTS.contains(&host) {
+        match segments.next()? {
+            "watch" => url
+                .query_pairs()
+                .find(|(name, _)| name == "v")
+                .map(|(_, value)| value.into_owned())?,
+            "shorts" | "live" | "embed" | "v" => segments.next()?.to_string(),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    is_video_id(&id).then_some(id)
+}
+
+/// Whether the text is shaped like a video id: 11 letters, digits, hyphens or underscores.
+fn is_video_id(s: &str) -> bool {
+    s.len() == 11
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
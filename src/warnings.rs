@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Machine-readable identifier for a non-fatal anomaly encountered during a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCode {
+    /// A playlist item was missing its video id or title and was skipped.
+    MalformedPlaylistItem,
+    /// A top-level comment was missing its text or author and was skipped.
+    MalformedComment,
+    /// A reply was missing its text or author and was skipped.
+    MalformedReply,
+    /// The number of replies fetched for a thread didn't match the thread's reported reply count.
+    ReplyCountMismatch,
+    /// Comments are turned off on a video, so it was written with no comments.
+    CommentsUnavailable,
+    /// Fetching a video's comments was refused for a reason other than comments being turned off
+    /// or quota running out, such as the video having been made private, so it was written with
+    /// only the comments fetched before that.
+    CommentsForbidden,
+    /// The live chat replay of a stream couldn't be fetched, so it was written without one.
+    LiveChatUnavailable,
+    /// The captions of a video couldn't be fetched, so it was written without them.
+    CaptionsUnavailable,
+    /// The comments on a video couldn't be translated, so it was written without translations.
+    TranslationUnavailable,
+    /// The handles of the commenters on a video couldn't be looked up, so those not looked up
+    /// before were written without them.
+    HandlesUnavailable,
+    /// Listing the channel's videos failed partway while comments were being fetched, so the
+    /// videos after those listed were left out.
+    ListingFailed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Warning {
+    pub code: WarningCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_id: Option<String>,
+    pub message: String,
+}
+
+/// Collects warnings over the course of a run.
+#[derive(Debug, Default)]
+pub struct Warnings {
+    warnings: Vec<Warning>,
+}
+
+impl From<Vec<Warning>> for Warnings {
+    fn from(warnings: Vec<Warning>) -> Self {
+        Self { warnings }
+    }
+}
+
+impl Warnings {
+    pub fn push(
+        &mut self,
+        code: WarningCode,
+        video_id: Option<&str>,
+        comment_id: Option<&str>,
+        message: impl Into<String>,
+    ) {
+        let message = message.into();
+        warn!(?code, video_id, comment_id, "{message}");
+        self.warnings.push(Warning {
+            code,
+            video_id: video_id.map(str::to_string),
+            comment_id: comment_id.map(str::to_string),
+            message,
+        });
+    }
+
+    /// Add warnings collected separately, such as while fetching several threads at once.
+    pub fn extend(&mut self, other: Warnings) {
+        self.warnings.extend(other.warnings);
+    }
+
+    pub fn len(&self) -> usize {
+        self.warnings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn into_inner(self) -> Vec<Warning> {
+        self.warnings
+    }
+}
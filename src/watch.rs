@@ -0,0 +1,275 @@
+use std::{collections::HashSet, path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use google_youtube3::chrono::Local;
+use tracing::{error, info};
+
+use crate::{
+    config::WatchReload,
+    feed::Feed,
+    fetch_video,
+    filter::VideoFilters,
+    get_playlist_items,
+    metadata::MetadataCache,
+    metrics,
+    progress::Progress,
+    rotate::{RotatingFile, Rotation},
+    schedule::Schedule,
+    shutdown::{self, Hangup},
+    systemd,
+    warnings::Warnings,
+    webhook::Webhook,
+    Client, FetchOptions, Pipeline, Video,
+};
+
+/// Parse a polling interval such as `90s`, `30m`, `1h` or `1d`. A bare number is in seconds.
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("{s} isn't a number followed by s, m, h or d"))?;
+    let multiplier: u64 = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return Err(format!("Unknown unit {unit} in {s}, expected s, m, h or d")),
+    };
+    match number.checked_mul(multiplier) {
+        Some(0) => Err("The interval must be greater than zero".to_string()),
+        Some(seconds) => Ok(Duration::from_secs(seconds)),
+        None => Err(format!("{s} is too large")),
+    }
+}
+
+/// What the watcher has already seen, so that each poll only reports what's new.
+#[derive(Debug, Default)]
+pub struct Seen {
+    videos: HashSet<String>,
+    comments: HashSet<String>,
+}
+
+impl Seen {
+    /// Remember a video and every comment and reply on it.
+    pub fn add(&mut self, video: &Video) {
+        self.videos.insert(video.id.clone());
+        for comment in &video.comments {
+            self.comments.insert(comment.id.clone());
+            self.comments
+                .extend(comment.children.iter().map(|c| c.id.clone()));
+        }
+    }
+
+    /// Remove every thread and reply from the video that has been seen before, returning the rest
+    /// to be remembered once they've been reported. Threads that gained replies are kept with only
+    /// their new replies.
+    fn take_new(&self, video: &mut Video) -> Seen {
+        let mut new = Seen::default();
+        new.videos.insert(video.id.clone());
+        video.comments.retain_mut(|comment| {
+            let new_thread =
+                !self.comments.contains(&comment.id) && new.comments.insert(comment.id.clone());
+            comment.children.retain(|child| {
+                !self.comments.contains(&child.id) && new.comments.insert(child.id.clone())
+            });
+            new_thread || !comment.children.is_empty()
+        });
+        new
+    }
+
+    /// Remember what `take_new` returned.
+    fn remember(&mut self, new: Seen) {
+        self.videos.extend(new.videos);
+        self.comments.extend(new.comments);
+    }
+}
+
+/// Settings for watch mode.
+pub struct Watch<'a> {
+    pub client: &'a Client,
+    pub playlist_id: &'a str,
+    pub interval: Duration,
+    /// Polls at the times it gives instead of every interval, when set.
+    pub schedule: Option<Schedule>,
+    /// Number of the most recent uploads whose comments are checked on each poll.
+    pub recent: usize,
+    /// Reads the interval, schedule and number of recent uploads again on SIGHUP.
+    pub reload: WatchReload,
+    pub hangup: Hangup,
+    /// New uploads these filters leave out aren't checked.
+    pub video_filters: &'a VideoFilters,
+    /// Details of the videos looked up so far, so each upload is only looked up once.
+    pub metadata: MetadataCache,
+    pub updates_path: &'a Path,
+    /// Rotates the updates file once it's large, if set.
+    pub rotation: Option<Rotation>,
+    pub fetch_options: FetchOptions,
+    pub webhook: Option<Webhook>,
+    pub feed: Option<Feed>,
+}
+
+/// Poll the channel until interrupted, appending each video with new comments to the updates file
+/// as a line of JSON. Failed polls are logged and retried at the next interval rather than ending
+/// the watch.
+pub async fn run(mut watch: Watch<'_>, pipeline: &mut Pipeline, mut seen: Seen) -> Result<()> {
+    let mut updates = RotatingFile::open(watch.updates_path, watch.rotation)?;
+    // Videos written but not yet posted to the webhook, kept until a post succeeds so comments
+    // aren't lost to a failed one or to a poll that fails or is interrupted after writing them.
+    let mut undelivered = vec![];
+
+    let shutdown = shutdown::signal();
+    tokio::pin!(shutdown);
+    loop {
+        let wait = match &watch.schedule {
+            Some(schedule) => {
+                let now = Local::now();
+                let next = schedule
+                    .next_after(now)
+                    .context("The schedule has no more times to poll at")?;
+                info!(at = %next, "Waiting for the next poll");
+                systemd::status(&format!("Waiting for the next poll at {next}"));
+                (next - now).to_std().unwrap_or_default()
+            }
+            None => {
+                info!(interval = ?watch.interval, "Waiting for the next poll");
+                systemd::status(&format!("Waiting {:?} for the next poll", watch.interval));
+                watch.interval
+            }
+        };
+        systemd::waiting(true);
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            // The wait starts over with the new settings.
+            _ = watch.hangup.recv() => {
+                reload(&mut watch);
+                continue;
+            }
+            _ = &mut shutdown => break,
+        }
+        systemd::waiting(false);
+        systemd::status("Polling for new comments");
+
+        let result = tokio::select! {
+            result = poll(&mut watch, pipeline, &mut seen, &mut updates, &mut undelivered) => result,
+            _ = &mut shutdown => break,
+        };
+        match result {
+            Ok(videos) => {
+                metrics::record_success();
+                info!(videos, "Polled for new comments");
+            }
+            Err(e) => {
+                metrics::record_error();
+                error!("Poll failed: {e:#}");
+            }
+        }
+        deliver(&watch, &mut undelivered).await;
+    }
+
+    systemd::stopping();
+    deliver(&watch, &mut undelivered).await;
+    info!("Stopped watching");
+    Ok(())
+}
+
+/// Read the watch settings from the config file again, keeping the current ones if it can't be.
+fn reload(watch: &mut Watch<'_>) {
+    match watch.reload.reload() {
+        Ok(settings) => {
+            watch.interval = settings.interval;
+            watch.schedule = settings.schedule;
+            watch.recent = settings.recent;
+            info!(
+                interval = ?watch.interval,
+                schedule = watch.schedule.is_some(),
+                recent = watch.recent,
+                "Reloaded the watch settings"
+            );
+        }
+        Err(e) => error!("Unable to reload the watch settings, keeping the current ones: {e:#}"),
+    }
+}
+
+/// Post the videos written so far to the webhook, if there is one, keeping them for the next try
+/// if that fails.
+async fn deliver(watch: &Watch<'_>, undelivered: &mut Vec<Video>) {
+    let Some(webhook) = &watch.webhook else {
+        return;
+    };
+    match webhook.notify(undelivered).await {
+        Ok(()) => undelivered.clear(),
+        Err(e) => error!(
+            videos = undelivered.len(),
+            "{e:#}, trying again after the next poll"
+        ),
+    }
+}
+
+/// Check new uploads and recent videos once, returning the number of videos with new comments.
+/// Each one is added to the feed as it's written, and to `undelivered` for the webhook.
+async fn poll(
+    watch: &mut Watch<'_>,
+    pipeline: &mut Pipeline,
+    seen: &mut Seen,
+    updates: &mut RotatingFile,
+    undelivered: &mut Vec<Video>,
+) -> Result<usize> {
+    // Warnings are still logged as they're recorded, but there's no manifest to write them to.
+    let mut warnings = Warnings::default();
+    let mut items = get_playlist_items(watch.playlist_id, watch.client, &mut warnings).await?;
+    if !watch.video_filters.is_empty() {
+        watch.metadata.add_details(&mut items, watch.client).await?;
+        items.retain(|item| watch.video_filters.keep(item));
+    }
+
+    let mut updated = 0;
+    for (index, item) in items.iter().enumerate() {
+        if index >= watch.recent && seen.videos.contains(&item.video_id) {
+            continue;
+        }
+        // Chat replays and captions rarely change after upload, so they're only fetched for new
+        // uploads.
+        let mut options = watch.fetch_options.clone();
+        if seen.videos.contains(&item.video_id) {
+            options.live_chat = false;
+            options.captions = false;
+        }
+        let mut video = fetch_video(
+            item,
+            watch.client,
+            &options,
+            &Progress::quiet(),
+            &mut warnings,
+            None,
+            None,
+        )
+        .await?;
+        // Only remembered once written, so that comments aren't lost to a failure before that but
+        // reported by a later poll.
+        let new = seen.take_new(&mut video);
+        pipeline
+            .process(&mut video, watch.client, &mut warnings)
+            .await?;
+        pipeline.sort(&mut video);
+        if video.comments.is_empty() && video.live_chat.is_none() && video.captions.is_none() {
+            seen.remember(new);
+            continue;
+        }
+        metrics::record_video(&video);
+
+        updates.write_line(&serde_json::to_vec(&video)?)?;
+        pipeline.written(&video)?;
+        seen.remember(new);
+        updated += 1;
+        // A feed that fails to be rewritten still holds the entries, and writes them out with the
+        // next video's.
+        let video = [video];
+        if let Some(feed) = &mut watch.feed {
+            feed.add(&video)?;
+        }
+        if watch.webhook.is_some() {
+            undelivered.extend(video);
+        }
+    }
+    Ok(updated)
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use serde_json::Value;
+
+/// Download the HTML of a video's watch page, for the data the Data API doesn't provide.
+pub async fn fetch(http_client: &reqwest::Client, video_id: &str) -> Result<String> {
+    Ok(http_client
+        .get(format!("https://www.youtube.com/watch?v={video_id}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// Extract one of the JSON objects the page is rendered from, such as `ytInitialData`.
+pub fn embedded_json(page: &str, name: &str) -> Option<Value> {
+    let start = page.find(&format!("{name} = "))?;
+    let json_start = start + page[start..].find('{')?;
+    // The object is followed by the rest of the script, so only the first value is parsed.
+    serde_json::Deserializer::from_str(&page[json_start..])
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
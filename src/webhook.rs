@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{report::comment_url, Video};
+
+/// Times a failed POST is retried before the batch is left for the next poll.
+const MAX_RETRIES: u32 = 3;
+/// Wait before the first retry, doubling with each one after it.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    comments: Vec<NewComment<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct NewComment<'a> {
+    video_id: &'a str,
+    video_title: &'a str,
+    id: &'a str,
+    /// Set for replies, to the id of the comment being replied to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<&'a str>,
+    author_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author_channel_url: Option<&'a str>,
+    text: &'a str,
+    /// Link that opens the video with this comment highlighted.
+    link: String,
+}
+
+/// Posts newly discovered comments to a URL as JSON.
+pub struct Webhook {
+    http_client: reqwest::Client,
+    url: String,
+}
+
+impl Webhook {
+    pub fn new(http_client: reqwest::Client, url: String) -> Self {
+        Self { http_client, url }
+    }
+
+    /// Post one batch holding every comment and reply on the videos, retrying with backoff if the
+    /// request fails.
+    pub async fn notify(&self, videos: &[Video]) -> Result<()> {
+        let mut comments = vec![];
+        for video in videos {
+            let new_comment = |id, parent_id, author_name, author_channel_url, text| NewComment {
+                video_id: &video.id,
+                video_title: &video.title,
+                id,
+                parent_id,
+                author_name,
+                author_channel_url,
+                text,
+                link: comment_url(&video.id, id),
+            };
+            for comment in &video.comments {
+                comments.push(new_comment(
+                    &comment.id,
+                    None,
+                    &comment.author_name,
+                    comment.author_channel_url.as_deref(),
+                    &comment.text,
+                ));
+                for child in &comment.children {
+                    comments.push(new_comment(
+                        &child.id,
+                        Some(&comment.id),
+                        &child.author_name,
+                        child.author_channel_url.as_deref(),
+                        &child.text,
+                    ));
+                }
+            }
+        }
+        if comments.is_empty() {
+            return Ok(());
+        }
+
+        let payload = Payload { comments };
+        let mut retries = 0;
+        loop {
+            let result = self
+                .http_client
+                .post(&self.url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            match result {
+                Ok(_) => return Ok(()),
+                Err(e) if retries < MAX_RETRIES => {
+                    retries += 1;
+                    let wait = INITIAL_BACKOFF * 2u32.pow(retries - 1);
+                    warn!(
+                        url = self.url,
+                        retry = retries,
+                        ?wait,
+                        "Webhook failed, retrying: {e}"
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Unable to notify webhook {}", self.url))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+
+use crate::sink::{comment_records, Column, Field, Sink};
+use crate::Video;
+
+/// Most rows a worksheet can hold. Comments past this go on another sheet.
+const MAX_ROWS: u32 = 1_048_576;
+
+/// Title and width of a column.
+fn header(column: Column) -> (&'static str, f64) {
+    match column {
+        Column::VideoId => ("Video ID", 14.0),
+        Column::VideoTitle => ("Video Title", 40.0),
+        Column::CommentId => ("Comment ID", 28.0),
+        Column::Url => ("URL", 50.0),
+        Column::ParentId => ("Parent ID", 28.0),
+        Column::Author => ("Author", 24.0),
+        Column::AuthorChannelId => ("Author Channel ID", 26.0),
+        Column::AuthorChannelUrl => ("Author Channel URL", 30.0),
+        Column::AuthorProfileImageUrl => ("Author Profile Image URL", 30.0),
+        Column::IsOwner => ("Is Owner", 10.0),
+        Column::Text => ("Text", 80.0),
+        Column::PublishedAt => ("Published At", 20.0),
+        Column::Likes => ("Likes", 8.0),
+        Column::Sentiment => ("Sentiment", 10.0),
+        Column::Language => ("Language", 10.0),
+        Column::DuplicateOf => ("Duplicate Of", 28.0),
+        Column::SpamScore => ("Spam Score", 10.0),
+        Column::Mentions => ("Mentions", 24.0),
+        Column::Links => ("Links", 40.0),
+        Column::Timestamps => ("Timestamps", 16.0),
+        Column::TextTranslated => ("Translated Text", 80.0),
+    }
+}
+
+/// Writes every comment and reply as a row of a single Excel sheet, with the video each belongs to
+/// in its own columns. Sheets are written in constant memory mode, which streams rows to a
+/// temporary file instead of holding them until the workbook is saved.
+pub struct XlsxSink {
+    path: PathBuf,
+    workbook: Workbook,
+    columns: Vec<Column>,
+    sheets: usize,
+    row: u32,
+}
+
+impl XlsxSink {
+    pub fn new(path: &Path, columns: &[Column]) -> Result<Self> {
+        let mut sink = Self {
+            path: path.to_path_buf(),
+            workbook: Workbook::new(),
+            columns: Column::or_all(columns),
+            sheets: 0,
+            row: 0,
+        };
+        sink.add_sheet()?;
+        Ok(sink)
+    }
+
+    fn add_sheet(&mut self) -> Result<()> {
+        self.sheets += 1;
+        let name = match self.sheets {
+            1 => "Comments".to_string(),
+            n => format!("Comments ({n})"),
+        };
+        let bold = Format::new().set_bold();
+        let sheet = self.workbook.add_worksheet_with_constant_memory();
+        sheet.set_name(name)?;
+        for (col, column) in self.columns.iter().enumerate() {
+            let (title, width) = header(*column);
+            sheet.set_column_width(col as u16, width)?;
+            sheet.write_string_with_format(0, col as u16, title, &bold)?;
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        self.row = 1;
+        Ok(())
+    }
+
+    fn sheet(&mut self) -> Result<&mut Worksheet> {
+        Ok(self.workbook.worksheet_from_index(self.sheets - 1)?)
+    }
+}
+
+#[async_trait]
+impl Sink for XlsxSink {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        for record in comment_records(video) {
+            if self.row == MAX_ROWS {
+                self.add_sheet()?;
+            }
+            let row = self.row;
+            let fields: Vec<_> = self.columns.iter().map(|c| c.value(&record)).collect();
+            let sheet = self.sheet()?;
+            for (col, field) in fields.into_iter().enumerate() {
+                match field {
+                    Field::Text(text) => sheet.write_string(row, col as u16, text)?,
+                    Field::Number(number) => sheet.write_number(row, col as u16, number)?,
+                    Field::Empty => continue,
+                };
+            }
+            self.row += 1;
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.workbook.save(&self.path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::Write;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{sink::Sink, Video};
+
+/// Writes the videos as an XML document, streamed as videos arrive. The structure is documented
+/// in the README.
+pub struct XmlSink<W: Write> {
+    w: W,
+}
+
+impl<W: Write> XmlSink<W> {
+    pub fn new(channel: &str, mut w: W) -> Result<Self> {
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(w, r#"<channel handle="{}">"#, escape_xml(channel))?;
+        Ok(Self { w })
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> Sink for XmlSink<W> {
+    async fn write_video(&mut self, video: &Video) -> Result<()> {
+        let w = &mut self.w;
+        let disabled = if video.comments_disabled {
+            r#" comments-disabled="true""#
+        } else {
+            ""
+        };
+        writeln!(w, r#"  <video id="{}"{disabled}>"#, escape_xml(&video.id))?;
+        writeln!(w, "    <title>{}</title>", escape_xml(&video.title))?;
+        for comment in &video.comments {
+            let fields = Fields {
+                id: &comment.id,
+                author_name: &comment.author_name,
+                author_channel_id: comment.author_channel_id.as_deref(),
+                author_channel_url: comment.author_channel_url.as_deref(),
+                like_count: comment.like_count,
+                moderation_status: comment.moderation_status.as_deref(),
+                sentiment: comment.sentiment.as_ref().map(|s| s.compound),
+                language: comment.language.as_deref(),
+                duplicate_of: comment.duplicate_of.as_deref(),
+                text: &comment.text,
+                text_html: comment.text_html.as_deref(),
+            };
+            write_start(w, "    ", "thread", &fields)?;
+            for child in &comment.children {
+                let fields = Fields {
+                    id: &child.id,
+                    author_name: &child.author_name,
+                    author_channel_id: child.author_channel_id.as_deref(),
+                    author_channel_url: child.author_channel_url.as_deref(),
+                    like_count: child.like_count,
+                    moderation_status: child.moderation_status.as_deref(),
+                    sentiment: child.sentiment.as_ref().map(|s| s.compound),
+                    language: child.language.as_deref(),
+                    duplicate_of: child.duplicate_of.as_deref(),
+                    text: &child.text,
+                    text_html: child.text_html.as_deref(),
+                };
+                write_start(w, "      ", "reply", &fields)?;
+                writeln!(w, "      </reply>")?;
+            }
+            writeln!(w, "    </thread>")?;
+        }
+        writeln!(w, "  </video>")?;
+        w.flush()?;
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        writeln!(self.w, "</channel>")?;
+        self.w.flush()?;
+        Ok(())
+    }
+}
+
+/// What threads and replies have in common.
+struct Fields<'a> {
+    id: &'a str,
+    author_name: &'a str,
+    author_channel_id: Option<&'a str>,
+    author_channel_url: Option<&'a str>,
+    like_count: u32,
+    moderation_status: Option<&'a str>,
+    sentiment: Option<f64>,
+    language: Option<&'a str>,
+    duplicate_of: Option<&'a str>,
+    text: &'a str,
+    text_html: Option<&'a str>,
+}
+
+/// Write the opening tag of a thread or reply, along with its author and text, leaving the
+/// element open for any replies.
+fn write_start(w: &mut impl Write, indent: &str, tag: &str, fields: &Fields) -> Result<()> {
+    write!(w, r#"{indent}<{tag} id="{}""#, escape_xml(fields.id))?;
+    write!(w, r#" likes="{}""#, fields.like_count)?;
+    let attributes = [
+        ("moderation-status", fields.moderation_status),
+        ("language", fields.language),
+        ("duplicate-of", fields.duplicate_of),
+    ];
+    for (name, value) in attributes {
+        if let Some(value) = value {
+            write!(w, r#" {name}="{}""#, escape_xml(value))?;
+        }
+    }
+    if let Some(sentiment) = fields.sentiment {
+        write!(w, r#" sentiment="{sentiment}""#)?;
+    }
+    writeln!(w, ">")?;
+
+    write!(w, "{indent}  <author")?;
+    if let Some(channel_id) = fields.author_channel_id {
+        write!(w, r#" channel-id="{}""#, escape_xml(channel_id))?;
+    }
+    if let Some(url) = fields.author_channel_url {
+        write!(w, r#" url="{}""#, escape_xml(url))?;
+    }
+    writeln!(w, ">{}</author>", escape_xml(fields.author_name))?;
+    writeln!(w, "{indent}  <text>{}</text>", escape_xml(fields.text))?;
+    if let Some(html) = fields.text_html {
+        writeln!(w, "{indent}  <text-html>{}</text-html>", escape_xml(html))?;
+    }
+    Ok(())
+}
+
+/// Escape text for use in element content or attribute values. Control characters XML 1.0 can't
+/// represent, even escaped, are dropped.
+pub fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' || matches!(c, '\u{FFFE}' | '\u{FFFF}') => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}